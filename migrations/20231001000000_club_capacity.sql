@@ -0,0 +1,3 @@
+-- maximum number of approved members a club can take per academic year
+-- NULL means the club has no limit
+ALTER TABLE clubs ADD COLUMN capacity bigint CHECK (capacity IS NULL OR capacity >= 0);
@@ -0,0 +1,2 @@
+-- requests made after a club is full wait in line until a seat frees up
+ALTER TYPE submission_status ADD VALUE IF NOT EXISTS 'waitlisted';
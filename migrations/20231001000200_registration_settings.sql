@@ -0,0 +1,9 @@
+-- per academic year registration rules, years without a row use the defaults
+CREATE TABLE registration_settings (
+    year bigint PRIMARY KEY,
+    max_clubs_per_student bigint NOT NULL DEFAULT 1 CHECK (max_clubs_per_student >= 1),
+    created_at timestamptz DEFAULT now()
+);
+
+-- requests withdrawn by the system or by the student
+ALTER TYPE submission_status ADD VALUE IF NOT EXISTS 'withdrawn';
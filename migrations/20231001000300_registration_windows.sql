@@ -0,0 +1,14 @@
+-- registration schedule per academic year, optionally narrowed down to a single grade
+CREATE TABLE registration_windows (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    year bigint NOT NULL,
+    grade bigint CHECK (grade BETWEEN 1 AND 6),
+    opens_at timestamptz NOT NULL,
+    closes_at timestamptz NOT NULL,
+    created_at timestamptz DEFAULT now(),
+    CHECK (opens_at < closes_at)
+);
+
+CREATE INDEX registration_windows_year_idx ON registration_windows (year);
+
+ALTER TABLE registration_settings ADD COLUMN allow_leaving_after_close boolean NOT NULL DEFAULT false;
@@ -0,0 +1,23 @@
+ALTER TABLE registration_settings ADD COLUMN lottery_enabled boolean NOT NULL DEFAULT false;
+ALTER TABLE registration_settings ADD COLUMN max_preferences bigint NOT NULL DEFAULT 3 CHECK (max_preferences >= 1);
+
+-- ranked club choices for years allocated by lottery
+CREATE TABLE club_preferences (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    student_id bigint NOT NULL REFERENCES student (id) ON DELETE CASCADE,
+    year bigint NOT NULL,
+    club_id uuid NOT NULL REFERENCES clubs (id) ON DELETE CASCADE,
+    rank bigint NOT NULL CHECK (rank >= 1),
+    created_at timestamptz DEFAULT now(),
+    UNIQUE (student_id, year, rank),
+    UNIQUE (student_id, year, club_id)
+);
+
+CREATE TABLE allocation_runs (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    year bigint NOT NULL,
+    seed bigint NOT NULL,
+    created_by uuid REFERENCES users (id),
+    report jsonb NOT NULL,
+    created_at timestamptz DEFAULT now()
+);
@@ -0,0 +1,4 @@
+-- reason shown to the student when a request is declined, and a note only club staff can read
+ALTER TABLE club_members ADD COLUMN decline_reason_th text;
+ALTER TABLE club_members ADD COLUMN decline_reason_en text;
+ALTER TABLE club_members ADD COLUMN staff_note text;
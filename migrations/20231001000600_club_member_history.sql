@@ -0,0 +1,41 @@
+-- every membership_status a join request has been through, written by a trigger so that
+-- cascading changes (waitlist promotions, withdrawals on reaching the club limit) are kept too
+CREATE TABLE club_member_history (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    club_member_id uuid NOT NULL REFERENCES club_members (id) ON DELETE CASCADE,
+    club_id uuid NOT NULL,
+    student_id bigint NOT NULL,
+    year bigint NOT NULL,
+    previous_status submission_status,
+    membership_status submission_status NOT NULL,
+    -- users.id of whoever made the change, set per transaction through app.actor_id
+    actor uuid,
+    created_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX club_member_history_club_member_id_idx ON club_member_history (club_member_id);
+CREATE INDEX club_member_history_club_id_idx ON club_member_history (club_id, created_at);
+CREATE INDEX club_member_history_actor_idx ON club_member_history (actor, created_at);
+
+CREATE FUNCTION record_club_member_history() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'INSERT' OR OLD.membership_status IS DISTINCT FROM NEW.membership_status THEN
+        INSERT INTO club_member_history (club_member_id, club_id, student_id, year, previous_status, membership_status, actor)
+        VALUES (
+            NEW.id,
+            NEW.club_id,
+            NEW.student_id,
+            NEW.year,
+            CASE WHEN TG_OP = 'UPDATE' THEN OLD.membership_status END,
+            NEW.membership_status,
+            NULLIF(current_setting('app.actor_id', true), '')::uuid
+        );
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER club_members_history
+AFTER INSERT OR UPDATE OF membership_status ON club_members
+FOR EACH ROW EXECUTE FUNCTION record_club_member_history();
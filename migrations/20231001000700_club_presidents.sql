@@ -0,0 +1,4 @@
+-- the president is one of the staff of a club, at most one per club and year
+ALTER TABLE club_staffs ADD COLUMN is_president boolean NOT NULL DEFAULT false;
+
+CREATE UNIQUE INDEX club_staffs_president_idx ON club_staffs (club_id, year) WHERE is_president;
@@ -0,0 +1,11 @@
+-- teachers supervising a club in an academic year, they may do everything club staff can
+CREATE TABLE club_advisors (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    club_id uuid NOT NULL REFERENCES clubs (id) ON DELETE CASCADE,
+    teacher_id bigint NOT NULL,
+    year bigint NOT NULL,
+    created_at timestamptz DEFAULT now(),
+    UNIQUE (club_id, teacher_id, year)
+);
+
+CREATE INDEX club_advisors_teacher_id_idx ON club_advisors (teacher_id, year);
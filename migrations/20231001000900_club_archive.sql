@@ -0,0 +1,5 @@
+-- archived clubs are hidden from listings and closed for new requests, their past
+-- memberships and history are kept
+ALTER TABLE clubs ADD COLUMN archived_at timestamptz;
+
+CREATE INDEX clubs_active_idx ON clubs (id) WHERE archived_at IS NULL;
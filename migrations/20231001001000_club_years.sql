@@ -0,0 +1,17 @@
+-- clubs are re-created for every academic year, existing clubs belong to the academic year
+-- they were created in, using the default calendar of src/utils/date.rs (years start on
+-- 05-01 in +07:00), change the date below before migrating if ACADEMIC_YEAR_START differs
+ALTER TABLE clubs ADD COLUMN year bigint;
+UPDATE clubs SET year = EXTRACT(YEAR FROM local_created_at)::bigint
+    - CASE WHEN to_char(local_created_at, 'MM-DD') < '05-01' THEN 1 ELSE 0 END
+FROM (
+    -- clubs without a creation date count as created when the migration runs
+    SELECT id, (COALESCE(created_at, now()) AT TIME ZONE 'UTC') + interval '7 hours' AS local_created_at FROM clubs
+) AS created
+WHERE clubs.id = created.id;
+ALTER TABLE clubs ALTER COLUMN year SET NOT NULL;
+
+-- the club of the previous year this one was rolled over from, a club is rolled over at most once
+ALTER TABLE clubs ADD COLUMN rolled_over_from uuid UNIQUE REFERENCES clubs (id) ON DELETE SET NULL;
+
+CREATE INDEX clubs_year_idx ON clubs (year);
@@ -0,0 +1,6 @@
+-- clubs and memberships can be limited to one semester, NULL means the whole academic year
+ALTER TABLE clubs ADD COLUMN semester bigint CHECK (semester IN (1, 2));
+
+ALTER TABLE club_members ADD COLUMN semester bigint CHECK (semester IN (1, 2));
+
+CREATE INDEX club_members_student_year_semester_idx ON club_members (student_id, year, semester);
@@ -0,0 +1,26 @@
+CREATE TYPE attendance_status AS ENUM ('present', 'late', 'absent', 'excused');
+
+-- a dated meeting of a club, the semester follows from the date and decides which
+-- semester members are expected
+CREATE TABLE club_sessions (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    club_id uuid NOT NULL REFERENCES clubs (id) ON DELETE CASCADE,
+    date date NOT NULL,
+    semester bigint NOT NULL CHECK (semester IN (1, 2)),
+    title text,
+    created_by uuid REFERENCES users (id),
+    created_at timestamptz DEFAULT now(),
+    UNIQUE (club_id, date)
+);
+
+CREATE TABLE club_attendance (
+    session_id uuid NOT NULL REFERENCES club_sessions (id) ON DELETE CASCADE,
+    student_id bigint NOT NULL REFERENCES student (id) ON DELETE CASCADE,
+    status attendance_status NOT NULL,
+    note text,
+    recorded_by uuid REFERENCES users (id),
+    recorded_at timestamptz DEFAULT now(),
+    PRIMARY KEY (session_id, student_id)
+);
+
+CREATE INDEX club_attendance_student_id_idx ON club_attendance (student_id);
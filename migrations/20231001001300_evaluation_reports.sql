@@ -0,0 +1,14 @@
+ALTER TABLE registration_settings ADD COLUMN pass_attendance_rate double precision NOT NULL DEFAULT 0.8
+    CHECK (pass_attendance_rate >= 0 AND pass_attendance_rate <= 1);
+
+-- the pass/fail activity grades of a classroom, signed off by one of its homeroom advisors
+-- and locked by an admin, results are kept as they were signed off
+CREATE TABLE evaluation_reports (
+    classroom_id bigint PRIMARY KEY REFERENCES classroom (id) ON DELETE CASCADE,
+    signed_off_by bigint REFERENCES teacher (id),
+    signed_off_at timestamptz,
+    locked_by uuid REFERENCES users (id),
+    locked_at timestamptz,
+    results jsonb,
+    created_at timestamptz DEFAULT now()
+);
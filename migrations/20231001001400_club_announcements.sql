@@ -0,0 +1,17 @@
+CREATE TYPE announcement_visibility AS ENUM ('public', 'members');
+
+CREATE TABLE club_announcements (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    club_id uuid NOT NULL REFERENCES clubs (id) ON DELETE CASCADE,
+    title_th text NOT NULL,
+    title_en text,
+    body_th text NOT NULL,
+    body_en text,
+    pinned boolean NOT NULL DEFAULT false,
+    visibility announcement_visibility NOT NULL DEFAULT 'members',
+    created_by uuid REFERENCES users (id),
+    created_at timestamptz NOT NULL DEFAULT now(),
+    updated_at timestamptz
+);
+
+CREATE INDEX club_announcements_club_id_idx ON club_announcements (club_id, pinned DESC, created_at DESC);
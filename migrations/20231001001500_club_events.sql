@@ -0,0 +1,25 @@
+-- room follows organizations.main_room, events without a room take place in the club's main room
+CREATE TABLE club_events (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    club_id uuid NOT NULL REFERENCES clubs (id) ON DELETE CASCADE,
+    title_th text NOT NULL,
+    title_en text,
+    description_th text,
+    description_en text,
+    starts_at timestamptz NOT NULL,
+    ends_at timestamptz NOT NULL,
+    room text,
+    created_by uuid REFERENCES users (id),
+    created_at timestamptz NOT NULL DEFAULT now(),
+    updated_at timestamptz,
+    CHECK (ends_at > starts_at)
+);
+
+CREATE INDEX club_events_club_id_starts_at_idx ON club_events (club_id, starts_at);
+
+-- calendar apps can't send a bearer token, so the personal feed is reached through a secret url
+CREATE TABLE calendar_tokens (
+    user_id uuid PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE,
+    token uuid NOT NULL UNIQUE DEFAULT gen_random_uuid(),
+    created_at timestamptz NOT NULL DEFAULT now()
+);
@@ -1,10 +1,16 @@
 use actix_cors::Cors;
 use actix_web::middleware::Logger;
 use actix_web::{http::header, web, App, HttpServer};
+use clap::{Parser, Subcommand};
 use dotenv::dotenv;
 // use openssl::ssl::{SslAcceptor, SslFiletype, SslMethod};
 use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
 use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use structs::club_import::{ClubImport, ClubImportOptions};
+use utils::date::AcademicCalendar;
+use utils::pdf::PdfFont;
 
 mod routes;
 mod structs;
@@ -13,10 +19,37 @@ mod utils;
 pub struct AppState {
     db: Pool<Postgres>,
     jwt_secret: String,
+    // None when PDF_FONT_PATH is not set
+    pdf_font: Option<Arc<PdfFont>>,
+}
+
+#[derive(Parser)]
+#[command(about = "MySK club registrar API")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the API server, the default when no command is given
+    Serve,
+    /// Import clubs and their staff from a CSV file, the same as POST /admin/clubs/import
+    ImportClubs {
+        file: PathBuf,
+        /// Academic year of the clubs, the current one when left out
+        #[arg(long)]
+        year: Option<i64>,
+        /// Validate the file without importing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
+    let cli = Cli::parse();
+
     if std::env::var_os("RUST_LOG").is_none() {
         std::env::set_var("RUST_LOG", "actix_web=info");
     }
@@ -24,7 +57,10 @@ async fn main() -> std::io::Result<()> {
     env_logger::init();
 
     let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-    let jwt_secret = env::var("JWT_SECRET").expect("JWT_SECRET must be set");
+
+    let academic_calendar =
+        AcademicCalendar::from_env().expect("the academic calendar is misconfigured");
+    AcademicCalendar::init(academic_calendar);
 
     let pool = match PgPoolOptions::new()
         .max_connections(5)
@@ -41,6 +77,25 @@ async fn main() -> std::io::Result<()> {
         }
     };
 
+    if let Some(Command::ImportClubs {
+        file,
+        year,
+        dry_run,
+    }) = cli.command
+    {
+        return import_clubs(&pool, &file, ClubImportOptions { year, dry_run }).await;
+    }
+
+    let jwt_secret = env::var("JWT_SECRET").expect("JWT_SECRET must be set");
+
+    let pdf_font = PdfFont::from_env()
+        .expect("the pdf font is misconfigured")
+        .map(Arc::new);
+
+    if pdf_font.is_none() {
+        println!("⚠️ PDF_FONT_PATH is not set, pdf rosters and certificates are unavailable");
+    }
+
     // let mut builder = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
 
     // builder
@@ -66,6 +121,7 @@ async fn main() -> std::io::Result<()> {
             .app_data(web::Data::new(AppState {
                 db: pool.clone(),
                 jwt_secret: jwt_secret.clone(),
+                pdf_font: pdf_font.clone(),
             }))
             .configure(routes::config)
             .wrap(cors)
@@ -76,3 +132,45 @@ async fn main() -> std::io::Result<()> {
     .run()
     .await
 }
+
+async fn import_clubs(
+    pool: &Pool<Postgres>,
+    file: &Path,
+    options: ClubImportOptions,
+) -> std::io::Result<()> {
+    let csv = std::fs::read(file)?;
+
+    let report = match ClubImport::run(pool, &csv, &options).await {
+        Ok(report) => report,
+        Err(e) => {
+            println!("🔥 Failed to import clubs: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    for row in report.rows.iter() {
+        for error in row.errors.iter() {
+            println!("row {}: {}", row.row, error);
+        }
+    }
+
+    if report.has_errors() {
+        println!("🔥 Nothing was imported, fix the rows above and try again");
+        std::process::exit(1);
+    }
+
+    match report.applied {
+        true => println!(
+            "✅ Imported {} clubs into {}",
+            report.rows.len(),
+            report.year
+        ),
+        false => println!(
+            "✅ {} clubs can be imported into {}",
+            report.rows.len(),
+            report.year
+        ),
+    }
+
+    Ok(())
+}
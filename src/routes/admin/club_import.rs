@@ -0,0 +1,87 @@
+use actix_web::{post, web, HttpRequest, HttpResponse, Responder};
+use serde_qs;
+use uuid::Uuid;
+
+use crate::structs::{
+    auth::Admin,
+    club_import::{ClubImport, ClubImportError, ClubImportOptions, ClubImportReport},
+    common::{ErrorResponseType, ErrorType, MetadataType, ResponseType},
+};
+
+use crate::AppState;
+
+// the body is the csv itself, the year and dry_run go in the query string
+#[post("/admin/clubs/import")]
+pub async fn import_clubs(
+    data: web::Data<AppState>,
+    _admin: Admin,
+    request: HttpRequest,
+    body: web::Bytes,
+) -> impl Responder {
+    let pool = &data.db;
+
+    let options = match serde_qs::from_str::<ClubImportOptions>(request.query_string()) {
+        Ok(options) => options,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: e.to_string(),
+                    source: "/admin/clubs/import".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    match ClubImport::run(pool, &body, &options).await {
+        // the report carries the error of every row, nothing was imported
+        Ok(report) if report.has_errors() => {
+            let response: ResponseType<ClubImportReport, _> =
+                ResponseType::new(report, None::<String>, None::<MetadataType>);
+
+            HttpResponse::UnprocessableEntity().json(response)
+        }
+        Ok(report) => {
+            let response: ResponseType<ClubImportReport, _> =
+                ResponseType::new(report, None::<String>, None::<MetadataType>);
+
+            match options.dry_run {
+                true => HttpResponse::Ok().json(response),
+                false => HttpResponse::Created().json(response),
+            }
+        }
+        Err(e @ ClubImportError::Csv(_)) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: e.to_string(),
+                    source: "/admin/clubs/import".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::BadRequest().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: "/admin/clubs/import".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use actix_web::{delete, patch, put, web, HttpResponse, Responder};
+use uuid::Uuid;
+
+use crate::structs::{
+    auth::Admin,
+    clubs::{Club, ClubError, ClubSortableField, QueryableClub, UpdatableClub},
+    common::{ErrorResponseType, ErrorType, MetadataType, RequestType, ResponseType},
+};
+
+use crate::AppState;
+
+#[patch("/admin/clubs/{club_id}")]
+pub async fn update_any_club_by_id(
+    data: web::Data<AppState>,
+    club_id: web::Path<Uuid>,
+    _admin: Admin,
+    request: web::Json<RequestType<UpdatableClub, QueryableClub, ClubSortableField>>,
+) -> impl Responder {
+    let pool = &data.db;
+    let club_id = club_id.into_inner();
+
+    let data = match &request.data {
+        Some(data) => data,
+        None => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: "request body is empty".to_string(),
+                    source: format!("/admin/clubs/{club_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    let club = Club::update_by_id(
+        pool,
+        club_id,
+        data,
+        request.fetch_level.clone(),
+        request.descendant_fetch_level.clone(),
+    )
+    .await;
+
+    match club {
+        Ok(club) => {
+            let response: ResponseType<Club, _> =
+                ResponseType::new(club, None::<String>, None::<MetadataType>);
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/admin/clubs/{club_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::NotFound().json(response)
+        }
+    }
+}
+
+// archived clubs disappear from GET /clubs and stop taking requests, their history is kept
+#[put("/admin/clubs/{club_id}/archive")]
+pub async fn archive_club_by_id(
+    data: web::Data<AppState>,
+    club_id: web::Path<Uuid>,
+    _admin: Admin,
+) -> impl Responder {
+    let pool = &data.db;
+    let club_id = club_id.into_inner();
+
+    match Club::set_archived(pool, club_id, true).await {
+        Ok(()) => (),
+        Err(sqlx::Error::RowNotFound) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: "club not found".to_string(),
+                    source: format!("/admin/clubs/{club_id}/archive"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::NotFound().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/admin/clubs/{club_id}/archive"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    match Club::get_by_id(pool, club_id, None, None).await {
+        Ok(club) => {
+            let response: ResponseType<Club, _> =
+                ResponseType::new(club, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/admin/clubs/{club_id}/archive"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[delete("/admin/clubs/{club_id}/archive")]
+pub async fn unarchive_club_by_id(
+    data: web::Data<AppState>,
+    club_id: web::Path<Uuid>,
+    _admin: Admin,
+) -> impl Responder {
+    let pool = &data.db;
+    let club_id = club_id.into_inner();
+
+    match Club::set_archived(pool, club_id, false).await {
+        Ok(()) => (),
+        Err(sqlx::Error::RowNotFound) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: "club not found".to_string(),
+                    source: format!("/admin/clubs/{club_id}/archive"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::NotFound().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/admin/clubs/{club_id}/archive"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    match Club::get_by_id(pool, club_id, None, None).await {
+        Ok(club) => {
+            let response: ResponseType<Club, _> =
+                ResponseType::new(club, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/admin/clubs/{club_id}/archive"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[delete("/admin/clubs/{club_id}")]
+pub async fn delete_club_by_id(
+    data: web::Data<AppState>,
+    club_id: web::Path<Uuid>,
+    _admin: Admin,
+) -> impl Responder {
+    let pool = &data.db;
+    let club_id = club_id.into_inner();
+
+    match Club::delete_by_id(pool, club_id).await {
+        Ok(()) => {
+            let response: ResponseType<Uuid, _> =
+                ResponseType::new(club_id, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(ClubError::Database(sqlx::Error::RowNotFound)) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: "club not found".to_string(),
+                    source: format!("/admin/clubs/{club_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::NotFound().json(response)
+        }
+        Err(e @ ClubError::HasMembers) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 409,
+                    error_type: "conflict".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/admin/clubs/{club_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::Conflict().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/admin/clubs/{club_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
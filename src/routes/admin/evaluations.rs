@@ -0,0 +1,150 @@
+use actix_web::{delete, put, web, HttpResponse, Responder};
+use uuid::Uuid;
+
+use crate::structs::{
+    auth::Admin,
+    classroom::Classroom,
+    common::{ErrorResponseType, ErrorType, MetadataType, RequestType, ResponseType},
+    evaluation::{EvaluationError, EvaluationReport},
+};
+
+use crate::AppState;
+
+#[put("/admin/evaluations/classrooms/{classroom_id}/lock")]
+pub async fn lock_evaluation_report(
+    data: web::Data<AppState>,
+    classroom_id: web::Path<u32>,
+    Admin(user): Admin,
+    request: web::Json<RequestType<String, String, String>>,
+) -> impl Responder {
+    let pool = &data.db;
+    let classroom_id = classroom_id.into_inner();
+
+    let roster = match Classroom::get_roster(pool, classroom_id).await {
+        Ok(roster) => roster,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/admin/evaluations/classrooms/{classroom_id}/lock"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::NotFound().json(response);
+        }
+    };
+
+    match EvaluationReport::lock(pool, &roster, user.id).await {
+        Ok(()) => (),
+        Err(e @ (EvaluationError::Locked | EvaluationError::NotSignedOff)) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 409,
+                    error_type: "conflict".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/admin/evaluations/classrooms/{classroom_id}/lock"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Conflict().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/admin/evaluations/classrooms/{classroom_id}/lock"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    let report = EvaluationReport::get_by_roster(
+        pool,
+        &roster,
+        request.fetch_level.clone(),
+        request.descendant_fetch_level.clone(),
+    )
+    .await;
+
+    match report {
+        Ok(report) => {
+            let response: ResponseType<EvaluationReport, _> =
+                ResponseType::new(report, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/admin/evaluations/classrooms/{classroom_id}/lock"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[delete("/admin/evaluations/classrooms/{classroom_id}/lock")]
+pub async fn unlock_evaluation_report(
+    data: web::Data<AppState>,
+    classroom_id: web::Path<u32>,
+    _admin: Admin,
+) -> impl Responder {
+    let pool = &data.db;
+    let classroom_id = classroom_id.into_inner();
+
+    match EvaluationReport::unlock(pool, classroom_id).await {
+        Ok(()) => {
+            let response: ResponseType<u32, _> =
+                ResponseType::new(classroom_id, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e @ EvaluationError::NotLocked) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 409,
+                    error_type: "conflict".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/admin/evaluations/classrooms/{classroom_id}/lock"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::Conflict().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/admin/evaluations/classrooms/{classroom_id}/lock"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
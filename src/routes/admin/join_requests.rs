@@ -0,0 +1,116 @@
+use actix_web::{patch, web, HttpResponse, Responder};
+use uuid::Uuid;
+
+use crate::structs::{
+    auth::Admin,
+    club_request::{
+        ClubRequest, ClubRequestError, ClubRequestSortableField, QueryableClubRequest,
+        UpdatableClubRequest,
+    },
+    clubs::SubmissionStatus,
+    common::{ErrorResponseType, ErrorType, MetadataType, RequestType, ResponseType},
+};
+
+use crate::AppState;
+
+// unlike PATCH /join_requests/{id} any status can be set here, regardless of the current
+// status, the club capacity or the per student limit
+#[patch("/admin/join_requests/{join_request_id}")]
+pub async fn override_club_request(
+    data: web::Data<AppState>,
+    join_request_id: web::Path<Uuid>,
+    Admin(user): Admin,
+    request_body: web::Json<
+        RequestType<UpdatableClubRequest, QueryableClubRequest, ClubRequestSortableField>,
+    >,
+) -> impl Responder {
+    let pool = &data.db;
+    let join_request_id = join_request_id.into_inner();
+
+    let data = match &request_body.data {
+        Some(data) => data,
+        None => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: "data is required".to_string(),
+                    source: format!("/admin/join_requests/{join_request_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    let detail = match (&data.membership_status, &data.decline_reason) {
+        (Some(SubmissionStatus::Declined), _) => None,
+        (_, Some(_)) => Some("decline_reason can only be given when declining".to_string()),
+        (None, None) if data.staff_note.is_none() => Some("nothing to update".to_string()),
+        _ => None,
+    };
+
+    if let Some(detail) = detail {
+        let response: ErrorResponseType = ErrorResponseType::new(
+            ErrorType {
+                id: Uuid::new_v4().to_string(),
+                code: 400,
+                error_type: "bad_request".to_string(),
+                detail,
+                source: format!("/admin/join_requests/{join_request_id}"),
+            },
+            None::<MetadataType>,
+        );
+
+        return HttpResponse::BadRequest().json(response);
+    }
+
+    let res = ClubRequest::override_request(
+        pool,
+        join_request_id,
+        data,
+        user.id,
+        request_body.fetch_level.clone(),
+        request_body.descendant_fetch_level.clone(),
+    )
+    .await;
+
+    match res {
+        Ok(club_request) => {
+            let response: ResponseType<ClubRequest, _> =
+                ResponseType::new(club_request, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(ClubRequestError::Database(sqlx::Error::RowNotFound)) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: ClubRequestError::NotFound.to_string(),
+                    source: format!("/admin/join_requests/{join_request_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::NotFound().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/admin/join_requests/{join_request_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
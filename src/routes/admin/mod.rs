@@ -0,0 +1,6 @@
+pub(crate) mod club_import;
+pub(crate) mod clubs;
+pub(crate) mod evaluations;
+pub(crate) mod join_requests;
+pub(crate) mod registration_settings;
+pub(crate) mod rollover;
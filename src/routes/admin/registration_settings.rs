@@ -0,0 +1,237 @@
+use actix_web::{delete, get, put, web, HttpRequest, HttpResponse, Responder};
+use serde_qs;
+use uuid::Uuid;
+
+use crate::structs::{
+    auth::Admin,
+    common::{ErrorResponseType, ErrorType, MetadataType, RequestType, ResponseType},
+    registration::{
+        QueryableRegistrationSettings, RegistrationSettings, UpdatableRegistrationSettings,
+    },
+};
+
+use crate::AppState;
+
+#[get("/admin/registration_settings")]
+pub async fn query_registration_settings(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    _admin: Admin,
+) -> impl Responder {
+    let pool = &data.db;
+
+    let request_query = serde_qs::from_str::<
+        RequestType<RegistrationSettings, QueryableRegistrationSettings, String>,
+    >(&request.query_string());
+
+    let request_query = match request_query {
+        Ok(request_query) => request_query,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: e.to_string(),
+                    source: "/admin/registration_settings".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    let filter = request_query
+        .filter
+        .as_ref()
+        .and_then(|filter| filter.data.as_ref());
+
+    match RegistrationSettings::query(pool, filter).await {
+        Ok(settings) => {
+            let response: ResponseType<Vec<RegistrationSettings>, _> =
+                ResponseType::new(settings, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: "/admin/registration_settings".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+// years without settings of their own return the defaults
+#[get("/admin/registration_settings/{year}")]
+pub async fn get_registration_settings_by_year(
+    data: web::Data<AppState>,
+    year: web::Path<i64>,
+    _admin: Admin,
+) -> impl Responder {
+    let pool = &data.db;
+    let year = year.into_inner();
+
+    match RegistrationSettings::get_by_year(pool, year).await {
+        Ok(settings) => {
+            let response: ResponseType<RegistrationSettings, _> =
+                ResponseType::new(settings, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/admin/registration_settings/{year}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[put("/admin/registration_settings/{year}")]
+pub async fn update_registration_settings(
+    data: web::Data<AppState>,
+    year: web::Path<i64>,
+    _admin: Admin,
+    request: web::Json<
+        RequestType<UpdatableRegistrationSettings, QueryableRegistrationSettings, String>,
+    >,
+) -> impl Responder {
+    let pool = &data.db;
+    let year = year.into_inner();
+
+    let data = match &request.data {
+        Some(data) => data,
+        None => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: "request body is empty".to_string(),
+                    source: format!("/admin/registration_settings/{year}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    let detail = match (data.max_clubs_per_student, data.max_preferences) {
+        (Some(max_clubs_per_student), _) if max_clubs_per_student < 1 => {
+            Some("max_clubs_per_student must be at least 1".to_string())
+        }
+        (_, Some(max_preferences)) if max_preferences < 1 => {
+            Some("max_preferences must be at least 1".to_string())
+        }
+        _ => match data.pass_attendance_rate {
+            Some(rate) if !(0.0..=1.0).contains(&rate) => {
+                Some("pass_attendance_rate must be between 0 and 1".to_string())
+            }
+            _ => None,
+        },
+    };
+
+    if let Some(detail) = detail {
+        let response: ErrorResponseType = ErrorResponseType::new(
+            ErrorType {
+                id: Uuid::new_v4().to_string(),
+                code: 400,
+                error_type: "bad_request".to_string(),
+                detail,
+                source: format!("/admin/registration_settings/{year}"),
+            },
+            None::<MetadataType>,
+        );
+
+        return HttpResponse::BadRequest().json(response);
+    }
+
+    match RegistrationSettings::update_by_year(pool, year, data).await {
+        Ok(settings) => {
+            let response: ResponseType<RegistrationSettings, _> =
+                ResponseType::new(settings, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/admin/registration_settings/{year}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[delete("/admin/registration_settings/{year}")]
+pub async fn delete_registration_settings(
+    data: web::Data<AppState>,
+    year: web::Path<i64>,
+    _admin: Admin,
+) -> impl Responder {
+    let pool = &data.db;
+    let year = year.into_inner();
+
+    match RegistrationSettings::delete_by_year(pool, year).await {
+        Ok(()) => {
+            let response: ResponseType<i64, _> =
+                ResponseType::new(year, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(sqlx::Error::RowNotFound) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: format!("no registration settings for {year}"),
+                    source: format!("/admin/registration_settings/{year}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::NotFound().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/admin/registration_settings/{year}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use actix_web::{post, web, HttpResponse, Responder};
+use uuid::Uuid;
+
+use crate::structs::{
+    auth::Admin,
+    clubs::{ClubSortableField, QueryableClub},
+    common::{ErrorResponseType, ErrorType, MetadataType, RequestType, ResponseType},
+    rollover::{CreatableRollover, Rollover, RolloverError, RolloverReport},
+};
+
+use crate::AppState;
+
+#[post("/admin/rollover")]
+pub async fn create_rollover(
+    data: web::Data<AppState>,
+    Admin(user): Admin,
+    request: web::Json<RequestType<CreatableRollover, QueryableClub, ClubSortableField>>,
+) -> impl Responder {
+    let pool = &data.db;
+
+    let data = match &request.data {
+        Some(data) => data,
+        None => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: "request body is empty".to_string(),
+                    source: "/admin/rollover".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    match Rollover::run(pool, data, user.id).await {
+        Ok(report) => {
+            let response: ResponseType<RolloverReport, _> =
+                ResponseType::new(report, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e @ RolloverError::InvalidYears { .. }) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: e.to_string(),
+                    source: "/admin/rollover".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::BadRequest().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: "/admin/rollover".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use serde_qs;
+use uuid::Uuid;
+
+use crate::structs::{
+    allocation::{AllocationRun, CreatableAllocationRun, QueryableAllocationRun},
+    auth::Admin,
+    common::{ErrorResponseType, ErrorType, MetadataType, RequestType, ResponseType},
+};
+
+use crate::utils::date::get_current_academic_year;
+
+use crate::AppState;
+
+#[get("/admin/allocations")]
+pub async fn query_allocation_runs(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    _admin: Admin,
+) -> impl Responder {
+    let pool = &data.db;
+
+    let request_query = serde_qs::from_str::<
+        RequestType<AllocationRun, QueryableAllocationRun, String>,
+    >(&request.query_string());
+
+    let request_query = match request_query {
+        Ok(request_query) => request_query,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: e.to_string(),
+                    source: "/admin/allocations".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    let year = request_query
+        .filter
+        .as_ref()
+        .and_then(|filter| filter.data.as_ref())
+        .and_then(|data| data.year);
+
+    match AllocationRun::query(pool, year).await {
+        Ok(runs) => {
+            let response: ResponseType<Vec<AllocationRun>, _> =
+                ResponseType::new(runs, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: "/admin/allocations".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[get("/admin/allocations/{allocation_id}")]
+pub async fn get_allocation_run_by_id(
+    data: web::Data<AppState>,
+    allocation_id: web::Path<Uuid>,
+    _admin: Admin,
+) -> impl Responder {
+    let pool = &data.db;
+    let allocation_id = allocation_id.into_inner();
+
+    match AllocationRun::get_by_id(pool, allocation_id).await {
+        Ok(run) => {
+            let response: ResponseType<AllocationRun, _> =
+                ResponseType::new(run, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(sqlx::Error::RowNotFound) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "not_found".to_string(),
+                    detail: "allocation run not found".to_string(),
+                    source: format!("/admin/allocations/{allocation_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::NotFound().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/admin/allocations/{allocation_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[post("/admin/allocations")]
+pub async fn create_allocation_run(
+    data: web::Data<AppState>,
+    Admin(user): Admin,
+    request: web::Json<RequestType<CreatableAllocationRun, QueryableAllocationRun, String>>,
+) -> impl Responder {
+    let pool = &data.db;
+
+    let year = request
+        .data
+        .as_ref()
+        .and_then(|data| data.year)
+        .unwrap_or(get_current_academic_year() as i64);
+
+    // a fresh seed is drawn unless the admin wants to reproduce an earlier run
+    let seed = match request.data.as_ref().and_then(|data| data.seed) {
+        Some(seed) => seed,
+        None => Uuid::new_v4().as_u64_pair().0 as i64,
+    };
+
+    match AllocationRun::run(pool, year, seed, user.id).await {
+        Ok(run) => {
+            let response: ResponseType<AllocationRun, _> =
+                ResponseType::new(run, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: "/admin/allocations".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
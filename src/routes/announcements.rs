@@ -0,0 +1,66 @@
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+use serde_qs;
+use uuid::Uuid;
+
+use crate::structs::{
+    auth::User,
+    club_announcement::ClubAnnouncement,
+    common::{ErrorResponseType, ErrorType, MetadataType, RequestType, ResponseType},
+};
+
+use crate::AppState;
+
+// announcements of every club the user belongs to
+#[get("/announcements")]
+pub async fn get_announcement_feed(
+    data: web::Data<AppState>,
+    user: User,
+    request: HttpRequest,
+) -> impl Responder {
+    let pool = &data.db;
+
+    let request_query = serde_qs::from_str::<RequestType<ClubAnnouncement, String, String>>(
+        &request.query_string(),
+    );
+
+    let request_query = match request_query {
+        Ok(request_query) => request_query,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: e.to_string(),
+                    source: "/announcements".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    match ClubAnnouncement::get_feed(pool, &user, &request_query.pagination).await {
+        Ok(announcements) => {
+            let response: ResponseType<Vec<ClubAnnouncement>, _> =
+                ResponseType::new(announcements, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: "/announcements".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+use serde_qs;
+use uuid::Uuid;
+
+use crate::structs::{
+    auth::Admin,
+    club_member_history::{ClubMemberHistory, QueryableClubMemberHistory},
+    common::{ErrorResponseType, ErrorType, MetadataType, RequestType, ResponseType},
+};
+
+use crate::AppState;
+
+#[get("/admin/audit/club_members")]
+pub async fn query_club_member_history(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    _admin: Admin,
+) -> impl Responder {
+    let pool = &data.db;
+
+    let request_query = serde_qs::from_str::<
+        RequestType<ClubMemberHistory, QueryableClubMemberHistory, String>,
+    >(&request.query_string());
+
+    let request_query = match request_query {
+        Ok(request_query) => request_query,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: e.to_string(),
+                    source: "/admin/audit/club_members".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    match ClubMemberHistory::query(pool, &request_query).await {
+        Ok(history) => {
+            let response: ResponseType<Vec<ClubMemberHistory>, _> =
+                ResponseType::new(history, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: "/admin/audit/club_members".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
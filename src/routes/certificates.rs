@@ -0,0 +1,113 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use uuid::Uuid;
+
+use crate::structs::{
+    auth::{User, UserRoles},
+    club_document::{ClubDocumentError, MembershipCertificate},
+    common::{ErrorResponseType, ErrorType, MetadataType},
+};
+use crate::utils::date::get_current_academic_year;
+
+use crate::AppState;
+
+// certificates are only issued once the academic year is over
+#[get("/students/{student_id}/certificates/{year}.pdf")]
+pub async fn get_membership_certificate(
+    data: web::Data<AppState>,
+    path: web::Path<(u32, u32)>,
+    user: User,
+) -> impl Responder {
+    let pool = &data.db;
+    let (student_id, year) = path.into_inner();
+    let source = format!("/students/{student_id}/certificates/{year}.pdf");
+
+    let font = match &data.pdf_font {
+        Some(font) => font.clone(),
+        None => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 503,
+                    error_type: "service_unavailable".to_string(),
+                    detail: "pdf documents are not configured on this server".to_string(),
+                    source,
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::ServiceUnavailable().json(response);
+        }
+    };
+
+    let is_allowed = user.is_admin
+        || matches!(user.role, UserRoles::Teacher)
+        || user.student == Some(student_id);
+
+    if !is_allowed {
+        let response: ErrorResponseType = ErrorResponseType::new(
+            ErrorType {
+                id: Uuid::new_v4().to_string(),
+                code: 403,
+                error_type: "forbidden".to_string(),
+                detail: "students can only get their own certificates".to_string(),
+                source,
+            },
+            None::<MetadataType>,
+        );
+
+        return HttpResponse::Forbidden().json(response);
+    }
+
+    if year >= get_current_academic_year() {
+        let response: ErrorResponseType = ErrorResponseType::new(
+            ErrorType {
+                id: Uuid::new_v4().to_string(),
+                code: 409,
+                error_type: "conflict".to_string(),
+                detail: format!("academic year {year} is not over yet"),
+                source,
+            },
+            None::<MetadataType>,
+        );
+
+        return HttpResponse::Conflict().json(response);
+    }
+
+    match MembershipCertificate::render(pool, student_id, year as i64, &font).await {
+        Ok(pdf) => HttpResponse::Ok()
+            .content_type("application/pdf")
+            .insert_header((
+                "Content-Disposition",
+                format!("inline; filename=\"certificate-{student_id}-{year}.pdf\""),
+            ))
+            .body(pdf),
+        Err(e @ ClubDocumentError::NoMemberships) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: e.to_string(),
+                    source,
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::NotFound().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source,
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
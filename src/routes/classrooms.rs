@@ -0,0 +1,184 @@
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+use serde_qs;
+use uuid::Uuid;
+
+use crate::structs::{
+    auth::{User, UserRoles},
+    classroom::{ClassroomWithRegistration, QueryableClassroom},
+    common::{ErrorResponseType, ErrorType, FetchLevel, MetadataType, RequestType, ResponseType},
+};
+
+use crate::AppState;
+
+// classrooms carry student records and registration numbers, so they are kept to teachers
+fn forbidden_unless_teacher(user: &User, source: &str) -> Option<HttpResponse> {
+    match (user.is_admin, &user.role) {
+        (true, _) | (false, UserRoles::Teacher) => None,
+        (false, UserRoles::Student) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 403,
+                    error_type: "forbidden".to_string(),
+                    detail: "only teachers can view classrooms".to_string(),
+                    source: source.to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            Some(HttpResponse::Forbidden().json(response))
+        }
+    }
+}
+
+#[get("/classrooms")]
+pub async fn query_classrooms(
+    data: web::Data<AppState>,
+    user: User,
+    request: HttpRequest,
+) -> impl Responder {
+    let pool = &data.db;
+
+    if let Some(response) = forbidden_unless_teacher(&user, "/classrooms") {
+        return response;
+    }
+
+    let request_query = serde_qs::from_str::<RequestType<String, QueryableClassroom, String>>(
+        request.query_string(),
+    );
+
+    let request_query = match request_query {
+        Ok(request_query) => request_query,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: e.to_string(),
+                    source: "/classrooms".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    let filter = request_query
+        .filter
+        .as_ref()
+        .and_then(|filter| filter.data.as_ref());
+
+    match ClassroomWithRegistration::query(
+        pool,
+        filter,
+        request_query.fetch_level.unwrap_or(FetchLevel::Compact),
+        request_query.descendant_fetch_level,
+    )
+    .await
+    {
+        Ok(classrooms) => {
+            let response: ResponseType<Vec<ClassroomWithRegistration>, _> =
+                ResponseType::new(classrooms, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: "/classrooms".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[get("/classrooms/{classroom_id}")]
+pub async fn get_classroom_by_id(
+    data: web::Data<AppState>,
+    classroom_id: web::Path<u32>,
+    user: User,
+    request: HttpRequest,
+) -> impl Responder {
+    let pool = &data.db;
+    let classroom_id = classroom_id.into_inner();
+    let source = format!("/classrooms/{classroom_id}");
+
+    if let Some(response) = forbidden_unless_teacher(&user, &source) {
+        return response;
+    }
+
+    let request_query = serde_qs::from_str::<RequestType<String, QueryableClassroom, String>>(
+        request.query_string(),
+    );
+
+    let request_query = match request_query {
+        Ok(request_query) => request_query,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: e.to_string(),
+                    source,
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    match ClassroomWithRegistration::get_by_id(
+        pool,
+        classroom_id,
+        request_query.fetch_level.unwrap_or(FetchLevel::Default),
+        request_query.descendant_fetch_level,
+    )
+    .await
+    {
+        Ok(classroom) => {
+            let response: ResponseType<ClassroomWithRegistration, _> =
+                ResponseType::new(classroom, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(sqlx::Error::RowNotFound) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: "classroom not found".to_string(),
+                    source,
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::NotFound().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source,
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use actix_web::{delete, post, web, HttpResponse, Responder};
+use uuid::Uuid;
+
+use crate::structs::{
+    auth::Admin,
+    club_staff::{ClubAdvisor, ClubStaffError, CreatableClubAdvisor},
+    clubs::{Club, ClubSortableField, QueryableClub},
+    common::{ErrorResponseType, ErrorType, FetchLevel, MetadataType, RequestType, ResponseType},
+    teacher::Teacher,
+};
+
+use crate::AppState;
+
+// advisors are assigned by the school, not by the club itself
+#[post("/clubs/{club_id}/advisors")]
+pub async fn add_club_advisor(
+    data: web::Data<AppState>,
+    club_id: web::Path<Uuid>,
+    _admin: Admin,
+    request: web::Json<RequestType<CreatableClubAdvisor, QueryableClub, ClubSortableField>>,
+) -> impl Responder {
+    let pool = &data.db;
+    let club_id = club_id.into_inner();
+
+    let data = match &request.data {
+        Some(data) => data,
+        None => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: "request body is empty".to_string(),
+                    source: format!("/clubs/{club_id}/advisors"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    // staff and advisors are kept per academic year, changes go to the year the club runs in
+    let year = match Club::get_year(pool, club_id).await {
+        Ok(year) => year,
+        Err(sqlx::Error::RowNotFound) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: "club not found".to_string(),
+                    source: format!("/clubs/{club_id}/advisors"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::NotFound().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/advisors"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    };
+
+    if let Err(e) =
+        Teacher::get_by_id(pool, data.teacher_id as u32, Some(FetchLevel::IdOnly), None).await
+    {
+        let response: ErrorResponseType = ErrorResponseType::new(
+            ErrorType {
+                id: Uuid::new_v4().to_string(),
+                code: 404,
+                error_type: "entity_not_found".to_string(),
+                detail: e.to_string(),
+                source: format!("/clubs/{club_id}/advisors"),
+            },
+            None::<MetadataType>,
+        );
+
+        return HttpResponse::NotFound().json(response);
+    }
+
+    let res = ClubAdvisor::add(pool, club_id, data.teacher_id, year).await;
+
+    match res {
+        Ok(()) => (),
+        Err(e @ ClubStaffError::AlreadyAdvisor) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 409,
+                    error_type: "conflict".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/advisors"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Conflict().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/advisors"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    match Club::get_by_id(
+        pool,
+        club_id,
+        request.fetch_level.clone(),
+        request.descendant_fetch_level.clone(),
+    )
+    .await
+    {
+        Ok(club) => {
+            let response: ResponseType<Club, _> =
+                ResponseType::new(club, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/advisors"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[delete("/clubs/{club_id}/advisors/{teacher_id}")]
+pub async fn remove_club_advisor(
+    data: web::Data<AppState>,
+    path: web::Path<(Uuid, i64)>,
+    _admin: Admin,
+) -> impl Responder {
+    let pool = &data.db;
+    let (club_id, teacher_id) = path.into_inner();
+
+    let year = match Club::get_year(pool, club_id).await {
+        Ok(year) => year,
+        Err(sqlx::Error::RowNotFound) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: "club not found".to_string(),
+                    source: format!("/clubs/{club_id}/advisors/{teacher_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::NotFound().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/advisors/{teacher_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    };
+
+    let res = ClubAdvisor::remove(pool, club_id, teacher_id, year).await;
+
+    match res {
+        Ok(()) => {
+            let response: ResponseType<i64, _> =
+                ResponseType::new(teacher_id, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e @ ClubStaffError::NotAdvisor) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/advisors/{teacher_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::NotFound().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/advisors/{teacher_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
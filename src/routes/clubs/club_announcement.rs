@@ -0,0 +1,433 @@
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+use serde_qs;
+use uuid::Uuid;
+
+use crate::structs::{
+    auth::User,
+    club_announcement::{ClubAnnouncement, CreatableClubAnnouncement, UpdatableClubAnnouncement},
+    clubs::Club,
+    common::{ErrorResponseType, ErrorType, FetchLevel, MetadataType, RequestType, ResponseType},
+};
+
+use crate::AppState;
+
+#[get("/clubs/{club_id}/announcements")]
+pub async fn query_club_announcements(
+    data: web::Data<AppState>,
+    club_id: web::Path<Uuid>,
+    user: Option<User>,
+    request: HttpRequest,
+) -> impl Responder {
+    let pool = &data.db;
+    let club_id = club_id.into_inner();
+
+    let request_query = serde_qs::from_str::<RequestType<ClubAnnouncement, String, String>>(
+        &request.query_string(),
+    );
+
+    let request_query = match request_query {
+        Ok(request_query) => request_query,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/announcements"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    // anyone can read the public announcements, members only ones need a membership
+    let include_members_only = match &user {
+        Some(user) if user.is_admin => Ok(true),
+        Some(user) => match Club::is_manager(pool, club_id, user).await {
+            Ok(true) => Ok(true),
+            Ok(false) => match user.student {
+                Some(student_id) => Club::is_member(pool, club_id, student_id as i64).await,
+                None => Ok(false),
+            },
+            Err(e) => Err(e),
+        },
+        None => Ok(false),
+    };
+
+    let include_members_only = match include_members_only {
+        Ok(include_members_only) => include_members_only,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/announcements"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    };
+
+    let announcements = ClubAnnouncement::get_by_club_id(
+        pool,
+        club_id,
+        include_members_only,
+        &request_query.pagination,
+    )
+    .await;
+
+    match announcements {
+        Ok(announcements) => {
+            let response: ResponseType<Vec<ClubAnnouncement>, _> =
+                ResponseType::new(announcements, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/announcements"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[post("/clubs/{club_id}/announcements")]
+pub async fn create_club_announcement(
+    data: web::Data<AppState>,
+    club_id: web::Path<Uuid>,
+    user: User,
+    request: web::Json<RequestType<CreatableClubAnnouncement, String, String>>,
+) -> impl Responder {
+    let pool = &data.db;
+    let club_id = club_id.into_inner();
+
+    let data = match &request.data {
+        Some(data) => data,
+        None => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: "request body is empty".to_string(),
+                    source: format!("/clubs/{club_id}/announcements"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    if data.title.th.trim().is_empty() || data.body.th.trim().is_empty() {
+        let response: ErrorResponseType = ErrorResponseType::new(
+            ErrorType {
+                id: Uuid::new_v4().to_string(),
+                code: 400,
+                error_type: "bad_request".to_string(),
+                detail: "title and body must not be empty".to_string(),
+                source: format!("/clubs/{club_id}/announcements"),
+            },
+            None::<MetadataType>,
+        );
+
+        return HttpResponse::BadRequest().json(response);
+    }
+
+    let is_allowed = match user.is_admin {
+        true => Ok(true),
+        false => Club::is_manager(pool, club_id, &user).await,
+    };
+
+    match is_allowed {
+        Ok(true) => (),
+        Ok(false) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 403,
+                    error_type: "forbidden".to_string(),
+                    detail: "the user is not club staff or advisor".to_string(),
+                    source: format!("/clubs/{club_id}/announcements"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Forbidden().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/announcements"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    if let Err(e) = Club::get_by_id(pool, club_id, Some(FetchLevel::IdOnly), None).await {
+        let response: ErrorResponseType = ErrorResponseType::new(
+            ErrorType {
+                id: Uuid::new_v4().to_string(),
+                code: 404,
+                error_type: "entity_not_found".to_string(),
+                detail: e.to_string(),
+                source: format!("/clubs/{club_id}/announcements"),
+            },
+            None::<MetadataType>,
+        );
+
+        return HttpResponse::NotFound().json(response);
+    }
+
+    match ClubAnnouncement::create(pool, club_id, data, user.id).await {
+        Ok(announcement) => {
+            let response: ResponseType<ClubAnnouncement, _> =
+                ResponseType::new(announcement, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Created().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/announcements"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[put("/clubs/{club_id}/announcements/{announcement_id}")]
+pub async fn update_club_announcement(
+    data: web::Data<AppState>,
+    path: web::Path<(Uuid, Uuid)>,
+    user: User,
+    request: web::Json<RequestType<UpdatableClubAnnouncement, String, String>>,
+) -> impl Responder {
+    let pool = &data.db;
+    let (club_id, announcement_id) = path.into_inner();
+
+    let data = match &request.data {
+        Some(data) => data,
+        None => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: "request body is empty".to_string(),
+                    source: format!("/clubs/{club_id}/announcements/{announcement_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    let is_empty = [&data.title, &data.body]
+        .iter()
+        .any(|text| matches!(text, Some(text) if text.th.trim().is_empty()));
+
+    if is_empty {
+        let response: ErrorResponseType = ErrorResponseType::new(
+            ErrorType {
+                id: Uuid::new_v4().to_string(),
+                code: 400,
+                error_type: "bad_request".to_string(),
+                detail: "title and body must not be empty".to_string(),
+                source: format!("/clubs/{club_id}/announcements/{announcement_id}"),
+            },
+            None::<MetadataType>,
+        );
+
+        return HttpResponse::BadRequest().json(response);
+    }
+
+    let is_allowed = match user.is_admin {
+        true => Ok(true),
+        false => Club::is_manager(pool, club_id, &user).await,
+    };
+
+    match is_allowed {
+        Ok(true) => (),
+        Ok(false) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 403,
+                    error_type: "forbidden".to_string(),
+                    detail: "the user is not club staff or advisor".to_string(),
+                    source: format!("/clubs/{club_id}/announcements/{announcement_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Forbidden().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/announcements/{announcement_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    match ClubAnnouncement::update_by_id(pool, club_id, announcement_id, data).await {
+        Ok(announcement) => {
+            let response: ResponseType<ClubAnnouncement, _> =
+                ResponseType::new(announcement, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(sqlx::Error::RowNotFound) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: format!("announcement with id {announcement_id} not found"),
+                    source: format!("/clubs/{club_id}/announcements/{announcement_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::NotFound().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/announcements/{announcement_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[delete("/clubs/{club_id}/announcements/{announcement_id}")]
+pub async fn delete_club_announcement(
+    data: web::Data<AppState>,
+    path: web::Path<(Uuid, Uuid)>,
+    user: User,
+) -> impl Responder {
+    let pool = &data.db;
+    let (club_id, announcement_id) = path.into_inner();
+
+    let is_allowed = match user.is_admin {
+        true => Ok(true),
+        false => Club::is_manager(pool, club_id, &user).await,
+    };
+
+    match is_allowed {
+        Ok(true) => (),
+        Ok(false) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 403,
+                    error_type: "forbidden".to_string(),
+                    detail: "the user is not club staff or advisor".to_string(),
+                    source: format!("/clubs/{club_id}/announcements/{announcement_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Forbidden().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/announcements/{announcement_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    match ClubAnnouncement::delete_by_id(pool, club_id, announcement_id).await {
+        Ok(()) => {
+            let response: ResponseType<Uuid, _> =
+                ResponseType::new(announcement_id, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(sqlx::Error::RowNotFound) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: format!("announcement with id {announcement_id} not found"),
+                    source: format!("/clubs/{club_id}/announcements/{announcement_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::NotFound().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/announcements/{announcement_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
@@ -2,10 +2,10 @@ use actix_web::{get, post, web, HttpResponse, Responder};
 use uuid::Uuid;
 
 use crate::structs::{
+    auth::User,
     clubs::{Club, ClubSortableField, QueryableClub},
     common::{ErrorResponseType, ErrorType, FetchLevel, MetadataType, RequestType, ResponseType},
     contacts::{Contact, CreateContact},
-    student::Student,
 };
 
 use crate::AppState;
@@ -14,38 +14,22 @@ use crate::AppState;
 pub async fn create_contact_for_club(
     data: web::Data<AppState>,
     club_id: web::Path<Uuid>,
-    student: Student,
+    user: User,
     request: web::Json<RequestType<CreateContact, QueryableClub, ClubSortableField>>,
 ) -> impl Responder {
     let pool = &data.db;
     let club_id = club_id.into_inner();
 
-    let student_id = match student {
-        Student::IdOnly(student) => student.id,
-        Student::Compact(student) => student.id,
-        Student::Default(student) => student.id,
-    };
-
-    // make sure the student is club staff
-    let res = sqlx::query!(
-        r#"
-        SELECT COUNT(id) FROM club_staffs WHERE student_id = $1 AND club_id = $2
-        "#,
-        student_id as i64,
-        club_id
-    )
-    .fetch_one(pool)
-    .await;
-
-    // if the student is not club staff, return 403
-    if let Ok(res) = res {
-        if res.count.unwrap_or(0) == 0 {
+    // club staff and teacher advisors can manage the club
+    match Club::is_manager(pool, club_id, &user).await {
+        Ok(true) => (),
+        Ok(false) => {
             let response: ErrorResponseType = ErrorResponseType::new(
                 ErrorType {
                     id: Uuid::new_v4().to_string(),
                     code: 403,
                     error_type: "forbidden".to_string(),
-                    detail: "the student is not club staff".to_string(),
+                    detail: "the user is not club staff or advisor".to_string(),
                     source: format!("/clubs/{club_id}"),
                 },
                 None::<MetadataType>,
@@ -53,6 +37,20 @@ pub async fn create_contact_for_club(
 
             return HttpResponse::Forbidden().json(response);
         }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
     }
 
     let club = Club::get_by_id(pool, club_id, Some(FetchLevel::IdOnly), None).await;
@@ -2,9 +2,9 @@ use actix_web::{get, patch, web, HttpResponse, Responder};
 use uuid::Uuid;
 
 use crate::structs::{
+    auth::User,
     clubs::{Club, ClubSortableField, QueryableClub, UpdatableClub},
     common::{ErrorResponseType, ErrorType, MetadataType, RequestType, ResponseType},
-    student::Student,
 };
 
 use crate::AppState;
@@ -53,7 +53,7 @@ pub async fn get_club_by_id(
 pub async fn update_club_by_id(
     data: web::Data<AppState>,
     club_id: web::Path<Uuid>,
-    student: Student,
+    user: User,
     request: web::Json<RequestType<UpdatableClub, QueryableClub, ClubSortableField>>,
 ) -> impl Responder {
     let pool = &data.db;
@@ -77,34 +77,16 @@ pub async fn update_club_by_id(
         }
     };
 
-    let student_id = match student {
-        Student::IdOnly(student) => student.id,
-        Student::Compact(student) => student.id,
-        Student::Default(student) => student.id,
-    };
-
-    // make sure the student is club staff
-    let res = sqlx::query!(
-        r#"
-        SELECT COUNT(id) FROM club_staffs WHERE student_id = $1 AND club_id = $2
-        "#,
-        student_id as i64,
-        club_id
-    )
-    .fetch_one(pool)
-    .await;
-
-    // dbg!(&res, student_id, club_id, &request);
-
-    // if the student is not club staff, return 403
-    if let Ok(res) = res {
-        if res.count.unwrap_or(0) == 0 {
+    // club staff and teacher advisors can manage the club
+    match Club::is_manager(pool, club_id, &user).await {
+        Ok(true) => (),
+        Ok(false) => {
             let response: ErrorResponseType = ErrorResponseType::new(
                 ErrorType {
                     id: Uuid::new_v4().to_string(),
                     code: 403,
                     error_type: "forbidden".to_string(),
-                    detail: "the student is not club staff".to_string(),
+                    detail: "the user is not club staff or advisor".to_string(),
                     source: format!("/clubs/{club_id}"),
                 },
                 None::<MetadataType>,
@@ -112,6 +94,20 @@ pub async fn update_club_by_id(
 
             return HttpResponse::Forbidden().json(response);
         }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
     }
 
     let club = Club::update_by_id(
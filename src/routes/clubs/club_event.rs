@@ -0,0 +1,441 @@
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+use serde_qs;
+use uuid::Uuid;
+
+use crate::structs::{
+    auth::User,
+    club_event::{
+        ClubEvent, ClubEventError, CreatableClubEvent, QueryableClubEvent, UpdatableClubEvent,
+    },
+    clubs::{Club, CompactClub},
+    common::{ErrorResponseType, ErrorType, FetchLevel, MetadataType, RequestType, ResponseType},
+};
+
+use crate::AppState;
+
+#[get("/clubs/{club_id}/events")]
+pub async fn query_club_events(
+    data: web::Data<AppState>,
+    club_id: web::Path<Uuid>,
+    request: HttpRequest,
+) -> impl Responder {
+    let pool = &data.db;
+    let club_id = club_id.into_inner();
+
+    let request_query = serde_qs::from_str::<RequestType<ClubEvent, QueryableClubEvent, String>>(
+        &request.query_string(),
+    );
+
+    let request_query = match request_query {
+        Ok(request_query) => request_query,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/events"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    let filter = request_query
+        .filter
+        .as_ref()
+        .and_then(|filter| filter.data.as_ref());
+
+    match ClubEvent::get_by_club_id(pool, club_id, filter).await {
+        Ok(events) => {
+            let response: ResponseType<Vec<ClubEvent>, _> =
+                ResponseType::new(events, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/events"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+// events are public like the club itself, so the feed needs no token
+#[get("/clubs/{club_id}/events.ics")]
+pub async fn get_club_calendar(
+    data: web::Data<AppState>,
+    club_id: web::Path<Uuid>,
+) -> impl Responder {
+    let pool = &data.db;
+    let club_id = club_id.into_inner();
+
+    let club = match CompactClub::get_by_id(pool, club_id).await {
+        Ok(club) => club,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/events.ics"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::NotFound().json(response);
+        }
+    };
+
+    match ClubEvent::get_by_club_id(pool, club_id, None).await {
+        Ok(events) => HttpResponse::Ok()
+            .content_type("text/calendar; charset=utf-8")
+            .body(ClubEvent::to_ical(&club.name.th, &events, false)),
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/events.ics"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[post("/clubs/{club_id}/events")]
+pub async fn create_club_event(
+    data: web::Data<AppState>,
+    club_id: web::Path<Uuid>,
+    user: User,
+    request: web::Json<RequestType<CreatableClubEvent, QueryableClubEvent, String>>,
+) -> impl Responder {
+    let pool = &data.db;
+    let club_id = club_id.into_inner();
+
+    let data = match &request.data {
+        Some(data) => data,
+        None => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: "request body is empty".to_string(),
+                    source: format!("/clubs/{club_id}/events"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    let is_allowed = match user.is_admin {
+        true => Ok(true),
+        false => Club::is_manager(pool, club_id, &user).await,
+    };
+
+    match is_allowed {
+        Ok(true) => (),
+        Ok(false) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 403,
+                    error_type: "forbidden".to_string(),
+                    detail: "the user is not club staff or advisor".to_string(),
+                    source: format!("/clubs/{club_id}/events"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Forbidden().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/events"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    if let Err(e) = Club::get_by_id(pool, club_id, Some(FetchLevel::IdOnly), None).await {
+        let response: ErrorResponseType = ErrorResponseType::new(
+            ErrorType {
+                id: Uuid::new_v4().to_string(),
+                code: 404,
+                error_type: "entity_not_found".to_string(),
+                detail: e.to_string(),
+                source: format!("/clubs/{club_id}/events"),
+            },
+            None::<MetadataType>,
+        );
+
+        return HttpResponse::NotFound().json(response);
+    }
+
+    match ClubEvent::create(pool, club_id, data, user.id).await {
+        Ok(event) => {
+            let response: ResponseType<ClubEvent, _> =
+                ResponseType::new(event, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Created().json(response)
+        }
+        Err(e @ ClubEventError::InvalidTime) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/events"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::BadRequest().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/events"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[put("/clubs/{club_id}/events/{event_id}")]
+pub async fn update_club_event(
+    data: web::Data<AppState>,
+    path: web::Path<(Uuid, Uuid)>,
+    user: User,
+    request: web::Json<RequestType<UpdatableClubEvent, QueryableClubEvent, String>>,
+) -> impl Responder {
+    let pool = &data.db;
+    let (club_id, event_id) = path.into_inner();
+
+    let data = match &request.data {
+        Some(data) => data,
+        None => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: "request body is empty".to_string(),
+                    source: format!("/clubs/{club_id}/events/{event_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    let is_allowed = match user.is_admin {
+        true => Ok(true),
+        false => Club::is_manager(pool, club_id, &user).await,
+    };
+
+    match is_allowed {
+        Ok(true) => (),
+        Ok(false) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 403,
+                    error_type: "forbidden".to_string(),
+                    detail: "the user is not club staff or advisor".to_string(),
+                    source: format!("/clubs/{club_id}/events/{event_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Forbidden().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/events/{event_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    match ClubEvent::update_by_id(pool, club_id, event_id, data).await {
+        Ok(event) => {
+            let response: ResponseType<ClubEvent, _> =
+                ResponseType::new(event, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(ClubEventError::Database(sqlx::Error::RowNotFound)) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: format!("event with id {event_id} not found"),
+                    source: format!("/clubs/{club_id}/events/{event_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::NotFound().json(response)
+        }
+        Err(e @ ClubEventError::InvalidTime) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/events/{event_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::BadRequest().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/events/{event_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[delete("/clubs/{club_id}/events/{event_id}")]
+pub async fn delete_club_event(
+    data: web::Data<AppState>,
+    path: web::Path<(Uuid, Uuid)>,
+    user: User,
+) -> impl Responder {
+    let pool = &data.db;
+    let (club_id, event_id) = path.into_inner();
+
+    let is_allowed = match user.is_admin {
+        true => Ok(true),
+        false => Club::is_manager(pool, club_id, &user).await,
+    };
+
+    match is_allowed {
+        Ok(true) => (),
+        Ok(false) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 403,
+                    error_type: "forbidden".to_string(),
+                    detail: "the user is not club staff or advisor".to_string(),
+                    source: format!("/clubs/{club_id}/events/{event_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Forbidden().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/events/{event_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    match ClubEvent::delete_by_id(pool, club_id, event_id).await {
+        Ok(()) => {
+            let response: ResponseType<Uuid, _> =
+                ResponseType::new(event_id, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(sqlx::Error::RowNotFound) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: format!("event with id {event_id} not found"),
+                    source: format!("/clubs/{club_id}/events/{event_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::NotFound().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/events/{event_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
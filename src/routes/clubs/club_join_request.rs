@@ -1,10 +1,17 @@
-use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+use std::collections::HashSet;
+
+use actix_web::{get, patch, web, HttpRequest, HttpResponse, Responder};
 use serde_qs;
 use uuid::Uuid;
 
 use crate::structs::{
+    auth::User,
     // clubs::{Club, ClubSortableField, QueryableClub, UpdatableClub}
-    club_request::{ClubRequest, ClubRequestSortableField, QueryableClubRequest},
+    club_request::{
+        BulkUpdatableClubRequest, BulkUpdateClubRequestResult, ClubRequest, ClubRequestError,
+        ClubRequestSortableField, QueryableClubRequest,
+    },
+    clubs::{Club, SubmissionStatus},
     common::{ErrorResponseType, ErrorType, MetadataType, RequestType, ResponseType},
 };
 
@@ -14,6 +21,7 @@ use crate::AppState;
 pub async fn query_club_requests(
     data: web::Data<AppState>,
     request: HttpRequest,
+    user: Option<User>,
 ) -> impl Responder {
     let pool = &data.db;
 
@@ -43,7 +51,7 @@ pub async fn query_club_requests(
 
     dbg!(&request_query);
 
-    let club_request = match ClubRequest::query(pool, &request_query).await {
+    let mut club_request = match ClubRequest::query(pool, &request_query).await {
         Ok(club_request) => club_request,
         Err(e) => {
             let response: ErrorResponseType = ErrorResponseType::new(
@@ -61,8 +69,188 @@ pub async fn query_club_requests(
         }
     };
 
+    // staff notes are only kept for the clubs the user is staff or advisor of
+    let club_ids = club_request
+        .iter()
+        .filter_map(|club_request| club_request.club_id())
+        .collect::<Vec<Uuid>>();
+
+    let managed_clubs = match &user {
+        Some(user) => match Club::get_managed_club_ids(pool, user, &club_ids).await {
+            Ok(managed_clubs) => managed_clubs,
+            Err(e) => {
+                let response: ErrorResponseType = ErrorResponseType::new(
+                    ErrorType {
+                        id: Uuid::new_v4().to_string(),
+                        code: 500,
+                        error_type: "internal_server_error".to_string(),
+                        detail: e.to_string(),
+                        source: "/clubs/join_requests".to_string(),
+                    },
+                    None::<MetadataType>,
+                );
+
+                return HttpResponse::InternalServerError().json(response);
+            }
+        },
+        None => HashSet::new(),
+    };
+
+    for club_request in club_request.iter_mut() {
+        match club_request.club_id() {
+            Some(club_id) if managed_clubs.contains(&club_id) => (),
+            _ => club_request.hide_staff_note(),
+        }
+    }
+
     let response: ResponseType<Vec<ClubRequest>, _> =
         ResponseType::new(club_request, None::<String>, None::<MetadataType>);
 
     HttpResponse::Ok().json(response)
 }
+
+#[patch("/join_requests")]
+pub async fn bulk_update_club_requests(
+    data: web::Data<AppState>,
+    user: User,
+    request_body: web::Json<
+        RequestType<BulkUpdatableClubRequest, QueryableClubRequest, ClubRequestSortableField>,
+    >,
+) -> impl Responder {
+    let pool = &data.db;
+
+    let data = match &request_body.data {
+        Some(data) => data,
+        None => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: "data is required".to_string(),
+                    source: "/join_requests".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    match data.membership_status {
+        SubmissionStatus::Approved | SubmissionStatus::Declined => (),
+        SubmissionStatus::Pending | SubmissionStatus::Waitlisted | SubmissionStatus::Withdrawn => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: format!(
+                        "membership_status can not be {}",
+                        data.membership_status.to_string()
+                    ),
+                    source: "/join_requests".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    }
+
+    if data.decline_reason.is_some() {
+        if let SubmissionStatus::Approved = data.membership_status {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: "decline_reason can only be given when declining".to_string(),
+                    source: "/join_requests".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    }
+
+    // a request listed twice would only be applied once, so report it once
+    let mut join_request_ids = Vec::with_capacity(data.join_request_ids.len());
+
+    for id in data.join_request_ids.iter() {
+        if !join_request_ids.contains(id) {
+            join_request_ids.push(*id);
+        }
+    }
+
+    let res = ClubRequest::bulk_update(
+        pool,
+        &join_request_ids,
+        data.membership_status.clone(),
+        data.decline_reason.as_ref(),
+        &user,
+    )
+    .await;
+
+    let res = match res {
+        Ok(res) => res,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: "/join_requests".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    };
+
+    let results = res
+        .into_iter()
+        .map(|(id, result)| match result {
+            Ok(membership_status) => BulkUpdateClubRequestResult {
+                id,
+                membership_status: Some(membership_status),
+                error: None,
+            },
+            Err(e) => {
+                let (code, error_type) = match e {
+                    ClubRequestError::NotFound => (404, "entity_not_found"),
+                    ClubRequestError::NotClubStaff => (403, "forbidden"),
+                    ClubRequestError::ClubFull { .. } => (409, "club_full"),
+                    ClubRequestError::ClubLimitReached { .. } => (409, "club_limit_reached"),
+                    ClubRequestError::ClubArchived => (409, "club_archived"),
+                    ClubRequestError::WrongYear { .. } => (409, "conflict"),
+                    ClubRequestError::InvalidSemester => (400, "bad_request"),
+                    ClubRequestError::WrongSemester { .. } => (409, "conflict"),
+                    ClubRequestError::RegistrationClosed { .. } => (403, "registration_closed"),
+                    ClubRequestError::InvalidTransition { .. } => (409, "conflict"),
+                    ClubRequestError::Database(_) => (500, "internal_server_error"),
+                };
+
+                BulkUpdateClubRequestResult {
+                    id,
+                    membership_status: None,
+                    error: Some(ErrorType {
+                        id: Uuid::new_v4().to_string(),
+                        code,
+                        error_type: error_type.to_string(),
+                        detail: e.to_string(),
+                        source: format!("/join_requests/{id}"),
+                    }),
+                }
+            }
+        })
+        .collect::<Vec<BulkUpdateClubRequestResult>>();
+
+    let response: ResponseType<Vec<BulkUpdateClubRequestResult>, _> =
+        ResponseType::new(results, None::<String>, None::<MetadataType>);
+
+    HttpResponse::Ok().json(response)
+}
@@ -1,16 +1,23 @@
 use std::fmt::format;
 
-use actix_web::{get, patch, web, HttpRequest, HttpResponse, Responder};
+use actix_web::{delete, get, patch, web, HttpRequest, HttpResponse, Responder};
+use chrono::Utc;
+use serde_json::json;
 use serde_qs;
 use uuid::Uuid;
 
 use crate::structs::{
+    auth::User,
+    classroom::Classroom,
+    club_member_history::ClubMemberHistory,
     // clubs::{Club, ClubSortableField, QueryableClub, UpdatableClub}
     club_request::{
-        ClubRequest, ClubRequestSortableField, QueryableClubRequest, UpdatableClubRequest,
+        ClubRequest, ClubRequestError, ClubRequestSortableField, ClubRequestTable,
+        QueryableClubRequest, UpdatableClubRequest,
     },
     clubs::{Club, SubmissionStatus},
     common::{ErrorResponseType, ErrorType, FetchLevel, MetadataType, RequestType, ResponseType},
+    registration::{RegistrationSettings, RegistrationStatus, RegistrationWindow},
     student::Student,
 };
 
@@ -21,6 +28,7 @@ pub async fn get_club_request_by_id(
     data: web::Data<AppState>,
     request: HttpRequest,
     join_request_id: web::Path<Uuid>,
+    user: Option<User>,
 ) -> impl Responder {
     let pool = &data.db;
     let join_request_id = join_request_id.into_inner();
@@ -47,7 +55,7 @@ pub async fn get_club_request_by_id(
         }
     };
 
-    let club_request = match ClubRequest::get_by_id(
+    let mut club_request = match ClubRequest::get_by_id(
         pool,
         join_request_id,
         request_query.fetch_level,
@@ -72,6 +80,32 @@ pub async fn get_club_request_by_id(
         }
     };
 
+    // only the staff and advisors of the club get to read the staff note
+    let is_staff = match (club_request.club_id(), &user) {
+        (Some(club_id), Some(user)) => match Club::is_manager(pool, club_id, user).await {
+            Ok(is_staff) => is_staff,
+            Err(e) => {
+                let response: ErrorResponseType = ErrorResponseType::new(
+                    ErrorType {
+                        id: Uuid::new_v4().to_string(),
+                        code: 500,
+                        error_type: "internal_server_error".to_string(),
+                        detail: e.to_string(),
+                        source: "/clubs/join_requests".to_string(),
+                    },
+                    None::<MetadataType>,
+                );
+
+                return HttpResponse::InternalServerError().json(response);
+            }
+        },
+        _ => false,
+    };
+
+    if !is_staff {
+        club_request.hide_staff_note();
+    }
+
     let response: ResponseType<ClubRequest, _> =
         ResponseType::new(club_request, None::<String>, None::<MetadataType>);
 
@@ -83,7 +117,7 @@ pub async fn approve_or_reject_club_request(
     data: web::Data<AppState>,
     request: HttpRequest,
     join_request_id: web::Path<Uuid>,
-    student: Student,
+    user: User,
     request_body: web::Json<
         RequestType<UpdatableClubRequest, QueryableClubRequest, ClubRequestSortableField>,
     >,
@@ -116,13 +150,6 @@ pub async fn approve_or_reject_club_request(
         }
     };
 
-    // check if the user is the staff of the club
-    let student_id = match student {
-        Student::IdOnly(student) => student.id,
-        Student::Compact(student) => student.id,
-        Student::Default(student) => student.id,
-    };
-
     let club_id = match club_request {
         ClubRequest::Default(club_request) => match club_request.club {
             Club::IdOnly(club) => club.id,
@@ -145,28 +172,16 @@ pub async fn approve_or_reject_club_request(
         }
     };
 
-    // make sure the student is club staff
-    let res = sqlx::query!(
-        r#"
-        SELECT COUNT(id) FROM club_staffs WHERE student_id = $1 AND club_id = $2
-        "#,
-        student_id as i64,
-        club_id
-    )
-    .fetch_one(pool)
-    .await;
-
-    // dbg!(&res, student_id, club_id, &request);
-
-    // if the student is not club staff, return 403
-    if let Ok(res) = res {
-        if res.count.unwrap_or(0) == 0 {
+    // club staff and teacher advisors can manage the club
+    match Club::is_manager(pool, club_id, &user).await {
+        Ok(true) => (),
+        Ok(false) => {
             let response: ErrorResponseType = ErrorResponseType::new(
                 ErrorType {
                     id: Uuid::new_v4().to_string(),
                     code: 403,
                     error_type: "forbidden".to_string(),
-                    detail: "the student is not club staff".to_string(),
+                    detail: "the user is not club staff or advisor".to_string(),
                     source: format!("/join_requests/{join_request_id}"),
                 },
                 None::<MetadataType>,
@@ -174,43 +189,24 @@ pub async fn approve_or_reject_club_request(
 
             return HttpResponse::Forbidden().json(response);
         }
-    }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/join_requests/{join_request_id}"),
+                },
+                None::<MetadataType>,
+            );
 
-    let res = match &request_body.data {
-        Some(data) => match data.membership_status {
-            SubmissionStatus::Approved => {
-                ClubRequest::approve_request(
-                    pool,
-                    join_request_id,
-                    request_body.fetch_level.clone(),
-                    request_body.descendant_fetch_level.clone(),
-                )
-                .await
-            }
-            SubmissionStatus::Declined => {
-                ClubRequest::deny_request(
-                    pool,
-                    join_request_id,
-                    request_body.fetch_level.clone(),
-                    request_body.descendant_fetch_level.clone(),
-                )
-                .await
-            }
-            SubmissionStatus::Pending => {
-                let response: ErrorResponseType = ErrorResponseType::new(
-                    ErrorType {
-                        id: Uuid::new_v4().to_string(),
-                        code: 400,
-                        error_type: "bad_request".to_string(),
-                        detail: "membership_status can not be pending".to_string(),
-                        source: format!("/join_requests/{join_request_id}"),
-                    },
-                    None::<MetadataType>,
-                );
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
 
-                return HttpResponse::BadRequest().json(response);
-            }
-        },
+    let data = match &request_body.data {
+        Some(data) => data,
         None => {
             let response: ErrorResponseType = ErrorResponseType::new(
                 ErrorType {
@@ -227,8 +223,102 @@ pub async fn approve_or_reject_club_request(
         }
     };
 
+    let detail = match (&data.membership_status, &data.decline_reason) {
+        (
+            Some(
+                membership_status @ (SubmissionStatus::Pending
+                | SubmissionStatus::Waitlisted
+                | SubmissionStatus::Withdrawn),
+            ),
+            _,
+        ) => Some(format!(
+            "membership_status can not be {}",
+            membership_status.to_string()
+        )),
+        (Some(SubmissionStatus::Approved) | None, Some(_)) => {
+            Some("decline_reason can only be given when declining".to_string())
+        }
+        (None, None) if data.staff_note.is_none() => Some("nothing to update".to_string()),
+        _ => None,
+    };
+
+    if let Some(detail) = detail {
+        let response: ErrorResponseType = ErrorResponseType::new(
+            ErrorType {
+                id: Uuid::new_v4().to_string(),
+                code: 400,
+                error_type: "bad_request".to_string(),
+                detail,
+                source: format!("/join_requests/{join_request_id}"),
+            },
+            None::<MetadataType>,
+        );
+
+        return HttpResponse::BadRequest().json(response);
+    }
+
+    let res = ClubRequest::update_request(
+        pool,
+        join_request_id,
+        data,
+        user.id,
+        request_body.fetch_level.clone(),
+        request_body.descendant_fetch_level.clone(),
+    )
+    .await;
+
     let club_request = match res {
         Ok(club_request) => club_request,
+        Err(
+            e @ ClubRequestError::ClubFull {
+                capacity,
+                remaining_seats,
+            },
+        ) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 409,
+                    error_type: "club_full".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/join_requests/{join_request_id}"),
+                },
+                Some(MetadataType::with_details(json!({
+                    "capacity": capacity,
+                    "remaining_seats": remaining_seats,
+                }))),
+            );
+
+            return HttpResponse::Conflict().json(response);
+        }
+        Err(e @ ClubRequestError::ClubLimitReached { .. }) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 409,
+                    error_type: "club_limit_reached".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/join_requests/{join_request_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Conflict().json(response);
+        }
+        Err(e @ ClubRequestError::InvalidTransition { .. }) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 409,
+                    error_type: "conflict".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/join_requests/{join_request_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Conflict().json(response);
+        }
         Err(e) => {
             let response: ErrorResponseType = ErrorResponseType::new(
                 ErrorType {
@@ -250,3 +340,280 @@ pub async fn approve_or_reject_club_request(
 
     HttpResponse::Ok().json(response)
 }
+
+#[delete("/join_requests/{join_request_id}")]
+pub async fn withdraw_club_request(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    join_request_id: web::Path<Uuid>,
+    user: User,
+    student: Student,
+) -> impl Responder {
+    let pool = &data.db;
+    let join_request_id = join_request_id.into_inner();
+
+    let request_query = serde_qs::from_str::<
+        RequestType<ClubRequest, QueryableClubRequest, ClubRequestSortableField>,
+    >(&request.query_string());
+
+    let request_query = match request_query {
+        Ok(request_query) => request_query,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/join_requests/{join_request_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    let club_request = match ClubRequestTable::get_by_id(pool, join_request_id).await {
+        Ok(club_request) => club_request,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/join_requests/{join_request_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::NotFound().json(response);
+        }
+    };
+
+    let student_id = match student {
+        Student::IdOnly(student) => student.id,
+        Student::Compact(student) => student.id,
+        Student::Default(student) => student.id,
+    };
+
+    // only the student who made the request can withdraw it
+    if club_request.student_id != student_id as i64 {
+        let response: ErrorResponseType = ErrorResponseType::new(
+            ErrorType {
+                id: Uuid::new_v4().to_string(),
+                code: 403,
+                error_type: "forbidden".to_string(),
+                detail: "the join request does not belong to the student".to_string(),
+                source: format!("/join_requests/{join_request_id}"),
+            },
+            None::<MetadataType>,
+        );
+
+        return HttpResponse::Forbidden().json(response);
+    }
+
+    // whether approved members may leave is decided in ClubRequestTable::withdraw
+    let settings = match RegistrationSettings::get_by_year(pool, club_request.year).await {
+        Ok(settings) => settings,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/join_requests/{join_request_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    };
+
+    let registration_status =
+        match Classroom::get_grade_by_student_id(pool, student_id, Some(club_request.year as u32))
+            .await
+        {
+            Ok(grade) => {
+                RegistrationWindow::get_status(pool, club_request.year, grade, Utc::now()).await
+            }
+            Err(e) => Err(e),
+        };
+
+    let registration_status = match registration_status {
+        Ok(registration_status) => registration_status,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/join_requests/{join_request_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    };
+
+    let res = ClubRequest::withdraw_request(
+        pool,
+        join_request_id,
+        &settings,
+        &registration_status,
+        user.id,
+        request_query.fetch_level,
+        request_query.descendant_fetch_level,
+    )
+    .await;
+
+    match res {
+        Ok(mut club_request) => {
+            // the response goes to the student, not the staff of the club
+            club_request.hide_staff_note();
+
+            let response: ResponseType<ClubRequest, _> =
+                ResponseType::new(club_request, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e @ ClubRequestError::RegistrationClosed { next_opening }) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 403,
+                    error_type: "registration_closed".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/join_requests/{join_request_id}"),
+                },
+                RegistrationStatus::Closed { next_opening }.to_metadata(),
+            );
+
+            HttpResponse::Forbidden().json(response)
+        }
+        Err(e @ ClubRequestError::InvalidTransition { .. }) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 409,
+                    error_type: "conflict".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/join_requests/{join_request_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::Conflict().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/join_requests/{join_request_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[get("/join_requests/{join_request_id}/history")]
+pub async fn get_club_request_history(
+    data: web::Data<AppState>,
+    join_request_id: web::Path<Uuid>,
+    user: User,
+) -> impl Responder {
+    let pool = &data.db;
+    let join_request_id = join_request_id.into_inner();
+
+    let club_request = match ClubRequestTable::get_by_id(pool, join_request_id).await {
+        Ok(club_request) => club_request,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/join_requests/{join_request_id}/history"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::NotFound().json(response);
+        }
+    };
+
+    // the history is visible to the student who made the request, the club staff and advisors
+    // and admins
+    let is_allowed = match user.student {
+        _ if user.is_admin => Ok(true),
+        Some(student_id) if student_id as i64 == club_request.student_id => Ok(true),
+        _ => Club::is_manager(pool, club_request.club_id, &user).await,
+    };
+
+    match is_allowed {
+        Ok(true) => (),
+        Ok(false) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 403,
+                    error_type: "forbidden".to_string(),
+                    detail: "the user can not view the history of this join request".to_string(),
+                    source: format!("/join_requests/{join_request_id}/history"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Forbidden().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/join_requests/{join_request_id}/history"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    match ClubMemberHistory::get_by_club_member_id(pool, join_request_id).await {
+        Ok(history) => {
+            let response: ResponseType<Vec<ClubMemberHistory>, _> =
+                ResponseType::new(history, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/join_requests/{join_request_id}/history"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
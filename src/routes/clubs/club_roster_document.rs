@@ -0,0 +1,115 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use uuid::Uuid;
+
+use crate::structs::{
+    auth::{User, UserRoles},
+    club_document::{ClubDocumentError, ClubRosterDocument},
+    clubs::Club,
+    common::{ErrorResponseType, ErrorType, MetadataType},
+};
+
+use crate::AppState;
+
+// printable roster for homeroom teachers and advisors to sign, any teacher can print one
+#[get("/clubs/{club_id}/roster.pdf")]
+pub async fn get_club_roster_pdf(
+    data: web::Data<AppState>,
+    club_id: web::Path<Uuid>,
+    user: User,
+) -> impl Responder {
+    let pool = &data.db;
+    let club_id = club_id.into_inner();
+
+    let font = match &data.pdf_font {
+        Some(font) => font.clone(),
+        None => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 503,
+                    error_type: "service_unavailable".to_string(),
+                    detail: "pdf documents are not configured on this server".to_string(),
+                    source: format!("/clubs/{club_id}/roster.pdf"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::ServiceUnavailable().json(response);
+        }
+    };
+
+    let is_allowed = match (user.is_admin, &user.role) {
+        (true, _) | (false, UserRoles::Teacher) => Ok(true),
+        (false, UserRoles::Student) => Club::is_manager(pool, club_id, &user).await,
+    };
+
+    match is_allowed {
+        Ok(true) => (),
+        Ok(false) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 403,
+                    error_type: "forbidden".to_string(),
+                    detail: "the user is not a teacher or club staff".to_string(),
+                    source: format!("/clubs/{club_id}/roster.pdf"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Forbidden().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/roster.pdf"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    match ClubRosterDocument::render(pool, club_id, &font).await {
+        Ok(pdf) => HttpResponse::Ok()
+            .content_type("application/pdf")
+            .insert_header((
+                "Content-Disposition",
+                format!("inline; filename=\"roster-{club_id}.pdf\""),
+            ))
+            .body(pdf),
+        Err(ClubDocumentError::Database(sqlx::Error::RowNotFound)) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: "club not found".to_string(),
+                    source: format!("/clubs/{club_id}/roster.pdf"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::NotFound().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/roster.pdf"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+use futures::stream;
+use serde_qs;
+use uuid::Uuid;
+
+use crate::structs::{
+    auth::User,
+    classroom::Classroom,
+    club_request::{ClubRequest, ClubRequestSortableField, QueryableClubRequest},
+    clubs::Club,
+    common::{ErrorResponseType, ErrorType, MetadataType, RequestType},
+    roster_export::RosterExport,
+};
+
+use crate::AppState;
+
+enum ExportFormat {
+    Csv,
+    Xlsx,
+}
+
+#[get("/join_requests/export.csv")]
+pub async fn export_roster_csv(
+    data: web::Data<AppState>,
+    user: User,
+    request: HttpRequest,
+) -> impl Responder {
+    export_roster(&data, &user, &request, ExportFormat::Csv).await
+}
+
+#[get("/join_requests/export.xlsx")]
+pub async fn export_roster_xlsx(
+    data: web::Data<AppState>,
+    user: User,
+    request: HttpRequest,
+) -> impl Responder {
+    export_roster(&data, &user, &request, ExportFormat::Xlsx).await
+}
+
+// takes the same query string as GET /join_requests, club staff export the clubs they run and
+// homeroom advisors their classroom
+async fn export_roster(
+    data: &web::Data<AppState>,
+    user: &User,
+    request: &HttpRequest,
+    format: ExportFormat,
+) -> HttpResponse {
+    let pool = &data.db;
+    let source = request.path().to_string();
+
+    let request_query = serde_qs::from_str::<
+        RequestType<ClubRequest, QueryableClubRequest, ClubRequestSortableField>,
+    >(&request.query_string());
+
+    let request_query = match request_query {
+        Ok(request_query) => request_query,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: e.to_string(),
+                    source,
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    let filter = request_query
+        .filter
+        .as_ref()
+        .and_then(|filter| filter.data.as_ref());
+
+    let is_allowed = match (
+        user.is_admin,
+        filter.and_then(|filter| filter.club_id),
+        filter.and_then(|filter| filter.classroom_id),
+    ) {
+        (true, _, _) => Ok(true),
+        (false, Some(club_id), _) => Club::is_manager(pool, club_id, user).await,
+        (false, None, Some(classroom_id)) => match user.teacher {
+            Some(teacher_id) => match Classroom::get_roster(pool, classroom_id as u32).await {
+                Ok(roster) => Ok(roster.advisors.contains(&(teacher_id as i64))),
+                Err(sqlx::Error::RowNotFound) => Ok(false),
+                Err(e) => Err(e),
+            },
+            None => Ok(false),
+        },
+        (false, None, None) => Ok(false),
+    };
+
+    match is_allowed {
+        Ok(true) => (),
+        Ok(false) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 403,
+                    error_type: "forbidden".to_string(),
+                    detail: "filter by a club_id the user is staff or advisor of or a classroom_id the user is advisor of".to_string(),
+                    source,
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Forbidden().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source,
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    let export = match RosterExport::query(pool, &request_query).await {
+        Ok(export) => export,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source,
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    };
+
+    let xlsx = match format {
+        ExportFormat::Csv => {
+            let lines = export
+                .into_csv_lines()
+                .map(|line| line.map(web::Bytes::from));
+
+            return HttpResponse::Ok()
+                .content_type("text/csv; charset=utf-8")
+                .insert_header(("Content-Disposition", "attachment; filename=\"roster.csv\""))
+                .streaming(stream::iter(lines));
+        }
+        // an xlsx file is a zip archive whose index is written last, rust_xlsxwriter only
+        // builds it in memory
+        ExportFormat::Xlsx => export.to_xlsx(),
+    };
+
+    match xlsx {
+        Ok(body) => HttpResponse::Ok()
+            .content_type("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet")
+            .insert_header((
+                "Content-Disposition",
+                "attachment; filename=\"roster.xlsx\"",
+            ))
+            .body(body),
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source,
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
@@ -0,0 +1,681 @@
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+use serde_qs;
+use uuid::Uuid;
+
+use crate::structs::{
+    auth::User,
+    club_session::{
+        AttendanceRate, ClubAttendance, ClubSession, ClubSessionError, CreatableClubSession,
+        QueryableClubAttendance, UpdatableClubAttendance,
+    },
+    clubs::Club,
+    common::{ErrorResponseType, ErrorType, FetchLevel, MetadataType, RequestType, ResponseType},
+};
+
+use crate::AppState;
+
+#[get("/clubs/{club_id}/sessions")]
+pub async fn query_club_sessions(
+    data: web::Data<AppState>,
+    club_id: web::Path<Uuid>,
+    user: User,
+) -> impl Responder {
+    let pool = &data.db;
+    let club_id = club_id.into_inner();
+
+    let is_allowed = match user.is_admin {
+        true => Ok(true),
+        false => Club::is_manager(pool, club_id, &user).await,
+    };
+
+    match is_allowed {
+        Ok(true) => (),
+        Ok(false) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 403,
+                    error_type: "forbidden".to_string(),
+                    detail: "the user is not club staff or advisor".to_string(),
+                    source: format!("/clubs/{club_id}/sessions"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Forbidden().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/sessions"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    match ClubSession::get_by_club_id(pool, club_id).await {
+        Ok(sessions) => {
+            let response: ResponseType<Vec<ClubSession>, _> =
+                ResponseType::new(sessions, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/sessions"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[post("/clubs/{club_id}/sessions")]
+pub async fn create_club_session(
+    data: web::Data<AppState>,
+    club_id: web::Path<Uuid>,
+    user: User,
+    request: web::Json<RequestType<CreatableClubSession, QueryableClubAttendance, String>>,
+) -> impl Responder {
+    let pool = &data.db;
+    let club_id = club_id.into_inner();
+
+    let data = match &request.data {
+        Some(data) => data,
+        None => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: "request body is empty".to_string(),
+                    source: format!("/clubs/{club_id}/sessions"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    let is_allowed = match user.is_admin {
+        true => Ok(true),
+        false => Club::is_manager(pool, club_id, &user).await,
+    };
+
+    match is_allowed {
+        Ok(true) => (),
+        Ok(false) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 403,
+                    error_type: "forbidden".to_string(),
+                    detail: "the user is not club staff or advisor".to_string(),
+                    source: format!("/clubs/{club_id}/sessions"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Forbidden().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/sessions"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    if let Err(e) = Club::get_by_id(pool, club_id, Some(FetchLevel::IdOnly), None).await {
+        let response: ErrorResponseType = ErrorResponseType::new(
+            ErrorType {
+                id: Uuid::new_v4().to_string(),
+                code: 404,
+                error_type: "entity_not_found".to_string(),
+                detail: e.to_string(),
+                source: format!("/clubs/{club_id}/sessions"),
+            },
+            None::<MetadataType>,
+        );
+
+        return HttpResponse::NotFound().json(response);
+    }
+
+    match ClubSession::create(pool, club_id, data, user.id).await {
+        Ok(session) => {
+            let response: ResponseType<ClubSession, _> =
+                ResponseType::new(session, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Created().json(response)
+        }
+        Err(e @ (ClubSessionError::WrongYear { .. } | ClubSessionError::WrongSemester { .. })) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/sessions"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::BadRequest().json(response)
+        }
+        Err(e @ ClubSessionError::SessionExists { .. }) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 409,
+                    error_type: "conflict".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/sessions"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::Conflict().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/sessions"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[delete("/clubs/{club_id}/sessions/{session_id}")]
+pub async fn delete_club_session(
+    data: web::Data<AppState>,
+    path: web::Path<(Uuid, Uuid)>,
+    user: User,
+) -> impl Responder {
+    let pool = &data.db;
+    let (club_id, session_id) = path.into_inner();
+
+    let is_allowed = match user.is_admin {
+        true => Ok(true),
+        false => Club::is_manager(pool, club_id, &user).await,
+    };
+
+    match is_allowed {
+        Ok(true) => (),
+        Ok(false) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 403,
+                    error_type: "forbidden".to_string(),
+                    detail: "the user is not club staff or advisor".to_string(),
+                    source: format!("/clubs/{club_id}/sessions/{session_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Forbidden().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/sessions/{session_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    // the attendance of the session goes with it
+    match ClubSession::delete_by_id(pool, club_id, session_id).await {
+        Ok(()) => {
+            let response: ResponseType<Uuid, _> =
+                ResponseType::new(session_id, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(sqlx::Error::RowNotFound) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: format!("session with id {session_id} not found"),
+                    source: format!("/clubs/{club_id}/sessions/{session_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::NotFound().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/sessions/{session_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[get("/clubs/{club_id}/sessions/{session_id}/attendance")]
+pub async fn get_session_attendance(
+    data: web::Data<AppState>,
+    path: web::Path<(Uuid, Uuid)>,
+    user: User,
+    request: HttpRequest,
+) -> impl Responder {
+    let pool = &data.db;
+    let (club_id, session_id) = path.into_inner();
+
+    let request_query = serde_qs::from_str::<
+        RequestType<ClubSession, QueryableClubAttendance, String>,
+    >(&request.query_string());
+
+    let request_query = match request_query {
+        Ok(request_query) => request_query,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/sessions/{session_id}/attendance"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    let is_allowed = match user.is_admin {
+        true => Ok(true),
+        false => Club::is_manager(pool, club_id, &user).await,
+    };
+
+    match is_allowed {
+        Ok(true) => (),
+        Ok(false) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 403,
+                    error_type: "forbidden".to_string(),
+                    detail: "the user is not club staff or advisor".to_string(),
+                    source: format!("/clubs/{club_id}/sessions/{session_id}/attendance"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Forbidden().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/sessions/{session_id}/attendance"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    let session = match ClubSession::get_by_id(pool, club_id, session_id).await {
+        Ok(session) => session,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/sessions/{session_id}/attendance"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::NotFound().json(response);
+        }
+    };
+
+    let attendance = ClubAttendance::get_by_session(
+        pool,
+        &session,
+        request_query.fetch_level.clone(),
+        request_query.descendant_fetch_level.clone(),
+    )
+    .await;
+
+    match attendance {
+        Ok(attendance) => {
+            let response: ResponseType<Vec<ClubAttendance>, _> =
+                ResponseType::new(attendance, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/sessions/{session_id}/attendance"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[put("/clubs/{club_id}/sessions/{session_id}/attendance")]
+pub async fn update_session_attendance(
+    data: web::Data<AppState>,
+    path: web::Path<(Uuid, Uuid)>,
+    user: User,
+    request: web::Json<RequestType<Vec<UpdatableClubAttendance>, QueryableClubAttendance, String>>,
+) -> impl Responder {
+    let pool = &data.db;
+    let (club_id, session_id) = path.into_inner();
+
+    let data = match &request.data {
+        Some(data) => data,
+        None => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: "request body is empty".to_string(),
+                    source: format!("/clubs/{club_id}/sessions/{session_id}/attendance"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    let is_allowed = match user.is_admin {
+        true => Ok(true),
+        false => Club::is_manager(pool, club_id, &user).await,
+    };
+
+    match is_allowed {
+        Ok(true) => (),
+        Ok(false) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 403,
+                    error_type: "forbidden".to_string(),
+                    detail: "the user is not club staff or advisor".to_string(),
+                    source: format!("/clubs/{club_id}/sessions/{session_id}/attendance"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Forbidden().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/sessions/{session_id}/attendance"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    let session = match ClubSession::get_by_id(pool, club_id, session_id).await {
+        Ok(session) => session,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/sessions/{session_id}/attendance"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::NotFound().json(response);
+        }
+    };
+
+    match ClubAttendance::record(pool, &session, data, user.id).await {
+        Ok(()) => (),
+        Err(e @ ClubSessionError::NotMember { .. }) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 409,
+                    error_type: "conflict".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/sessions/{session_id}/attendance"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Conflict().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/sessions/{session_id}/attendance"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    let attendance = ClubAttendance::get_by_session(
+        pool,
+        &session,
+        request.fetch_level.clone(),
+        request.descendant_fetch_level.clone(),
+    )
+    .await;
+
+    match attendance {
+        Ok(attendance) => {
+            let response: ResponseType<Vec<ClubAttendance>, _> =
+                ResponseType::new(attendance, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/sessions/{session_id}/attendance"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[get("/clubs/{club_id}/attendance")]
+pub async fn query_club_attendance_rates(
+    data: web::Data<AppState>,
+    club_id: web::Path<Uuid>,
+    user: User,
+    request: HttpRequest,
+) -> impl Responder {
+    let pool = &data.db;
+    let club_id = club_id.into_inner();
+
+    let request_query = serde_qs::from_str::<
+        RequestType<ClubSession, QueryableClubAttendance, String>,
+    >(&request.query_string());
+
+    let request_query = match request_query {
+        Ok(request_query) => request_query,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/attendance"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    let filter = request_query
+        .filter
+        .as_ref()
+        .and_then(|filter| filter.data.as_ref());
+
+    // students may look up their own attendance, everything else is for the club staff
+    let is_own_attendance = match (user.student, filter.and_then(|filter| filter.student_id)) {
+        (Some(own_id), Some(student_id)) => own_id as i64 == student_id,
+        _ => false,
+    };
+
+    let is_allowed = match user.is_admin || is_own_attendance {
+        true => Ok(true),
+        false => Club::is_manager(pool, club_id, &user).await,
+    };
+
+    match is_allowed {
+        Ok(true) => (),
+        Ok(false) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 403,
+                    error_type: "forbidden".to_string(),
+                    detail: "the user is not club staff or advisor".to_string(),
+                    source: format!("/clubs/{club_id}/attendance"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Forbidden().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/attendance"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    let rates = AttendanceRate::get_by_club_id(
+        pool,
+        club_id,
+        filter,
+        request_query.fetch_level.clone(),
+        request_query.descendant_fetch_level.clone(),
+    )
+    .await;
+
+    match rates {
+        Ok(rates) => {
+            let response: ResponseType<Vec<AttendanceRate>, _> =
+                ResponseType::new(rates, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/attendance"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
@@ -0,0 +1,491 @@
+use actix_web::{delete, post, put, web, HttpResponse, Responder};
+use uuid::Uuid;
+
+use crate::structs::{
+    auth::User,
+    club_staff::{ClubStaff, ClubStaffError, CreatableClubStaff, UpdatableClubPresident},
+    clubs::{Club, ClubSortableField, QueryableClub},
+    common::{ErrorResponseType, ErrorType, FetchLevel, MetadataType, RequestType, ResponseType},
+    student::Student,
+};
+
+use crate::AppState;
+
+#[post("/clubs/{club_id}/staffs")]
+pub async fn add_club_staff(
+    data: web::Data<AppState>,
+    club_id: web::Path<Uuid>,
+    user: User,
+    request: web::Json<RequestType<CreatableClubStaff, QueryableClub, ClubSortableField>>,
+) -> impl Responder {
+    let pool = &data.db;
+    let club_id = club_id.into_inner();
+
+    let data = match &request.data {
+        Some(data) => data,
+        None => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: "request body is empty".to_string(),
+                    source: format!("/clubs/{club_id}/staffs"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    // existing staff, advisors and admins can add staff
+    let is_allowed = match user.is_admin {
+        true => Ok(true),
+        false => Club::is_manager(pool, club_id, &user).await,
+    };
+
+    match is_allowed {
+        Ok(true) => (),
+        Ok(false) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 403,
+                    error_type: "forbidden".to_string(),
+                    detail: "the user is not club staff or advisor".to_string(),
+                    source: format!("/clubs/{club_id}/staffs"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Forbidden().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/staffs"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    // staff and advisors are kept per academic year, changes go to the year the club runs in
+    let year = match Club::get_year(pool, club_id).await {
+        Ok(year) => year,
+        Err(sqlx::Error::RowNotFound) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: "club not found".to_string(),
+                    source: format!("/clubs/{club_id}/staffs"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::NotFound().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/staffs"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    };
+
+    if let Err(e) =
+        Student::get_by_id(pool, data.student_id as u32, Some(FetchLevel::IdOnly), None).await
+    {
+        let response: ErrorResponseType = ErrorResponseType::new(
+            ErrorType {
+                id: Uuid::new_v4().to_string(),
+                code: 404,
+                error_type: "entity_not_found".to_string(),
+                detail: e.to_string(),
+                source: format!("/clubs/{club_id}/staffs"),
+            },
+            None::<MetadataType>,
+        );
+
+        return HttpResponse::NotFound().json(response);
+    }
+
+    let res = ClubStaff::add(pool, club_id, data.student_id, year).await;
+
+    match res {
+        Ok(()) => (),
+        Err(e @ ClubStaffError::AlreadyStaff) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 409,
+                    error_type: "conflict".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/staffs"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Conflict().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/staffs"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    match Club::get_by_id(
+        pool,
+        club_id,
+        request.fetch_level.clone(),
+        request.descendant_fetch_level.clone(),
+    )
+    .await
+    {
+        Ok(club) => {
+            let response: ResponseType<Club, _> =
+                ResponseType::new(club, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/staffs"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[delete("/clubs/{club_id}/staffs/{student_id}")]
+pub async fn remove_club_staff(
+    data: web::Data<AppState>,
+    path: web::Path<(Uuid, i64)>,
+    user: User,
+) -> impl Responder {
+    let pool = &data.db;
+    let (club_id, student_id) = path.into_inner();
+
+    // existing staff, advisors and admins can remove staff, staff can also step down themselves
+    let is_allowed = match user.is_admin {
+        true => Ok(true),
+        false => Club::is_manager(pool, club_id, &user).await,
+    };
+
+    match is_allowed {
+        Ok(true) => (),
+        Ok(false) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 403,
+                    error_type: "forbidden".to_string(),
+                    detail: "the user is not club staff or advisor".to_string(),
+                    source: format!("/clubs/{club_id}/staffs/{student_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Forbidden().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/staffs/{student_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    let year = match Club::get_year(pool, club_id).await {
+        Ok(year) => year,
+        Err(sqlx::Error::RowNotFound) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: "club not found".to_string(),
+                    source: format!("/clubs/{club_id}/staffs/{student_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::NotFound().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/staffs/{student_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    };
+
+    let res = ClubStaff::remove(pool, club_id, student_id, year).await;
+
+    match res {
+        Ok(()) => {
+            let response: ResponseType<i64, _> =
+                ResponseType::new(student_id, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e @ ClubStaffError::NotStaff) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/staffs/{student_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::NotFound().json(response)
+        }
+        Err(e @ (ClubStaffError::LastStaff | ClubStaffError::IsPresident)) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 409,
+                    error_type: "conflict".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/staffs/{student_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::Conflict().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/staffs/{student_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[put("/clubs/{club_id}/president")]
+pub async fn transfer_club_president(
+    data: web::Data<AppState>,
+    club_id: web::Path<Uuid>,
+    user: User,
+    request: web::Json<RequestType<UpdatableClubPresident, QueryableClub, ClubSortableField>>,
+) -> impl Responder {
+    let pool = &data.db;
+    let club_id = club_id.into_inner();
+
+    let data = match &request.data {
+        Some(data) => data,
+        None => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: "request body is empty".to_string(),
+                    source: format!("/clubs/{club_id}/president"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    let year = match Club::get_year(pool, club_id).await {
+        Ok(year) => year,
+        Err(sqlx::Error::RowNotFound) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: "club not found".to_string(),
+                    source: format!("/clubs/{club_id}/president"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::NotFound().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/president"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    };
+
+    // only the current president, an advisor or an admin can hand the role over
+    let is_allowed = match (user.student, user.teacher) {
+        _ if user.is_admin => Ok(true),
+        (Some(student_id), _) => {
+            ClubStaff::is_president(pool, club_id, student_id as i64, year).await
+        }
+        (None, Some(teacher_id)) => Club::is_advisor(pool, club_id, teacher_id as i64).await,
+        (None, None) => Ok(false),
+    };
+
+    match is_allowed {
+        Ok(true) => (),
+        Ok(false) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 403,
+                    error_type: "forbidden".to_string(),
+                    detail: "the user is not the club president".to_string(),
+                    source: format!("/clubs/{club_id}/president"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Forbidden().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/president"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    match ClubStaff::transfer_president(pool, club_id, data.student_id, year).await {
+        Ok(()) => (),
+        Err(e @ ClubStaffError::NotStaff) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 409,
+                    error_type: "conflict".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/president"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Conflict().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/president"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    match Club::get_by_id(
+        pool,
+        club_id,
+        request.fetch_level.clone(),
+        request.descendant_fetch_level.clone(),
+    )
+    .await
+    {
+        Ok(club) => {
+            let response: ResponseType<Club, _> =
+                ResponseType::new(club, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/president"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
@@ -1,10 +1,12 @@
-use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
 use serde_qs;
 use uuid::Uuid;
 
 use crate::structs::{
-    clubs::{Club, ClubSortableField, QueryableClub},
-    common::{ErrorResponseType, ErrorType, MetadataType, RequestType, ResponseType},
+    auth::{User, UserRoles},
+    clubs::{Club, ClubSortableField, CreatableClub, QueryableClub},
+    common::{ErrorResponseType, ErrorType, FetchLevel, MetadataType, RequestType, ResponseType},
+    student::Student,
 };
 
 use crate::AppState;
@@ -67,3 +69,113 @@ pub async fn query_clubs(
 
     HttpResponse::Ok().json(response)
 }
+
+#[post("/clubs")]
+pub async fn create_club(
+    data: web::Data<AppState>,
+    user: User,
+    request: web::Json<RequestType<CreatableClub, QueryableClub, ClubSortableField>>,
+) -> impl Responder {
+    let pool = &data.db;
+
+    if !user.is_admin && !matches!(user.role, UserRoles::Teacher) {
+        let response: ErrorResponseType = ErrorResponseType::new(
+            ErrorType {
+                id: Uuid::new_v4().to_string(),
+                code: 403,
+                error_type: "forbidden".to_string(),
+                detail: "only admins and teachers can create clubs".to_string(),
+                source: "/clubs".to_string(),
+            },
+            None::<MetadataType>,
+        );
+
+        return HttpResponse::Forbidden().json(response);
+    }
+
+    let data = match &request.data {
+        Some(data) => data,
+        None => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: "request body is empty".to_string(),
+                    source: "/clubs".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    if let Some(semester) = data.semester {
+        if !(1..=2).contains(&semester) {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: "semester must be 1 or 2".to_string(),
+                    source: "/clubs".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    }
+
+    for student_id in data.staffs.iter() {
+        if let Err(e) =
+            Student::get_by_id(pool, *student_id as u32, Some(FetchLevel::IdOnly), None).await
+        {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: format!("student {student_id}: {e}"),
+                    source: "/clubs".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::NotFound().json(response);
+        }
+    }
+
+    let club = Club::create(
+        pool,
+        data,
+        &user,
+        request.fetch_level.clone(),
+        request.descendant_fetch_level.clone(),
+    )
+    .await;
+
+    match club {
+        Ok(club) => {
+            let response: ResponseType<Club, _> =
+                ResponseType::new(club, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Created().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: "/clubs".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
@@ -1,28 +1,32 @@
 use std::fmt::format;
 
 use actix_web::{post, web, HttpRequest, HttpResponse, Responder};
+use chrono::Utc;
 use serde_qs;
 use uuid::Uuid;
 
 use crate::structs::{
+    auth::User,
+    classroom::Classroom,
     club_request::{
-        ClubRequest, ClubRequestSortableField, CreatableClubRequest, QueryableClubRequest,
+        ClubRequest, ClubRequestError, ClubRequestSortableField, CreatableClubRequest,
+        JoinableClub, QueryableClubRequest,
     },
     clubs::{Club, SubmissionStatus},
     common::{ErrorResponseType, ErrorType, FetchLevel, MetadataType, RequestType, ResponseType},
+    registration::{RegistrationSettings, RegistrationStatus, RegistrationWindow},
     student::Student,
 };
 
-use crate::utils::date::get_current_academic_year;
-
 use crate::AppState;
 
 #[post("/clubs/{club_id}/join")]
 pub async fn join_club_by_id(
     data: web::Data<AppState>,
     club_id: web::Path<Uuid>,
+    user: User,
     student: Student,
-    request: web::Json<RequestType<String, QueryableClubRequest, ClubRequestSortableField>>,
+    request: web::Json<RequestType<JoinableClub, QueryableClubRequest, ClubRequestSortableField>>,
 ) -> impl Responder {
     let pool = &data.db;
     let club_id = club_id.into_inner();
@@ -31,7 +35,7 @@ pub async fn join_club_by_id(
 
     // check if the student is in the club by SELECT COUNT(id) FROM club_members WHERE club_id = club_id AND student_id = student_id AND membership_status = 'approved
     // if yes, return 409
-    // if no, insert into club_requests
+    // if no, insert into club_requests, or into the waitlist if the club is full
 
     let club_id = match Club::get_by_id(pool, club_id, Some(FetchLevel::IdOnly), None).await {
         Ok(club) => match club {
@@ -73,13 +77,37 @@ pub async fn join_club_by_id(
         Student::Default(student) => student.id,
     };
 
+    // everything below is checked against the academic year the club runs in, so joining a
+    // club of the next year during pre-registration uses that year's windows and limits
+    let year = match Club::get_year(pool, club_id).await {
+        Ok(year) => year,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/join"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    };
+
+    let semester = request.data.as_ref().and_then(|data| data.semester);
+
+    // a student may hold the same club again only for the other semester
     let club_request_count = sqlx::query!(
         r#"
-        SELECT COUNT(id) FROM club_members WHERE club_id = $1 AND student_id = $2 AND year = $3 AND (membership_status = 'approved' OR membership_status = 'pending')
+        SELECT COUNT(id) FROM club_members WHERE club_id = $1 AND student_id = $2 AND year = $3 AND (semester IS NULL OR $4::bigint IS NULL OR semester = $4) AND (membership_status = 'approved' OR membership_status = 'pending' OR membership_status = 'waitlisted')
         "#,
         club_id,
         student_id as i64,
-        get_current_academic_year() as i64
+        year,
+        semester
     ).fetch_one(pool).await;
 
     if let Ok(club_request_count) = club_request_count {
@@ -99,15 +127,105 @@ pub async fn join_club_by_id(
         }
     }
 
+    // make sure registration is open for the student's grade
+    let grade = match Classroom::get_grade_by_student_id(pool, student_id, Some(year as u32)).await
+    {
+        Ok(grade) => grade,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/join"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    };
+
+    let registration_status = RegistrationWindow::get_status(pool, year, grade, Utc::now()).await;
+
+    match registration_status {
+        Ok(RegistrationStatus::Open) => (),
+        Ok(status @ RegistrationStatus::Closed { .. }) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 403,
+                    error_type: "registration_closed".to_string(),
+                    detail: status.to_string(),
+                    source: format!("/clubs/{club_id}/join"),
+                },
+                status.to_metadata(),
+            );
+
+            return HttpResponse::Forbidden().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/join"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    // in lottery years students submit ranked preferences instead of joining directly
+    match RegistrationSettings::get_by_year(pool, year).await {
+        Ok(settings) if settings.lottery_enabled => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 409,
+                    error_type: "lottery_enabled".to_string(),
+                    detail: "clubs are allocated by lottery this year, submit preferences instead"
+                        .to_string(),
+                    source: format!("/clubs/{club_id}/join"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Conflict().json(response);
+        }
+        Ok(_) => (),
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/join"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
     let club_request = CreatableClubRequest {
         club_id,
         student_id: student_id as i64,
-        year: Some(get_current_academic_year() as i64),
+        year: Some(year),
+        semester,
     };
 
     let res = ClubRequest::create(
         pool,
         club_request,
+        user.id,
         request.fetch_level.clone(),
         request.descendant_fetch_level.clone(),
     )
@@ -120,6 +238,52 @@ pub async fn join_club_by_id(
 
             HttpResponse::Ok().json(response)
         }
+        Err(e @ ClubRequestError::ClubLimitReached { .. }) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 409,
+                    error_type: "club_limit_reached".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/join"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::Conflict().json(response)
+        }
+        Err(e @ ClubRequestError::InvalidSemester) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/join"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::BadRequest().json(response)
+        }
+        Err(
+            e @ (ClubRequestError::ClubArchived
+            | ClubRequestError::WrongYear { .. }
+            | ClubRequestError::WrongSemester { .. }),
+        ) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 409,
+                    error_type: "conflict".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/clubs/{club_id}/join"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::Conflict().json(response)
+        }
         Err(e) => {
             let response: ErrorResponseType = ErrorResponseType::new(
                 ErrorType {
@@ -1,6 +1,13 @@
+pub(crate) mod club_advisor;
+pub(crate) mod club_announcement;
 pub(crate) mod club_contact;
 pub(crate) mod club_detail;
+pub(crate) mod club_event;
 pub(crate) mod club_join_request;
 pub(crate) mod club_join_request_detail;
+pub(crate) mod club_roster_document;
+pub(crate) mod club_roster_export;
+pub(crate) mod club_session;
+pub(crate) mod club_staff;
 pub(crate) mod clubs;
 pub(crate) mod join_club;
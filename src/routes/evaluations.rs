@@ -0,0 +1,225 @@
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use serde_qs;
+use uuid::Uuid;
+
+use crate::structs::{
+    auth::User,
+    classroom::Classroom,
+    common::{ErrorResponseType, ErrorType, MetadataType, RequestType, ResponseType},
+    evaluation::{EvaluationError, EvaluationReport},
+};
+
+use crate::AppState;
+
+#[get("/evaluations/classrooms/{classroom_id}")]
+pub async fn get_evaluation_report(
+    data: web::Data<AppState>,
+    classroom_id: web::Path<u32>,
+    user: User,
+    request: HttpRequest,
+) -> impl Responder {
+    let pool = &data.db;
+    let classroom_id = classroom_id.into_inner();
+
+    let request_query =
+        serde_qs::from_str::<RequestType<String, String, String>>(&request.query_string());
+
+    let request_query = match request_query {
+        Ok(request_query) => request_query,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/evaluations/classrooms/{classroom_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    let roster = match Classroom::get_roster(pool, classroom_id).await {
+        Ok(roster) => roster,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/evaluations/classrooms/{classroom_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::NotFound().json(response);
+        }
+    };
+
+    // the report is for the homeroom advisors of the classroom and admins
+    let is_advisor = match user.teacher {
+        Some(teacher_id) => roster.advisors.contains(&(teacher_id as i64)),
+        None => false,
+    };
+
+    if !user.is_admin && !is_advisor {
+        let response: ErrorResponseType = ErrorResponseType::new(
+            ErrorType {
+                id: Uuid::new_v4().to_string(),
+                code: 403,
+                error_type: "forbidden".to_string(),
+                detail: "the user is not a classroom advisor".to_string(),
+                source: format!("/evaluations/classrooms/{classroom_id}"),
+            },
+            None::<MetadataType>,
+        );
+
+        return HttpResponse::Forbidden().json(response);
+    }
+
+    let report = EvaluationReport::get_by_roster(
+        pool,
+        &roster,
+        request_query.fetch_level.clone(),
+        request_query.descendant_fetch_level.clone(),
+    )
+    .await;
+
+    match report {
+        Ok(report) => {
+            let response: ResponseType<EvaluationReport, _> =
+                ResponseType::new(report, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/evaluations/classrooms/{classroom_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[post("/evaluations/classrooms/{classroom_id}/sign_off")]
+pub async fn sign_off_evaluation_report(
+    data: web::Data<AppState>,
+    classroom_id: web::Path<u32>,
+    user: User,
+    request: web::Json<RequestType<String, String, String>>,
+) -> impl Responder {
+    let pool = &data.db;
+    let classroom_id = classroom_id.into_inner();
+
+    let roster = match Classroom::get_roster(pool, classroom_id).await {
+        Ok(roster) => roster,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/evaluations/classrooms/{classroom_id}/sign_off"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::NotFound().json(response);
+        }
+    };
+
+    // only a homeroom advisor of the classroom can sign off, admins lock the report instead
+    let teacher_id = match user.teacher {
+        Some(teacher_id) if roster.advisors.contains(&(teacher_id as i64)) => teacher_id as i64,
+        _ => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 403,
+                    error_type: "forbidden".to_string(),
+                    detail: "the user is not a classroom advisor".to_string(),
+                    source: format!("/evaluations/classrooms/{classroom_id}/sign_off"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Forbidden().json(response);
+        }
+    };
+
+    match EvaluationReport::sign_off(pool, &roster, teacher_id).await {
+        Ok(()) => (),
+        Err(e @ EvaluationError::Locked) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 409,
+                    error_type: "conflict".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/evaluations/classrooms/{classroom_id}/sign_off"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Conflict().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/evaluations/classrooms/{classroom_id}/sign_off"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    let report = EvaluationReport::get_by_roster(
+        pool,
+        &roster,
+        request.fetch_level.clone(),
+        request.descendant_fetch_level.clone(),
+    )
+    .await;
+
+    match report {
+        Ok(report) => {
+            let response: ResponseType<EvaluationReport, _> =
+                ResponseType::new(report, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/evaluations/classrooms/{classroom_id}/sign_off"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
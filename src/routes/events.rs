@@ -0,0 +1,148 @@
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use serde_qs;
+use uuid::Uuid;
+
+use crate::structs::{
+    auth::User,
+    club_event::{CalendarSubscription, CalendarToken, ClubEvent, QueryableClubEvent},
+    common::{ErrorResponseType, ErrorType, MetadataType, RequestType, ResponseType},
+};
+
+use crate::AppState;
+
+// events of every club the user belongs to
+#[get("/events")]
+pub async fn query_user_events(
+    data: web::Data<AppState>,
+    user: User,
+    request: HttpRequest,
+) -> impl Responder {
+    let pool = &data.db;
+
+    let request_query = serde_qs::from_str::<RequestType<ClubEvent, QueryableClubEvent, String>>(
+        &request.query_string(),
+    );
+
+    let request_query = match request_query {
+        Ok(request_query) => request_query,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: e.to_string(),
+                    source: "/events".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    let filter = request_query
+        .filter
+        .as_ref()
+        .and_then(|filter| filter.data.as_ref());
+
+    match ClubEvent::get_by_user(pool, &user, filter).await {
+        Ok(events) => {
+            let response: ResponseType<Vec<ClubEvent>, _> =
+                ResponseType::new(events, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: "/events".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+// replaces any previous token, so the old subscription url stops working
+#[post("/events/calendar_token")]
+pub async fn create_calendar_token(data: web::Data<AppState>, user: User) -> impl Responder {
+    let pool = &data.db;
+
+    match CalendarToken::rotate(pool, user.id).await {
+        Ok(subscription) => {
+            let response: ResponseType<CalendarSubscription, _> =
+                ResponseType::new(subscription, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Created().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: "/events/calendar_token".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+// the token stands in for the bearer token calendar apps can't send
+#[get("/events/calendar/{token}.ics")]
+pub async fn get_user_calendar(
+    data: web::Data<AppState>,
+    token: web::Path<Uuid>,
+) -> impl Responder {
+    let pool = &data.db;
+    let token = token.into_inner();
+
+    let user = match CalendarToken::get_user(pool, token).await {
+        Ok(user) => user,
+        Err(_) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: "calendar not found".to_string(),
+                    source: "/events/calendar".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::NotFound().json(response);
+        }
+    };
+
+    match ClubEvent::get_by_user(pool, &user, None).await {
+        Ok(events) => HttpResponse::Ok()
+            .content_type("text/calendar; charset=utf-8")
+            .body(ClubEvent::to_ical("MySK Clubs", &events, true)),
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: "/events/calendar".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
@@ -1,9 +1,20 @@
 use actix_web::web;
 
+pub(crate) mod admin;
+pub(crate) mod allocations;
+pub(crate) mod announcements;
+pub(crate) mod audit;
+pub(crate) mod certificates;
+pub(crate) mod classrooms;
 pub(crate) mod clubs;
+pub(crate) mod evaluations;
+pub(crate) mod events;
 pub(crate) mod health;
 pub(crate) mod index;
+pub(crate) mod preferences;
+pub(crate) mod registration;
 pub(crate) mod test_auth;
+pub(crate) mod unregistered_students;
 // pub(crate) mod
 
 use utoipa::{
@@ -13,7 +24,9 @@ use utoipa::{
 use utoipa_swagger_ui::SwaggerUi;
 
 use crate::structs::{
-    auth, classroom, club_request, clubs as clubsType, common, contacts, student,
+    allocation, auth, classroom, club_announcement, club_event, club_import, club_member_history,
+    club_request, club_session, clubs as clubsType, common, contacts, evaluation,
+    registration as registrationType, rollover, student, teacher, unregistered_student,
 };
 
 struct SecurityAddon;
@@ -57,11 +70,40 @@ impl Modify for SecurityAddon {
         clubsType::Club,
         contacts::Contact,
         classroom::Classroom,
+        classroom::ClassroomRegistrationStats,
+        classroom::ClassroomWithRegistration,
         student::Student,
+        teacher::IdOnlyTeacher,
+        teacher::CompactTeacher,
+        teacher::DefaultTeacher,
+        teacher::Teacher,
         common::MultiLangString,
         auth::User,
         auth::UserRoles,
         club_request::ClubRequestTable,
+        club_request::BulkUpdateClubRequestResult,
+        club_member_history::ClubMemberHistory,
+        club_session::ClubSession,
+        club_session::AttendanceStatus,
+        club_announcement::ClubAnnouncement,
+        club_announcement::AnnouncementVisibility,
+        club_event::ClubEvent,
+        club_event::CalendarSubscription,
+        club_import::ClubImportRow,
+        club_import::ClubImportReport,
+        registrationType::RegistrationSettings,
+        registrationType::RegistrationWindow,
+        allocation::ClubPreferences,
+        allocation::AllocationAssignment,
+        allocation::AllocationReport,
+        allocation::AllocationRun,
+        rollover::RolledOverClub,
+        rollover::RolloverReport,
+        evaluation::ClubEvaluation,
+        evaluation::EvaluationResult,
+        unregistered_student::UnregisteredStudent,
+        unregistered_student::UnregisteredClassroom,
+        unregistered_student::UnregisteredStudentReport,
     )),
     modifiers(&SecurityAddon)
 )]
@@ -74,11 +116,74 @@ pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(clubs::club_detail::get_club_by_id);
     cfg.service(clubs::club_detail::update_club_by_id);
     cfg.service(clubs::clubs::query_clubs);
+    cfg.service(clubs::clubs::create_club);
     cfg.service(clubs::club_contact::create_contact_for_club);
+    cfg.service(clubs::club_staff::add_club_staff);
+    cfg.service(clubs::club_staff::remove_club_staff);
+    cfg.service(clubs::club_staff::transfer_club_president);
+    cfg.service(clubs::club_advisor::add_club_advisor);
+    cfg.service(clubs::club_advisor::remove_club_advisor);
+    // before /join_requests/{join_request_id}, which would take export.csv as an id
+    cfg.service(clubs::club_roster_export::export_roster_csv);
+    cfg.service(clubs::club_roster_export::export_roster_xlsx);
     cfg.service(clubs::club_join_request::query_club_requests);
+    cfg.service(clubs::club_join_request::bulk_update_club_requests);
     cfg.service(clubs::club_join_request_detail::get_club_request_by_id);
     cfg.service(clubs::club_join_request_detail::approve_or_reject_club_request);
+    cfg.service(clubs::club_join_request_detail::withdraw_club_request);
+    cfg.service(clubs::club_join_request_detail::get_club_request_history);
     cfg.service(clubs::join_club::join_club_by_id);
+    cfg.service(clubs::club_session::query_club_sessions);
+    cfg.service(clubs::club_session::create_club_session);
+    cfg.service(clubs::club_session::delete_club_session);
+    cfg.service(clubs::club_session::get_session_attendance);
+    cfg.service(clubs::club_session::update_session_attendance);
+    cfg.service(clubs::club_session::query_club_attendance_rates);
+    cfg.service(clubs::club_announcement::query_club_announcements);
+    cfg.service(clubs::club_announcement::create_club_announcement);
+    cfg.service(clubs::club_announcement::update_club_announcement);
+    cfg.service(clubs::club_announcement::delete_club_announcement);
+    cfg.service(announcements::get_announcement_feed);
+    cfg.service(clubs::club_roster_document::get_club_roster_pdf);
+    cfg.service(certificates::get_membership_certificate);
+    cfg.service(clubs::club_event::query_club_events);
+    cfg.service(clubs::club_event::get_club_calendar);
+    cfg.service(clubs::club_event::create_club_event);
+    cfg.service(clubs::club_event::update_club_event);
+    cfg.service(clubs::club_event::delete_club_event);
+    cfg.service(events::query_user_events);
+    cfg.service(events::create_calendar_token);
+    cfg.service(events::get_user_calendar);
+    cfg.service(registration::query_registration_windows);
+    cfg.service(registration::create_registration_window);
+    cfg.service(registration::update_registration_window);
+    cfg.service(registration::delete_registration_window);
+    cfg.service(preferences::get_preferences);
+    cfg.service(preferences::update_preferences);
+    cfg.service(allocations::query_allocation_runs);
+    cfg.service(allocations::get_allocation_run_by_id);
+    cfg.service(allocations::create_allocation_run);
+    cfg.service(audit::query_club_member_history);
+    cfg.service(classrooms::query_classrooms);
+    cfg.service(classrooms::get_classroom_by_id);
+    cfg.service(unregistered_students::query_unregistered_students);
+    cfg.service(unregistered_students::export_unregistered_students_csv);
+    cfg.service(unregistered_students::export_unregistered_students_xlsx);
+    cfg.service(evaluations::get_evaluation_report);
+    cfg.service(evaluations::sign_off_evaluation_report);
+    cfg.service(admin::join_requests::override_club_request);
+    cfg.service(admin::club_import::import_clubs);
+    cfg.service(admin::clubs::update_any_club_by_id);
+    cfg.service(admin::clubs::archive_club_by_id);
+    cfg.service(admin::clubs::unarchive_club_by_id);
+    cfg.service(admin::clubs::delete_club_by_id);
+    cfg.service(admin::rollover::create_rollover);
+    cfg.service(admin::evaluations::lock_evaluation_report);
+    cfg.service(admin::evaluations::unlock_evaluation_report);
+    cfg.service(admin::registration_settings::query_registration_settings);
+    cfg.service(admin::registration_settings::get_registration_settings_by_year);
+    cfg.service(admin::registration_settings::update_registration_settings);
+    cfg.service(admin::registration_settings::delete_registration_settings);
     cfg.service(
         SwaggerUi::new("/swagger-ui/{_:.*}").url("/api-docs/openapi.json", ApiDoc::openapi()),
     );
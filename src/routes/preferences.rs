@@ -0,0 +1,380 @@
+use std::collections::HashSet;
+
+use actix_web::{get, put, web, HttpRequest, HttpResponse, Responder};
+use chrono::Utc;
+use serde_qs;
+use uuid::Uuid;
+
+use crate::structs::{
+    allocation::{ClubPreferences, UpdatableClubPreferences},
+    classroom::Classroom,
+    clubs::Club,
+    common::{ErrorResponseType, ErrorType, MetadataType, RequestType, ResponseType},
+    registration::{RegistrationSettings, RegistrationStatus, RegistrationWindow},
+    student::Student,
+};
+
+use crate::utils::date::get_current_academic_year;
+
+use crate::AppState;
+
+#[get("/preferences")]
+pub async fn get_preferences(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    student: Student,
+) -> impl Responder {
+    let pool = &data.db;
+
+    let request_query =
+        serde_qs::from_str::<RequestType<String, String, String>>(&request.query_string());
+
+    let request_query = match request_query {
+        Ok(request_query) => request_query,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: e.to_string(),
+                    source: "/preferences".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    let student_id = match student {
+        Student::IdOnly(student) => student.id,
+        Student::Compact(student) => student.id,
+        Student::Default(student) => student.id,
+    };
+
+    match ClubPreferences::get_by_student_id(
+        pool,
+        student_id as i64,
+        get_current_academic_year() as i64,
+        request_query.fetch_level,
+        request_query.descendant_fetch_level,
+    )
+    .await
+    {
+        Ok(preferences) => {
+            let response: ResponseType<ClubPreferences, _> =
+                ResponseType::new(preferences, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: "/preferences".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[put("/preferences")]
+pub async fn update_preferences(
+    data: web::Data<AppState>,
+    student: Student,
+    request: web::Json<RequestType<UpdatableClubPreferences, String, String>>,
+) -> impl Responder {
+    let pool = &data.db;
+    let year = get_current_academic_year() as i64;
+
+    let preferences = match &request.data {
+        Some(data) => data,
+        None => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: "request body is empty".to_string(),
+                    source: "/preferences".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    let student_id = match student {
+        Student::IdOnly(student) => student.id,
+        Student::Compact(student) => student.id,
+        Student::Default(student) => student.id,
+    };
+
+    let settings = match RegistrationSettings::get_by_year(pool, year).await {
+        Ok(settings) => settings,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: "/preferences".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    };
+
+    if !settings.lottery_enabled {
+        let response: ErrorResponseType = ErrorResponseType::new(
+            ErrorType {
+                id: Uuid::new_v4().to_string(),
+                code: 409,
+                error_type: "lottery_disabled".to_string(),
+                detail: "clubs are not allocated by lottery this year, join clubs directly instead"
+                    .to_string(),
+                source: "/preferences".to_string(),
+            },
+            None::<MetadataType>,
+        );
+
+        return HttpResponse::Conflict().json(response);
+    }
+
+    if preferences.clubs.len() as i64 > settings.max_preferences {
+        let response: ErrorResponseType = ErrorResponseType::new(
+            ErrorType {
+                id: Uuid::new_v4().to_string(),
+                code: 400,
+                error_type: "bad_request".to_string(),
+                detail: format!(
+                    "at most {} preferences can be submitted",
+                    settings.max_preferences
+                ),
+                source: "/preferences".to_string(),
+            },
+            None::<MetadataType>,
+        );
+
+        return HttpResponse::BadRequest().json(response);
+    }
+
+    if preferences.clubs.iter().collect::<HashSet<_>>().len() != preferences.clubs.len() {
+        let response: ErrorResponseType = ErrorResponseType::new(
+            ErrorType {
+                id: Uuid::new_v4().to_string(),
+                code: 400,
+                error_type: "bad_request".to_string(),
+                detail: "a club can only be ranked once".to_string(),
+                source: "/preferences".to_string(),
+            },
+            None::<MetadataType>,
+        );
+
+        return HttpResponse::BadRequest().json(response);
+    }
+
+    for club_id in preferences.clubs.iter() {
+        match Club::get_year(pool, *club_id).await {
+            Ok(club_year) if club_year == year => (),
+            Ok(club_year) => {
+                let response: ErrorResponseType = ErrorResponseType::new(
+                    ErrorType {
+                        id: Uuid::new_v4().to_string(),
+                        code: 409,
+                        error_type: "conflict".to_string(),
+                        detail: format!("club {club_id} runs in {club_year}, not in {year}"),
+                        source: "/preferences".to_string(),
+                    },
+                    None::<MetadataType>,
+                );
+
+                return HttpResponse::Conflict().json(response);
+            }
+            Err(sqlx::Error::RowNotFound) => {
+                let response: ErrorResponseType = ErrorResponseType::new(
+                    ErrorType {
+                        id: Uuid::new_v4().to_string(),
+                        code: 404,
+                        error_type: "not_found".to_string(),
+                        detail: format!("club {club_id} does not exist"),
+                        source: "/preferences".to_string(),
+                    },
+                    None::<MetadataType>,
+                );
+
+                return HttpResponse::NotFound().json(response);
+            }
+            Err(e) => {
+                let response: ErrorResponseType = ErrorResponseType::new(
+                    ErrorType {
+                        id: Uuid::new_v4().to_string(),
+                        code: 500,
+                        error_type: "internal_server_error".to_string(),
+                        detail: e.to_string(),
+                        source: "/preferences".to_string(),
+                    },
+                    None::<MetadataType>,
+                );
+
+                return HttpResponse::InternalServerError().json(response);
+            }
+        }
+
+        match Club::is_archived(pool, *club_id).await {
+            Ok(false) => (),
+            Ok(true) => {
+                let response: ErrorResponseType = ErrorResponseType::new(
+                    ErrorType {
+                        id: Uuid::new_v4().to_string(),
+                        code: 409,
+                        error_type: "conflict".to_string(),
+                        detail: format!("club {club_id} is archived"),
+                        source: "/preferences".to_string(),
+                    },
+                    None::<MetadataType>,
+                );
+
+                return HttpResponse::Conflict().json(response);
+            }
+            Err(sqlx::Error::RowNotFound) => {
+                let response: ErrorResponseType = ErrorResponseType::new(
+                    ErrorType {
+                        id: Uuid::new_v4().to_string(),
+                        code: 404,
+                        error_type: "not_found".to_string(),
+                        detail: format!("club {club_id} does not exist"),
+                        source: "/preferences".to_string(),
+                    },
+                    None::<MetadataType>,
+                );
+
+                return HttpResponse::NotFound().json(response);
+            }
+            Err(e) => {
+                let response: ErrorResponseType = ErrorResponseType::new(
+                    ErrorType {
+                        id: Uuid::new_v4().to_string(),
+                        code: 500,
+                        error_type: "internal_server_error".to_string(),
+                        detail: e.to_string(),
+                        source: "/preferences".to_string(),
+                    },
+                    None::<MetadataType>,
+                );
+
+                return HttpResponse::InternalServerError().json(response);
+            }
+        }
+    }
+
+    // preferences can only be changed while registration is open for the student's grade
+    let grade = match Classroom::get_grade_by_student_id(pool, student_id, None).await {
+        Ok(grade) => grade,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: "/preferences".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    };
+
+    match RegistrationWindow::get_status(pool, year, grade, Utc::now()).await {
+        Ok(RegistrationStatus::Open) => (),
+        Ok(status @ RegistrationStatus::Closed { .. }) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 403,
+                    error_type: "registration_closed".to_string(),
+                    detail: status.to_string(),
+                    source: "/preferences".to_string(),
+                },
+                status.to_metadata(),
+            );
+
+            return HttpResponse::Forbidden().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: "/preferences".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    if let Err(e) =
+        ClubPreferences::replace(pool, student_id as i64, year, &preferences.clubs).await
+    {
+        let response: ErrorResponseType = ErrorResponseType::new(
+            ErrorType {
+                id: Uuid::new_v4().to_string(),
+                code: 500,
+                error_type: "internal_server_error".to_string(),
+                detail: e.to_string(),
+                source: "/preferences".to_string(),
+            },
+            None::<MetadataType>,
+        );
+
+        return HttpResponse::InternalServerError().json(response);
+    }
+
+    match ClubPreferences::get_by_student_id(
+        pool,
+        student_id as i64,
+        year,
+        request.fetch_level.clone(),
+        request.descendant_fetch_level.clone(),
+    )
+    .await
+    {
+        Ok(preferences) => {
+            let response: ResponseType<ClubPreferences, _> =
+                ResponseType::new(preferences, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: "/preferences".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
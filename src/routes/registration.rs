@@ -0,0 +1,280 @@
+use actix_web::{delete, get, patch, post, web, HttpRequest, HttpResponse, Responder};
+use serde_qs;
+use uuid::Uuid;
+
+use crate::structs::{
+    auth::Admin,
+    common::{ErrorResponseType, ErrorType, MetadataType, RequestType, ResponseType},
+    registration::{
+        CreatableRegistrationWindow, QueryableRegistrationWindow, RegistrationWindow,
+        UpdatableRegistrationWindow,
+    },
+};
+
+use crate::utils::date::get_current_academic_year;
+
+use crate::AppState;
+
+#[get("/registration_windows")]
+pub async fn query_registration_windows(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+) -> impl Responder {
+    let pool = &data.db;
+
+    let request_query = serde_qs::from_str::<
+        RequestType<RegistrationWindow, QueryableRegistrationWindow, String>,
+    >(&request.query_string());
+
+    let request_query = match request_query {
+        Ok(request_query) => request_query,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: e.to_string(),
+                    source: "/registration_windows".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    let filter = request_query
+        .filter
+        .as_ref()
+        .and_then(|filter| filter.data.as_ref());
+
+    match RegistrationWindow::query(pool, filter).await {
+        Ok(windows) => {
+            let response: ResponseType<Vec<RegistrationWindow>, _> =
+                ResponseType::new(windows, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: "/registration_windows".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[post("/admin/registration_windows")]
+pub async fn create_registration_window(
+    data: web::Data<AppState>,
+    _admin: Admin,
+    request: web::Json<
+        RequestType<CreatableRegistrationWindow, QueryableRegistrationWindow, String>,
+    >,
+) -> impl Responder {
+    let pool = &data.db;
+
+    let data = match &request.data {
+        Some(data) => data,
+        None => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: "request body is empty".to_string(),
+                    source: "/admin/registration_windows".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    if data.opens_at >= data.closes_at {
+        let response: ErrorResponseType = ErrorResponseType::new(
+            ErrorType {
+                id: Uuid::new_v4().to_string(),
+                code: 400,
+                error_type: "bad_request".to_string(),
+                detail: "opens_at must be before closes_at".to_string(),
+                source: "/admin/registration_windows".to_string(),
+            },
+            None::<MetadataType>,
+        );
+
+        return HttpResponse::BadRequest().json(response);
+    }
+
+    let year = match data.year {
+        Some(year) => year,
+        None => get_current_academic_year() as i64,
+    };
+
+    match RegistrationWindow::create(pool, data, year).await {
+        Ok(window) => {
+            let response: ResponseType<RegistrationWindow, _> =
+                ResponseType::new(window, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: "/admin/registration_windows".to_string(),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[patch("/admin/registration_windows/{window_id}")]
+pub async fn update_registration_window(
+    data: web::Data<AppState>,
+    window_id: web::Path<Uuid>,
+    _admin: Admin,
+    request: web::Json<
+        RequestType<UpdatableRegistrationWindow, QueryableRegistrationWindow, String>,
+    >,
+) -> impl Responder {
+    let pool = &data.db;
+    let window_id = window_id.into_inner();
+
+    let data = match &request.data {
+        Some(data) => data,
+        None => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: "request body is empty".to_string(),
+                    source: format!("/admin/registration_windows/{window_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    let window = match RegistrationWindow::get_by_id(pool, window_id).await {
+        Ok(window) => window,
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/admin/registration_windows/{window_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::NotFound().json(response);
+        }
+    };
+
+    if data.opens_at.unwrap_or(window.opens_at) >= data.closes_at.unwrap_or(window.closes_at) {
+        let response: ErrorResponseType = ErrorResponseType::new(
+            ErrorType {
+                id: Uuid::new_v4().to_string(),
+                code: 400,
+                error_type: "bad_request".to_string(),
+                detail: "opens_at must be before closes_at".to_string(),
+                source: format!("/admin/registration_windows/{window_id}"),
+            },
+            None::<MetadataType>,
+        );
+
+        return HttpResponse::BadRequest().json(response);
+    }
+
+    match RegistrationWindow::update_by_id(pool, window_id, data).await {
+        Ok(window) => {
+            let response: ResponseType<RegistrationWindow, _> =
+                ResponseType::new(window, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/admin/registration_windows/{window_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
+#[delete("/admin/registration_windows/{window_id}")]
+pub async fn delete_registration_window(
+    data: web::Data<AppState>,
+    window_id: web::Path<Uuid>,
+    _admin: Admin,
+) -> impl Responder {
+    let pool = &data.db;
+    let window_id = window_id.into_inner();
+
+    match RegistrationWindow::delete_by_id(pool, window_id).await {
+        Ok(_) => {
+            let response: ResponseType<Uuid, _> =
+                ResponseType::new(window_id, None::<String>, None::<MetadataType>);
+
+            HttpResponse::Ok().json(response)
+        }
+        Err(sqlx::Error::RowNotFound) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: format!("registration window with id {} not found", window_id),
+                    source: format!("/admin/registration_windows/{window_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::NotFound().json(response)
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source: format!("/admin/registration_windows/{window_id}"),
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+use serde_qs;
+use uuid::Uuid;
+
+use crate::structs::{
+    auth::{User, UserRoles},
+    classroom::Classroom,
+    common::{ErrorResponseType, ErrorType, MetadataType, RequestType, ResponseType},
+    unregistered_student::{QueryableUnregisteredStudent, UnregisteredStudentReport},
+};
+
+use crate::AppState;
+
+enum ReportFormat {
+    Json,
+    Csv,
+    Xlsx,
+}
+
+// students of a classroom or grade without an approved or pending club membership
+#[get("/unregistered_students")]
+pub async fn query_unregistered_students(
+    data: web::Data<AppState>,
+    user: User,
+    request: HttpRequest,
+) -> impl Responder {
+    unregistered_students(&data, &user, &request, ReportFormat::Json).await
+}
+
+#[get("/unregistered_students/export.csv")]
+pub async fn export_unregistered_students_csv(
+    data: web::Data<AppState>,
+    user: User,
+    request: HttpRequest,
+) -> impl Responder {
+    unregistered_students(&data, &user, &request, ReportFormat::Csv).await
+}
+
+#[get("/unregistered_students/export.xlsx")]
+pub async fn export_unregistered_students_xlsx(
+    data: web::Data<AppState>,
+    user: User,
+    request: HttpRequest,
+) -> impl Responder {
+    unregistered_students(&data, &user, &request, ReportFormat::Xlsx).await
+}
+
+// homeroom advisors can see their classroom, whole grades are open to any teacher
+async fn unregistered_students(
+    data: &web::Data<AppState>,
+    user: &User,
+    request: &HttpRequest,
+    format: ReportFormat,
+) -> HttpResponse {
+    let pool = &data.db;
+    let source = request.path().to_string();
+
+    let request_query = serde_qs::from_str::<
+        RequestType<String, QueryableUnregisteredStudent, String>,
+    >(request.query_string());
+
+    let filter = match request_query
+        .map(|request_query| request_query.filter.and_then(|filter| filter.data))
+    {
+        Ok(Some(filter)) if filter.classroom_id.is_some() || filter.grade.is_some() => filter,
+        Ok(_) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: "filter by a classroom_id or a grade".to_string(),
+                    source,
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 400,
+                    error_type: "bad_request".to_string(),
+                    detail: e.to_string(),
+                    source,
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::BadRequest().json(response);
+        }
+    };
+
+    let is_allowed = match (user.is_admin, &user.role, filter.classroom_id, user.teacher) {
+        (true, _, _, _) => Ok(true),
+        (false, UserRoles::Teacher, Some(classroom_id), Some(teacher_id)) => {
+            match Classroom::get_roster(pool, classroom_id as u32).await {
+                Ok(roster) => Ok(roster.advisors.contains(&(teacher_id as i64))),
+                Err(sqlx::Error::RowNotFound) => Ok(false),
+                Err(e) => Err(e),
+            }
+        }
+        (false, UserRoles::Teacher, None, _) => Ok(true),
+        _ => Ok(false),
+    };
+
+    match is_allowed {
+        Ok(true) => (),
+        Ok(false) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 403,
+                    error_type: "forbidden".to_string(),
+                    detail: "the user is not a teacher or not an advisor of the classroom"
+                        .to_string(),
+                    source,
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::Forbidden().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source,
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    }
+
+    let report = match UnregisteredStudentReport::query(pool, &filter).await {
+        Ok(report) => report,
+        Err(sqlx::Error::RowNotFound) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 404,
+                    error_type: "entity_not_found".to_string(),
+                    detail: "classroom not found".to_string(),
+                    source,
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::NotFound().json(response);
+        }
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source,
+                },
+                None::<MetadataType>,
+            );
+
+            return HttpResponse::InternalServerError().json(response);
+        }
+    };
+
+    let (body, content_type, file_name) = match format {
+        ReportFormat::Json => {
+            let response: ResponseType<UnregisteredStudentReport, _> =
+                ResponseType::new(report, None::<String>, None::<MetadataType>);
+
+            return HttpResponse::Ok().json(response);
+        }
+        ReportFormat::Csv => (
+            report.to_csv(),
+            "text/csv; charset=utf-8",
+            "unregistered_students.csv",
+        ),
+        ReportFormat::Xlsx => (
+            report.to_xlsx(),
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            "unregistered_students.xlsx",
+        ),
+    };
+
+    match body {
+        Ok(body) => HttpResponse::Ok()
+            .content_type(content_type)
+            .insert_header((
+                "Content-Disposition",
+                format!("attachment; filename=\"{file_name}\""),
+            ))
+            .body(body),
+        Err(e) => {
+            let response: ErrorResponseType = ErrorResponseType::new(
+                ErrorType {
+                    id: Uuid::new_v4().to_string(),
+                    code: 500,
+                    error_type: "internal_server_error".to_string(),
+                    detail: e.to_string(),
+                    source,
+                },
+                None::<MetadataType>,
+            );
+
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
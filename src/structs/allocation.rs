@@ -0,0 +1,520 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{types::Json, FromRow};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::utils::random::SeededRandom;
+
+use super::{
+    club_member_history::ClubMemberHistory,
+    club_request::ClubRequestTable,
+    clubs::{Club, SubmissionStatus},
+    common::FetchLevel,
+    registration::RegistrationSettings,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ClubPreferenceTable {
+    pub id: Uuid,
+    pub student_id: i64,
+    pub year: i64,
+    pub club_id: Uuid,
+    pub rank: i64,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdatableClubPreferences {
+    // club ids ordered from the most to the least preferred
+    pub clubs: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ClubPreferences {
+    pub student_id: i64,
+    pub year: i64,
+    pub clubs: Vec<Club>,
+}
+
+impl ClubPreferences {
+    pub async fn get_by_student_id(
+        pool: &sqlx::PgPool,
+        student_id: i64,
+        year: i64,
+        fetch_level: Option<FetchLevel>,
+        descendant_fetch_level: Option<FetchLevel>,
+    ) -> Result<Self, sqlx::Error> {
+        let preferences = sqlx::query_as::<_, ClubPreferenceTable>(
+            r#"
+            SELECT id, student_id, year, club_id, rank, created_at FROM club_preferences
+            WHERE student_id = $1 AND year = $2
+            ORDER BY rank
+            "#,
+        )
+        .bind(student_id)
+        .bind(year)
+        .fetch_all(pool)
+        .await?;
+
+        let mut clubs = Vec::new();
+
+        for preference in preferences {
+            clubs.push(
+                Club::get_by_id(
+                    pool,
+                    preference.club_id,
+                    fetch_level.clone(),
+                    descendant_fetch_level.clone(),
+                )
+                .await?,
+            );
+        }
+
+        Ok(Self {
+            student_id,
+            year,
+            clubs,
+        })
+    }
+
+    // replace the whole ranked list of the student for the year
+    pub async fn replace(
+        pool: &sqlx::PgPool,
+        student_id: i64,
+        year: i64,
+        clubs: &[Uuid],
+    ) -> Result<(), sqlx::Error> {
+        let mut transaction = pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM club_preferences WHERE student_id = $1 AND year = $2
+            "#,
+        )
+        .bind(student_id)
+        .bind(year)
+        .execute(&mut transaction)
+        .await?;
+
+        for (i, club_id) in clubs.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO club_preferences (student_id, year, club_id, rank) VALUES ($1, $2, $3, $4)
+                "#,
+            )
+            .bind(student_id)
+            .bind(year)
+            .bind(club_id)
+            .bind(i as i64 + 1)
+            .execute(&mut transaction)
+            .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatableAllocationRun {
+    pub year: Option<i64>,
+    // pass the seed of a previous run to reproduce it
+    pub seed: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryableAllocationRun {
+    pub year: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AllocationAssignment {
+    pub student_id: i64,
+    #[schema(value_type = String)]
+    pub club_id: Uuid,
+    pub rank: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AllocationReport {
+    pub assigned: Vec<AllocationAssignment>,
+    pub unassigned: Vec<i64>,
+}
+
+impl AllocationReport {
+    // Students are shuffled once with the seed, then every preference rank is filled in
+    // turn: all first choices in shuffled order, then all second choices of the students
+    // left over, and so on. remaining_seats of None means the club has no capacity limit.
+    pub fn allocate(
+        preferences: &[ClubPreferenceTable],
+        remaining_seats: &HashMap<Uuid, Option<i64>>,
+        seed: i64,
+    ) -> Self {
+        let mut by_student: HashMap<i64, Vec<&ClubPreferenceTable>> = HashMap::new();
+
+        for preference in preferences {
+            by_student
+                .entry(preference.student_id)
+                .or_default()
+                .push(preference);
+        }
+
+        for student_preferences in by_student.values_mut() {
+            student_preferences.sort_by_key(|preference| preference.rank);
+        }
+
+        // sort before shuffling so the outcome only depends on the seed and the data
+        let mut students = by_student.keys().copied().collect::<Vec<i64>>();
+        students.sort();
+        SeededRandom::new(seed as u64).shuffle(&mut students);
+
+        let max_rank = by_student
+            .values()
+            .map(|student_preferences| student_preferences.len())
+            .max()
+            .unwrap_or(0);
+
+        let mut remaining_seats = remaining_seats.clone();
+        let mut assigned = Vec::new();
+        let mut assigned_students = HashSet::new();
+
+        for round in 0..max_rank {
+            for student_id in students.iter() {
+                if assigned_students.contains(student_id) {
+                    continue;
+                }
+
+                let preference = match by_student[student_id].get(round) {
+                    Some(preference) => preference,
+                    None => continue,
+                };
+
+                let seats = remaining_seats.entry(preference.club_id).or_insert(Some(0));
+
+                match seats {
+                    Some(seats) if *seats <= 0 => continue,
+                    Some(seats) => *seats -= 1,
+                    None => (),
+                }
+
+                assigned_students.insert(*student_id);
+                assigned.push(AllocationAssignment {
+                    student_id: *student_id,
+                    club_id: preference.club_id,
+                    rank: preference.rank,
+                });
+            }
+        }
+
+        let mut unassigned = students
+            .into_iter()
+            .filter(|student_id| !assigned_students.contains(student_id))
+            .collect::<Vec<i64>>();
+        unassigned.sort();
+
+        Self {
+            assigned,
+            unassigned,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+struct AllocationRunTable {
+    pub id: Uuid,
+    pub year: i64,
+    pub seed: i64,
+    pub created_by: Option<Uuid>,
+    pub report: Json<AllocationReport>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AllocationRun {
+    #[schema(value_type = String)]
+    pub id: Uuid,
+    pub year: i64,
+    pub seed: i64,
+    #[schema(value_type = Option<String>)]
+    pub created_by: Option<Uuid>,
+    pub report: AllocationReport,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl AllocationRun {
+    fn from_table(table: AllocationRunTable) -> Self {
+        Self {
+            id: table.id,
+            year: table.year,
+            seed: table.seed,
+            created_by: table.created_by,
+            report: table.report.0,
+            created_at: table.created_at,
+        }
+    }
+
+    pub async fn get_by_id(pool: &sqlx::PgPool, id: Uuid) -> Result<Self, sqlx::Error> {
+        let res = sqlx::query_as::<_, AllocationRunTable>(
+            r#"
+            SELECT id, year, seed, created_by, report, created_at FROM allocation_runs WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(Self::from_table(res))
+    }
+
+    pub async fn query(pool: &sqlx::PgPool, year: Option<i64>) -> Result<Vec<Self>, sqlx::Error> {
+        let res = sqlx::query_as::<_, AllocationRunTable>(
+            r#"
+            SELECT id, year, seed, created_by, report, created_at FROM allocation_runs
+            WHERE ($1::bigint IS NULL OR year = $1)
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(year)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(res.into_iter().map(Self::from_table).collect())
+    }
+
+    // allocate every submitted preference of the year and write the outcome into club_members
+    pub async fn run(
+        pool: &sqlx::PgPool,
+        year: i64,
+        seed: i64,
+        created_by: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let mut transaction = pool.begin().await?;
+        ClubMemberHistory::set_actor(&mut transaction, created_by).await?;
+
+        let settings = RegistrationSettings::get_by_year(&mut transaction, year).await?;
+
+        let memberships = sqlx::query_as::<_, (i64, Uuid)>(
+            r#"
+            SELECT student_id, club_id FROM club_members
+            WHERE year = $1 AND membership_status = 'approved'
+            "#,
+        )
+        .bind(year)
+        .fetch_all(&mut transaction)
+        .await?
+        .into_iter()
+        .collect::<HashSet<(i64, Uuid)>>();
+
+        let mut joined_clubs: HashMap<i64, i64> = HashMap::new();
+
+        for (student_id, _) in memberships.iter() {
+            *joined_clubs.entry(*student_id).or_default() += 1;
+        }
+
+        // students who already hold as many clubs as they are allowed to are left out,
+        // as are clubs the student is already a member of and clubs of another year
+        let preferences = sqlx::query_as::<_, ClubPreferenceTable>(
+            r#"
+            SELECT club_preferences.id, student_id, club_preferences.year, club_id, rank, club_preferences.created_at
+            FROM club_preferences INNER JOIN clubs ON clubs.id = club_preferences.club_id
+            WHERE club_preferences.year = $1 AND clubs.year = $1
+            ORDER BY student_id, rank
+            "#,
+        )
+        .bind(year)
+        .fetch_all(&mut transaction)
+        .await?
+        .into_iter()
+        .filter(|preference| {
+            joined_clubs
+                .get(&preference.student_id)
+                .copied()
+                .unwrap_or(0)
+                < settings.max_clubs_per_student
+                && !memberships.contains(&(preference.student_id, preference.club_id))
+        })
+        .collect::<Vec<ClubPreferenceTable>>();
+
+        let mut club_ids = preferences
+            .iter()
+            .map(|preference| preference.club_id)
+            .collect::<Vec<Uuid>>();
+        club_ids.sort();
+        club_ids.dedup();
+
+        // lock the clubs so that no approval slips in while the seats are handed out
+        let capacities = sqlx::query_as::<_, (Uuid, Option<i64>)>(
+            r#"
+            SELECT id, capacity FROM clubs WHERE id = ANY($1) ORDER BY id FOR UPDATE
+            "#,
+        )
+        .bind(&club_ids)
+        .fetch_all(&mut transaction)
+        .await?;
+
+        // allocated seats are in the semester the club runs in, like in lock_club_seats
+        let approved = sqlx::query_as::<_, (Uuid, i64)>(
+            r#"
+            SELECT club_id, COUNT(club_members.id) FROM club_members
+            INNER JOIN clubs ON clubs.id = club_members.club_id
+            WHERE club_id = ANY($1) AND club_members.year = $2 AND membership_status = 'approved'
+                AND (clubs.semester IS NULL OR club_members.semester IS NOT DISTINCT FROM clubs.semester OR club_members.semester IS NULL)
+            GROUP BY club_id
+            "#,
+        )
+        .bind(&club_ids)
+        .bind(year)
+        .fetch_all(&mut transaction)
+        .await?
+        .into_iter()
+        .collect::<HashMap<Uuid, i64>>();
+
+        let remaining_seats = capacities
+            .into_iter()
+            .map(|(club_id, capacity)| {
+                let approved = approved.get(&club_id).copied().unwrap_or(0);
+
+                (club_id, capacity.map(|capacity| capacity - approved))
+            })
+            .collect::<HashMap<Uuid, Option<i64>>>();
+
+        let report = AllocationReport::allocate(&preferences, &remaining_seats, seed);
+
+        let assigned_clubs = report
+            .assigned
+            .iter()
+            .map(|assignment| (assignment.student_id, assignment.club_id))
+            .collect::<HashMap<i64, Uuid>>();
+
+        for preference in preferences.iter() {
+            let membership_status = match assigned_clubs.get(&preference.student_id) {
+                Some(club_id) if *club_id == preference.club_id => SubmissionStatus::Approved,
+                _ => SubmissionStatus::Declined,
+            };
+
+            ClubRequestTable::set_status(
+                &mut transaction,
+                preference.club_id,
+                preference.student_id,
+                year,
+                membership_status,
+            )
+            .await?;
+        }
+
+        let mut assigned_students = assigned_clubs.keys().copied().collect::<Vec<i64>>();
+        assigned_students.sort();
+
+        // same as a manual approval, students who reach their limit release their other requests
+        for student_id in assigned_students {
+            ClubRequestTable::withdraw_open_requests(
+                &mut transaction,
+                student_id,
+                year,
+                settings.max_clubs_per_student,
+            )
+            .await?;
+        }
+
+        let res = sqlx::query_as::<_, AllocationRunTable>(
+            r#"
+            INSERT INTO allocation_runs (year, seed, created_by, report)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, year, seed, created_by, report, created_at
+            "#,
+        )
+        .bind(year)
+        .bind(seed)
+        .bind(created_by)
+        .bind(Json(&report))
+        .fetch_one(&mut transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(Self::from_table(res))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preferences(students: &[i64], clubs: &[Uuid]) -> Vec<ClubPreferenceTable> {
+        students
+            .iter()
+            .flat_map(|student_id| {
+                clubs
+                    .iter()
+                    .enumerate()
+                    .map(move |(rank, club_id)| ClubPreferenceTable {
+                        id: Uuid::new_v4(),
+                        student_id: *student_id,
+                        year: 2023,
+                        club_id: *club_id,
+                        rank: rank as i64 + 1,
+                        created_at: None,
+                    })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fixed_seed_fixed_allocation() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let preferences = preferences(&[1, 2, 3, 4, 5], &[a, b, c]);
+        let remaining_seats = HashMap::from([(a, Some(2)), (b, Some(1)), (c, Some(1))]);
+
+        // seed 42 shuffles the students into 2, 3, 1, 5, 4
+        let report = AllocationReport::allocate(&preferences, &remaining_seats, 42);
+
+        let assigned = report
+            .assigned
+            .iter()
+            .map(|assignment| (assignment.student_id, assignment.club_id, assignment.rank))
+            .collect::<Vec<_>>();
+
+        assert_eq!(assigned, vec![(2, a, 1), (3, a, 1), (1, b, 2), (5, c, 3)]);
+        assert_eq!(report.unassigned, vec![4]);
+    }
+
+    #[test]
+    fn allocation_respects_capacity() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let students = (1..=40).collect::<Vec<i64>>();
+        let preferences = preferences(&students, &[a, b]);
+        let remaining_seats = HashMap::from([(a, Some(10)), (b, Some(5))]);
+
+        for seed in 0..20 {
+            let report = AllocationReport::allocate(&preferences, &remaining_seats, seed);
+
+            let taken = |club_id: Uuid| {
+                report
+                    .assigned
+                    .iter()
+                    .filter(|assignment| assignment.club_id == club_id)
+                    .count()
+            };
+
+            assert_eq!(taken(a), 10);
+            assert_eq!(taken(b), 5);
+            assert_eq!(report.unassigned.len(), 25);
+        }
+    }
+
+    #[test]
+    fn clubs_without_capacity_take_everyone() {
+        let a = Uuid::new_v4();
+        let preferences = preferences(&[1, 2, 3], &[a]);
+        let remaining_seats = HashMap::from([(a, None)]);
+
+        let report = AllocationReport::allocate(&preferences, &remaining_seats, 7);
+
+        assert_eq!(report.assigned.len(), 3);
+        assert!(report.unassigned.is_empty());
+    }
+}
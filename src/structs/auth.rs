@@ -1,4 +1,4 @@
-use actix_web::error::{ErrorNotFound, ErrorUnauthorized};
+use actix_web::error::{ErrorForbidden, ErrorNotFound, ErrorUnauthorized};
 use actix_web::{dev::Payload, Error as ActixWebError};
 use actix_web::{http, web, FromRequest, HttpRequest};
 // use anyhow::Ok;
@@ -94,6 +94,22 @@ impl UserTable {
         }
     }
 
+    async fn from_teacher_id(
+        teacher_id: u32,
+        db: &Pool<Postgres>,
+    ) -> Result<UserTable, sqlx::Error> {
+        sqlx::query_as::<_, UserTable>(
+            r#"
+            SELECT id, email, role, student, teacher, onboarded, is_admin
+            FROM users
+            WHERE teacher = $1
+            "#,
+        )
+        .bind(teacher_id as i64)
+        .fetch_one(db)
+        .await
+    }
+
     async fn from_student_id(
         student_id: u32,
         db: &Pool<Postgres>,
@@ -185,6 +201,14 @@ impl User {
         Ok(User::new(user))
     }
 
+    pub async fn from_teacher_id(
+        teacher_id: u32,
+        db: &Pool<Postgres>,
+    ) -> Result<User, sqlx::Error> {
+        let user = UserTable::from_teacher_id(teacher_id, db).await?;
+        Ok(User::new(user))
+    }
+
     pub async fn from_student_ids(
         student_ids: Vec<u32>,
         db: &Pool<Postgres>,
@@ -353,3 +377,35 @@ impl FromRequest for User {
         })
     }
 }
+
+// a user with is_admin set, everything else is rejected before the handler runs
+#[derive(Debug)]
+pub struct Admin(pub User);
+
+impl FromRequest for Admin {
+    type Error = ActixWebError;
+    type Future = Pin<Box<dyn FutureTrait<Output = Result<Self, Self::Error>>>>;
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        // run normal user auth
+        let fut = User::from_request(req, payload);
+
+        // then check if user is admin
+        Box::pin(async move {
+            let user = fut.await?;
+
+            match user.is_admin {
+                true => Ok(Admin(user)),
+                false => Err(ErrorForbidden(ErrorResponseType::new(
+                    ErrorType {
+                        id: "403".to_string(),
+                        detail: "the user is not an admin".to_string(),
+                        code: 403,
+                        error_type: "forbidden".to_string(),
+                        source: "".to_string(),
+                    },
+                    None,
+                ))),
+            }
+        })
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, Pool, Postgres};
@@ -7,7 +9,7 @@ use crate::structs::{contacts::Contact, student::Student};
 use crate::utils::date::get_current_academic_year;
 // use crate::utils::logger;
 
-use super::common::FetchLevel;
+use super::common::{FetchLevel, Language};
 
 #[derive(FromRow, Debug)]
 struct ClassroomTable {
@@ -23,6 +25,22 @@ struct ClassroomTable {
 }
 
 impl ClassroomTable {
+    pub async fn query(
+        pool: &Pool<Postgres>,
+        year: i64,
+        number: Option<i64>,
+    ) -> Result<Vec<ClassroomTable>, sqlx::Error> {
+        sqlx::query_as::<_, ClassroomTable>(
+            r#"
+            SELECT * FROM classroom WHERE year = $1 AND ($2::bigint IS NULL OR number = $2) ORDER BY number
+            "#,
+        )
+        .bind(year)
+        .bind(number)
+        .fetch_all(pool)
+        .await
+    }
+
     pub async fn get_by_id(pool: &Pool<Postgres>, id: u32) -> Result<ClassroomTable, sqlx::Error> {
         sqlx::query_as!(
             ClassroomTable,
@@ -110,7 +128,7 @@ impl CompactClassroom {
         Ok(CompactClassroom {
             id: classroom.id as u32,
             number: classroom.number as u32,
-            room: "TODO".to_string(),
+            room: Classroom::room_from_number(classroom.number as u32, Language::En),
         })
     }
     pub async fn get_by_student_id(
@@ -124,7 +142,7 @@ impl CompactClassroom {
             Some(classroom) => Ok(Some(CompactClassroom {
                 id: classroom.id as u32,
                 number: classroom.number as u32,
-                room: "TODO".to_string(),
+                room: Classroom::room_from_number(classroom.number as u32, Language::En),
             })),
             None => Ok(None),
         }
@@ -161,7 +179,7 @@ impl DefaultClassroom {
         Ok(DefaultClassroom {
             id: classroom.id as u32,
             number: classroom.number as u32,
-            room: "TODO".to_string(),
+            room: Classroom::room_from_number(classroom.number as u32, Language::En),
             students: Student::get_from_ids(
                 pool,
                 classroom.students,
@@ -199,7 +217,7 @@ impl DefaultClassroom {
                 Ok(Some(DefaultClassroom {
                     id: classroom.id as u32,
                     number: classroom.number as u32,
-                    room: "TODO".to_string(),
+                    room: Classroom::room_from_number(classroom.number as u32, Language::En),
                     students: Student::get_from_ids(
                         pool,
                         classroom.students,
@@ -216,6 +234,142 @@ impl DefaultClassroom {
     }
 }
 
+// the students of a classroom in class number order together with its homeroom advisors
+#[derive(Debug, Clone)]
+pub struct ClassroomRoster {
+    pub id: u32,
+    pub number: u32,
+    pub year: i64,
+    pub advisors: Vec<i64>,
+    // (student_id, class_number), students missing from the no list come last without a number
+    pub students: Vec<(i64, Option<u32>)>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct QueryableClassroom {
+    // the current academic year when left out
+    pub year: Option<i64>,
+    pub number: Option<i64>,
+}
+
+// club registration of the classroom's students in the classroom's academic year, every
+// student is counted once under their furthest status
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct ClassroomRegistrationStats {
+    pub total_students: i64,
+    // students with at least one approved membership
+    pub approved: i64,
+    // students whose requests are all still pending
+    pub pending: i64,
+    // students without an approved or pending membership
+    pub unregistered: i64,
+}
+
+impl ClassroomRegistrationStats {
+    async fn get(
+        pool: &Pool<Postgres>,
+        students: &[i64],
+        year: i64,
+    ) -> Result<ClassroomRegistrationStats, sqlx::Error> {
+        let (approved, pending) = sqlx::query_as::<_, (i64, i64)>(
+            r#"
+            SELECT
+                COUNT(*) FILTER (WHERE approved),
+                COUNT(*) FILTER (WHERE NOT approved AND pending)
+            FROM (
+                SELECT
+                    bool_or(membership_status = 'approved') AS approved,
+                    bool_or(membership_status = 'pending') AS pending
+                FROM club_members
+                WHERE student_id = ANY($1) AND year = $2
+                GROUP BY student_id
+            ) memberships
+            "#,
+        )
+        .bind(students)
+        .bind(year)
+        .fetch_one(pool)
+        .await?;
+
+        let total_students = students.len() as i64;
+
+        Ok(ClassroomRegistrationStats {
+            total_students,
+            approved,
+            pending,
+            unregistered: total_students - approved - pending,
+        })
+    }
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ClassroomWithRegistration {
+    pub classroom: Classroom,
+    pub registration: ClassroomRegistrationStats,
+}
+
+impl ClassroomWithRegistration {
+    pub async fn get_by_id(
+        pool: &Pool<Postgres>,
+        id: u32,
+        fetch_level: FetchLevel,
+        descendant_fetch_level: Option<FetchLevel>,
+    ) -> Result<ClassroomWithRegistration, sqlx::Error> {
+        let classroom = ClassroomTable::get_by_id(pool, id).await?;
+
+        Self::from_table(pool, classroom, fetch_level, descendant_fetch_level).await
+    }
+
+    pub async fn query(
+        pool: &Pool<Postgres>,
+        filter: Option<&QueryableClassroom>,
+        fetch_level: FetchLevel,
+        descendant_fetch_level: Option<FetchLevel>,
+    ) -> Result<Vec<ClassroomWithRegistration>, sqlx::Error> {
+        let year = filter
+            .and_then(|filter| filter.year)
+            .unwrap_or(get_current_academic_year() as i64);
+        let number = filter.and_then(|filter| filter.number);
+
+        let mut classrooms = vec![];
+
+        for classroom in ClassroomTable::query(pool, year, number).await? {
+            classrooms.push(
+                Self::from_table(
+                    pool,
+                    classroom,
+                    fetch_level.clone(),
+                    descendant_fetch_level.clone(),
+                )
+                .await?,
+            );
+        }
+
+        Ok(classrooms)
+    }
+
+    async fn from_table(
+        pool: &Pool<Postgres>,
+        classroom: ClassroomTable,
+        fetch_level: FetchLevel,
+        descendant_fetch_level: Option<FetchLevel>,
+    ) -> Result<ClassroomWithRegistration, sqlx::Error> {
+        let registration =
+            ClassroomRegistrationStats::get(pool, &classroom.students, classroom.year).await?;
+
+        Ok(ClassroomWithRegistration {
+            classroom: Classroom::get_by_id(
+                pool,
+                classroom.id as u32,
+                fetch_level,
+                descendant_fetch_level,
+            )
+            .await?,
+            registration,
+        })
+    }
+}
+
 #[derive(Deserialize, Debug, ToSchema)]
 pub enum Classroom {
     Default(DefaultClassroom),
@@ -274,6 +428,100 @@ impl Classroom {
         }
     }
 
+    // classroom numbers are the grade followed by the room, e.g. 405 is room 5 of grade 4
+    pub fn grade_from_number(number: u32) -> u32 {
+        number / 100
+    }
+
+    // how the classroom is written at school, 405 is ม.4/5 in thai and M.4/5 in english
+    pub fn room_from_number(number: u32, language: Language) -> String {
+        let grade_prefix = match language {
+            Language::Th => "ม.",
+            Language::En => "M.",
+        };
+
+        format!(
+            "{grade_prefix}{}/{}",
+            Self::grade_from_number(number),
+            number % 100
+        )
+    }
+
+    pub async fn get_grade_by_student_id(
+        pool: &Pool<Postgres>,
+        id: u32,
+        year: Option<u32>,
+    ) -> Result<Option<u32>, sqlx::Error> {
+        let classroom = ClassroomTable::get_by_student_id(pool, id, year).await?;
+
+        Ok(classroom.map(|classroom| Self::grade_from_number(classroom.number as u32)))
+    }
+
+    // (classroom number, class number) of many students at once, keyed by (student id, year)
+    pub async fn get_places(
+        pool: &Pool<Postgres>,
+        student_ids: &[i64],
+        years: &[i64],
+    ) -> Result<HashMap<(i64, i64), (u32, Option<u32>)>, sqlx::Error> {
+        let classrooms = sqlx::query_as::<_, (i64, i64, Vec<i64>, Vec<i64>)>(
+            r#"
+            SELECT number, year, students, no_list FROM classroom
+            WHERE year = ANY($1) AND students && $2
+            "#,
+        )
+        .bind(years)
+        .bind(student_ids)
+        .fetch_all(pool)
+        .await?;
+
+        let mut places = HashMap::new();
+
+        for (number, year, students, no_list) in classrooms {
+            for student_id in students {
+                let class_number = no_list
+                    .iter()
+                    .position(|x| *x == student_id)
+                    .map(|position| position as u32 + 1);
+
+                places.insert((student_id, year), (number as u32, class_number));
+            }
+        }
+
+        Ok(places)
+    }
+
+    pub async fn get_roster(
+        pool: &Pool<Postgres>,
+        id: u32,
+    ) -> Result<ClassroomRoster, sqlx::Error> {
+        let classroom = ClassroomTable::get_by_id(pool, id).await?;
+
+        let mut students = classroom
+            .students
+            .iter()
+            .map(|student_id| {
+                let class_number = classroom
+                    .no_list
+                    .iter()
+                    .position(|x| x == student_id)
+                    .map(|position| position as u32 + 1);
+
+                (*student_id, class_number)
+            })
+            .collect::<Vec<(i64, Option<u32>)>>();
+        students.sort_by_key(|(student_id, class_number)| {
+            (class_number.is_none(), *class_number, *student_id)
+        });
+
+        Ok(ClassroomRoster {
+            id: classroom.id as u32,
+            number: classroom.number as u32,
+            year: classroom.year,
+            advisors: classroom.advisors,
+            students,
+        })
+    }
+
     pub async fn get_class_no_by_student_id(
         pool: &Pool<Postgres>,
         id: u32,
@@ -0,0 +1,333 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Decode, Encode, FromRow, Postgres, Type};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::utils::date::get_current_academic_year;
+
+use super::{
+    auth::{User, UserRoles},
+    common::{MultiLangString, PaginationConfig},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, ToSchema)]
+pub enum AnnouncementVisibility {
+    Public,
+    // approved members, staff and advisors of the club
+    Members,
+}
+
+impl AnnouncementVisibility {
+    pub fn to_string(&self) -> String {
+        match self {
+            AnnouncementVisibility::Public => "public".to_string(),
+            AnnouncementVisibility::Members => "members".to_string(),
+        }
+    }
+
+    pub fn from_string(s: &str) -> Option<AnnouncementVisibility> {
+        match s {
+            "public" => Some(AnnouncementVisibility::Public),
+            "members" => Some(AnnouncementVisibility::Members),
+            _ => None,
+        }
+    }
+}
+
+impl Type<Postgres> for AnnouncementVisibility {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        sqlx::postgres::PgTypeInfo::with_name("announcement_visibility")
+    }
+}
+
+impl Encode<'_, Postgres> for AnnouncementVisibility {
+    fn encode_by_ref(
+        &self,
+        buf: &mut <sqlx::Postgres as sqlx::database::HasArguments<'_>>::ArgumentBuffer,
+    ) -> sqlx::encode::IsNull {
+        let s = self.to_string();
+        <String as sqlx::Encode<sqlx::Postgres>>::encode(s, buf)
+    }
+}
+
+impl Decode<'_, Postgres> for AnnouncementVisibility {
+    fn decode(
+        value: <Postgres as sqlx::database::HasValueRef<'_>>::ValueRef,
+    ) -> Result<Self, sqlx::error::BoxDynError> {
+        let s = <String as Decode<Postgres>>::decode(value)?;
+
+        match AnnouncementVisibility::from_string(&s) {
+            Some(visibility) => Ok(visibility),
+            None => Err("Invalid announcement visibility".into()),
+        }
+    }
+}
+
+impl Serialize for AnnouncementVisibility {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for AnnouncementVisibility {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+
+        match AnnouncementVisibility::from_string(&s) {
+            Some(visibility) => Ok(visibility),
+            None => Err(serde::de::Error::custom("Invalid announcement visibility")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatableClubAnnouncement {
+    pub title: MultiLangString,
+    pub body: MultiLangString,
+    pub pinned: Option<bool>,
+    pub visibility: Option<AnnouncementVisibility>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdatableClubAnnouncement {
+    pub title: Option<MultiLangString>,
+    pub body: Option<MultiLangString>,
+    pub pinned: Option<bool>,
+    pub visibility: Option<AnnouncementVisibility>,
+}
+
+#[derive(FromRow)]
+struct ClubAnnouncementTable {
+    id: Uuid,
+    club_id: Uuid,
+    title_th: String,
+    title_en: Option<String>,
+    body_th: String,
+    body_en: Option<String>,
+    pinned: bool,
+    visibility: AnnouncementVisibility,
+    created_by: Option<Uuid>,
+    created_at: DateTime<Utc>,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ClubAnnouncement {
+    #[schema(value_type = String)]
+    pub id: Uuid,
+    #[schema(value_type = String)]
+    pub club_id: Uuid,
+    pub title: MultiLangString,
+    pub body: MultiLangString,
+    pub pinned: bool,
+    pub visibility: AnnouncementVisibility,
+    #[schema(value_type = Option<String>)]
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<ClubAnnouncementTable> for ClubAnnouncement {
+    fn from(announcement: ClubAnnouncementTable) -> Self {
+        ClubAnnouncement {
+            id: announcement.id,
+            club_id: announcement.club_id,
+            title: MultiLangString::new(announcement.title_en, announcement.title_th),
+            body: MultiLangString::new(announcement.body_en, announcement.body_th),
+            pinned: announcement.pinned,
+            visibility: announcement.visibility,
+            created_by: announcement.created_by,
+            created_at: announcement.created_at,
+            updated_at: announcement.updated_at,
+        }
+    }
+}
+
+impl ClubAnnouncement {
+    // pinned first, then newest first
+    pub async fn get_by_club_id(
+        pool: &sqlx::PgPool,
+        club_id: Uuid,
+        include_members_only: bool,
+        pagination: &Option<PaginationConfig>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let pagination = match pagination {
+            Some(pagination) => pagination,
+            None => &PaginationConfig {
+                size: Some(50),
+                p: 1,
+            },
+        };
+
+        let size = pagination.size.unwrap_or(50);
+        let page = pagination.p;
+
+        let res = sqlx::query_as::<_, ClubAnnouncementTable>(
+            r#"
+            SELECT id, club_id, title_th, title_en, body_th, body_en, pinned, visibility, created_by, created_at, updated_at
+            FROM club_announcements
+            WHERE club_id = $1 AND ($2 OR visibility = 'public')
+            ORDER BY pinned DESC, created_at DESC, id
+            LIMIT $3 OFFSET $4
+            "#,
+        )
+        .bind(club_id)
+        .bind(include_members_only)
+        .bind(size as i64)
+        .bind((page.max(1) - 1) as i64 * size as i64)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(res
+            .into_iter()
+            .map(|announcement| announcement.into())
+            .collect())
+    }
+
+    // every announcement of the clubs the user is an approved member, staff or advisor of in
+    // the current academic year, newest first so pins of one club don't bury the news of the
+    // others
+    pub async fn get_feed(
+        pool: &sqlx::PgPool,
+        user: &User,
+        pagination: &Option<PaginationConfig>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let year = get_current_academic_year() as i64;
+
+        let pagination = match pagination {
+            Some(pagination) => pagination,
+            None => &PaginationConfig {
+                size: Some(50),
+                p: 1,
+            },
+        };
+
+        let size = pagination.size.unwrap_or(50);
+        let page = pagination.p;
+
+        let (student_id, teacher_id) = match user.role {
+            UserRoles::Student => (user.student.map(|id| id as i64), None),
+            UserRoles::Teacher => (None, user.teacher.map(|id| id as i64)),
+        };
+
+        let res = sqlx::query_as::<_, ClubAnnouncementTable>(
+            r#"
+            SELECT id, club_id, title_th, title_en, body_th, body_en, pinned, visibility, created_by, created_at, updated_at
+            FROM club_announcements
+            WHERE club_id IN (
+                SELECT club_id FROM club_members
+                WHERE student_id = $1 AND year = $3 AND membership_status = 'approved'
+                UNION
+                SELECT club_id FROM club_staffs INNER JOIN clubs ON clubs.id = club_staffs.club_id
+                WHERE student_id = $1 AND club_staffs.year = $3 AND clubs.year = $3
+                UNION
+                SELECT club_id FROM club_advisors INNER JOIN clubs ON clubs.id = club_advisors.club_id
+                WHERE teacher_id = $2 AND club_advisors.year = $3 AND clubs.year = $3
+            )
+            ORDER BY created_at DESC, id
+            LIMIT $4 OFFSET $5
+            "#,
+        )
+        .bind(student_id)
+        .bind(teacher_id)
+        .bind(year)
+        .bind(size as i64)
+        .bind((page.max(1) - 1) as i64 * size as i64)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(res
+            .into_iter()
+            .map(|announcement| announcement.into())
+            .collect())
+    }
+
+    pub async fn create(
+        pool: &sqlx::PgPool,
+        club_id: Uuid,
+        announcement: &CreatableClubAnnouncement,
+        actor: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let res = sqlx::query_as::<_, ClubAnnouncementTable>(
+            r#"
+            INSERT INTO club_announcements (club_id, title_th, title_en, body_th, body_en, pinned, visibility, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id, club_id, title_th, title_en, body_th, body_en, pinned, visibility, created_by, created_at, updated_at
+            "#,
+        )
+        .bind(club_id)
+        .bind(&announcement.title.th)
+        .bind(&announcement.title.en)
+        .bind(&announcement.body.th)
+        .bind(&announcement.body.en)
+        .bind(announcement.pinned.unwrap_or(false))
+        .bind(
+            announcement
+                .visibility
+                .unwrap_or(AnnouncementVisibility::Members),
+        )
+        .bind(actor)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(res.into())
+    }
+
+    // a title or body replaces both languages, so an english text can be dropped by leaving it out
+    pub async fn update_by_id(
+        pool: &sqlx::PgPool,
+        club_id: Uuid,
+        id: Uuid,
+        announcement: &UpdatableClubAnnouncement,
+    ) -> Result<Self, sqlx::Error> {
+        let res = sqlx::query_as::<_, ClubAnnouncementTable>(
+            r#"
+            UPDATE club_announcements SET
+                title_th = COALESCE($3, title_th),
+                title_en = CASE WHEN $3::text IS NULL THEN title_en ELSE $4 END,
+                body_th = COALESCE($5, body_th),
+                body_en = CASE WHEN $5::text IS NULL THEN body_en ELSE $6 END,
+                pinned = COALESCE($7, pinned),
+                visibility = COALESCE($8, visibility),
+                updated_at = now()
+            WHERE id = $1 AND club_id = $2
+            RETURNING id, club_id, title_th, title_en, body_th, body_en, pinned, visibility, created_by, created_at, updated_at
+            "#,
+        )
+        .bind(id)
+        .bind(club_id)
+        .bind(announcement.title.as_ref().map(|title| &title.th))
+        .bind(announcement.title.as_ref().and_then(|title| title.en.as_ref()))
+        .bind(announcement.body.as_ref().map(|body| &body.th))
+        .bind(announcement.body.as_ref().and_then(|body| body.en.as_ref()))
+        .bind(announcement.pinned)
+        .bind(announcement.visibility)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(res.into())
+    }
+
+    pub async fn delete_by_id(
+        pool: &sqlx::PgPool,
+        club_id: Uuid,
+        id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        let res = sqlx::query(
+            r#"
+            DELETE FROM club_announcements WHERE id = $1 AND club_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(club_id)
+        .execute(pool)
+        .await?;
+
+        if res.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        Ok(())
+    }
+}
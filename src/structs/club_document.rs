@@ -0,0 +1,455 @@
+use std::collections::HashMap;
+
+use printpdf::{image_crate::DynamicImage, Rgb};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::{
+    classroom::Classroom,
+    club_request::{ClubRequest, ClubRequestSortableField, QueryableClubRequest},
+    common::{FetchLevel, FilterConfig, Language, RequestType},
+    roster_export::{RosterExport, RosterExportError, RosterRow},
+    student::DefaultStudent,
+};
+use crate::utils::pdf::{fetch_image, parse_hex_color, PdfCanvas, PdfFont};
+
+// used when a club has no accent color of its own
+const DEFAULT_ACCENT_COLOR: &str = "#2b4c7e";
+
+#[derive(Debug)]
+pub enum ClubDocumentError {
+    Database(sqlx::Error),
+    Roster(RosterExportError),
+    Pdf(printpdf::Error),
+    NoMemberships,
+}
+
+impl From<sqlx::Error> for ClubDocumentError {
+    fn from(e: sqlx::Error) -> Self {
+        ClubDocumentError::Database(e)
+    }
+}
+
+impl From<printpdf::Error> for ClubDocumentError {
+    fn from(e: printpdf::Error) -> Self {
+        ClubDocumentError::Pdf(e)
+    }
+}
+
+impl From<RosterExportError> for ClubDocumentError {
+    fn from(e: RosterExportError) -> Self {
+        ClubDocumentError::Roster(e)
+    }
+}
+
+impl std::fmt::Display for ClubDocumentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ClubDocumentError::Database(e) => write!(f, "{}", e),
+            ClubDocumentError::Roster(e) => write!(f, "{}", e),
+            ClubDocumentError::Pdf(e) => write!(f, "{}", e),
+            ClubDocumentError::NoMemberships => write!(
+                f,
+                "the student was not an approved member of any club that academic year"
+            ),
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct ClubBranding {
+    name_th: String,
+    name_en: Option<String>,
+    logo_url: Option<String>,
+    accent_color: Option<String>,
+    year: i64,
+}
+
+impl ClubBranding {
+    async fn get_by_id(pool: &sqlx::PgPool, id: Uuid) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT name_th, name_en, logo_url, accent_color, clubs.year
+            FROM clubs INNER JOIN organizations ON clubs.organization_id = organizations.id
+            WHERE clubs.id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_one(pool)
+        .await
+    }
+
+    // clubs the student was an approved member of in the academic year
+    async fn get_by_membership(
+        pool: &sqlx::PgPool,
+        student_id: i64,
+        year: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT name_th, name_en, logo_url, accent_color, clubs.year
+            FROM club_members
+            INNER JOIN clubs ON club_members.club_id = clubs.id
+            INNER JOIN organizations ON clubs.organization_id = organizations.id
+            WHERE club_members.student_id = $1 AND club_members.year = $2 AND club_members.membership_status = 'approved'
+            ORDER BY name_th
+            "#,
+        )
+        .bind(student_id)
+        .bind(year)
+        .fetch_all(pool)
+        .await
+    }
+
+    fn accent(&self) -> Rgb {
+        self.accent_color
+            .as_deref()
+            .and_then(parse_hex_color)
+            .or_else(|| parse_hex_color(DEFAULT_ACCENT_COLOR))
+            .unwrap()
+    }
+
+    async fn fetch_logo(&self) -> Option<DynamicImage> {
+        match &self.logo_url {
+            Some(url) => fetch_image(url).await,
+            None => None,
+        }
+    }
+}
+
+fn text_color() -> Rgb {
+    Rgb::new(0.1, 0.1, 0.1, None)
+}
+
+fn white() -> Rgb {
+    Rgb::new(1.0, 1.0, 1.0, None)
+}
+
+// thai prefixes are written against the first name, english ones are not
+fn full_name(
+    prefix: Option<&str>,
+    first_name: &str,
+    middle_name: Option<&str>,
+    last_name: &str,
+    prefix_separator: &str,
+) -> String {
+    let mut name = match prefix {
+        Some(prefix) if !prefix.is_empty() => format!("{prefix}{prefix_separator}{first_name}"),
+        _ => first_name.to_string(),
+    };
+
+    if let Some(middle_name) = middle_name.filter(|name| !name.is_empty()) {
+        name.push(' ');
+        name.push_str(middle_name);
+    }
+
+    name.push(' ');
+    name.push_str(last_name);
+
+    name
+}
+
+fn row_name_th(row: &RosterRow) -> String {
+    full_name(
+        Some(&row.prefix_th),
+        &row.first_name_th,
+        row.middle_name_th.as_deref(),
+        &row.last_name_th,
+        "",
+    )
+}
+
+fn row_name_en(row: &RosterRow) -> String {
+    match (&row.first_name_en, &row.last_name_en) {
+        (Some(first_name), Some(last_name)) => full_name(
+            row.prefix_en.as_deref(),
+            first_name,
+            row.middle_name_en.as_deref(),
+            last_name,
+            " ",
+        ),
+        _ => String::new(),
+    }
+}
+
+// the buddhist era year printed on thai documents
+fn thai_year(year: i64) -> i64 {
+    year + 543
+}
+
+// approved members of a club, printed for advisors to sign
+pub struct ClubRosterDocument;
+
+impl ClubRosterDocument {
+    pub async fn render(
+        pool: &sqlx::PgPool,
+        club_id: Uuid,
+        font: &PdfFont,
+    ) -> Result<Vec<u8>, ClubDocumentError> {
+        let club = ClubBranding::get_by_id(pool, club_id).await?;
+
+        let request: RequestType<ClubRequest, QueryableClubRequest, ClubRequestSortableField> =
+            RequestType {
+                data: None,
+                pagination: None,
+                filter: Some(FilterConfig {
+                    data: Some(QueryableClubRequest {
+                        id: None,
+                        club_id: Some(club_id),
+                        student_id: None,
+                        year: Some(club.year),
+                        semester: None,
+                        classroom_id: None,
+                        membership_status: None,
+                    }),
+                    q: None,
+                }),
+                sorting: None,
+                fetch_level: Some(FetchLevel::Compact),
+                descendant_fetch_level: None,
+            };
+
+        let roster = RosterExport::query(pool, &request).await?;
+        let logo = club.fetch_logo().await;
+
+        // printpdf documents can't be held across an await, so drawing happens in one go
+        Ok(Self::draw(&club, logo.as_ref(), roster.rows(), font)?)
+    }
+
+    fn draw(
+        club: &ClubBranding,
+        logo: Option<&DynamicImage>,
+        rows: &[RosterRow],
+        font: &PdfFont,
+    ) -> Result<Vec<u8>, printpdf::Error> {
+        let mut canvas = PdfCanvas::new(&club.name_th, font, false)?;
+        let accent = club.accent();
+        let text = text_color();
+
+        // x and width of each column: no., student id, thai name, english name, class, class no.
+        let columns: [(f32, f32); 6] = [
+            (17.0, 8.0),
+            (27.0, 18.0),
+            (47.0, 58.0),
+            (107.0, 58.0),
+            (167.0, 14.0),
+            (183.0, 10.0),
+        ];
+        let headers = ["ที่", "เลขประจำตัว", "ชื่อ-สกุล", "Name", "ชั้น", "เลขที่"];
+        let row_height = 7.0;
+        let page_bottom = canvas.height() - 20.0;
+
+        canvas.fill_rect(0.0, 0.0, canvas.width(), 6.0, &accent);
+
+        if let Some(logo) = logo {
+            canvas.image(logo, canvas.width() / 2.0, 12.0, 22.0, 22.0);
+        }
+
+        canvas.text_centered(&club.name_th, 16.0, 44.0, &text);
+
+        if let Some(name_en) = &club.name_en {
+            canvas.text_centered(name_en, 12.0, 51.0, &text);
+        }
+
+        canvas.text_centered(
+            &format!(
+                "รายชื่อสมาชิก ปีการศึกษา {} / Members, Academic Year {}",
+                thai_year(club.year),
+                club.year
+            ),
+            11.0,
+            58.0,
+            &text,
+        );
+
+        let draw_header = |canvas: &PdfCanvas, y: f32| {
+            canvas.fill_rect(15.0, y, 180.0, row_height, &accent);
+
+            for ((x, width), header) in columns.iter().zip(headers.iter()) {
+                canvas.text_clipped(header, 10.0, *x, y + 5.0, *width, &white());
+            }
+        };
+
+        let mut y = 64.0;
+        draw_header(&canvas, y);
+        y += row_height;
+
+        for (index, row) in rows.iter().enumerate() {
+            if y + row_height > page_bottom {
+                canvas.add_page();
+                y = 20.0;
+                draw_header(&canvas, y);
+                y += row_height;
+            }
+
+            let cells = [
+                (index + 1).to_string(),
+                row.student_id.to_string(),
+                row_name_th(row),
+                row_name_en(row),
+                row.class
+                    .map(|class| Classroom::room_from_number(class, Language::Th))
+                    .unwrap_or_default(),
+                row.class_number
+                    .map(|number| number.to_string())
+                    .unwrap_or_default(),
+            ];
+
+            for ((x, width), cell) in columns.iter().zip(cells.iter()) {
+                canvas.text_clipped(cell, 10.0, *x, y + 5.0, *width, &text);
+            }
+
+            canvas.line(15.0, y + row_height, 195.0, y + row_height, 0.3, &accent);
+            y += row_height;
+        }
+
+        // the signature block needs about 30mm below the table
+        if y + 30.0 > page_bottom {
+            canvas.add_page();
+            y = 20.0;
+        }
+
+        canvas.line(120.0, y + 20.0, 190.0, y + 20.0, 0.5, &text);
+        canvas.text("ครูที่ปรึกษาชุมนุม / Club advisor", 10.0, 128.0, y + 26.0, &text);
+
+        canvas.finish()
+    }
+}
+
+// one page per club the student completed in an academic year
+pub struct MembershipCertificate;
+
+impl MembershipCertificate {
+    pub async fn render(
+        pool: &sqlx::PgPool,
+        student_id: u32,
+        year: i64,
+        font: &PdfFont,
+    ) -> Result<Vec<u8>, ClubDocumentError> {
+        let clubs = ClubBranding::get_by_membership(pool, student_id as i64, year).await?;
+
+        if clubs.is_empty() {
+            return Err(ClubDocumentError::NoMemberships);
+        }
+
+        let student = DefaultStudent::get_by_id(pool, student_id, Some(FetchLevel::IdOnly)).await?;
+
+        let mut logos: HashMap<String, DynamicImage> = HashMap::new();
+
+        for club in clubs.iter() {
+            if let Some(url) = &club.logo_url {
+                if logos.contains_key(url) {
+                    continue;
+                }
+
+                if let Some(logo) = club.fetch_logo().await {
+                    logos.insert(url.clone(), logo);
+                }
+            }
+        }
+
+        Ok(Self::draw(&student, &clubs, &logos, year, font)?)
+    }
+
+    fn draw(
+        student: &DefaultStudent,
+        clubs: &[ClubBranding],
+        logos: &HashMap<String, DynamicImage>,
+        year: i64,
+        font: &PdfFont,
+    ) -> Result<Vec<u8>, printpdf::Error> {
+        let name_th = full_name(
+            Some(&student.prefix.th),
+            &student.first_name.th,
+            student.middle_name.as_ref().map(|name| name.th.as_str()),
+            &student.last_name.th,
+            "",
+        );
+
+        let name_en = match (&student.first_name.en, &student.last_name.en) {
+            (Some(first_name), Some(last_name)) => Some(full_name(
+                student.prefix.en.as_deref(),
+                first_name,
+                student
+                    .middle_name
+                    .as_ref()
+                    .and_then(|name| name.en.as_deref()),
+                last_name,
+                " ",
+            )),
+            _ => None,
+        };
+
+        let mut canvas = PdfCanvas::new(
+            &format!("เกียรติบัตร {name_th} ปีการศึกษา {}", thai_year(year)),
+            font,
+            true,
+        )?;
+        let text = text_color();
+
+        for (index, club) in clubs.iter().enumerate() {
+            if index > 0 {
+                canvas.add_page();
+            }
+
+            let accent = club.accent();
+            let (width, height) = (canvas.width(), canvas.height());
+
+            canvas.stroke_rect(10.0, 10.0, width - 20.0, height - 20.0, 3.0, &accent);
+            canvas.stroke_rect(14.0, 14.0, width - 28.0, height - 28.0, 0.75, &accent);
+
+            if let Some(logo) = club.logo_url.as_ref().and_then(|url| logos.get(url)) {
+                canvas.image(logo, width / 2.0, 22.0, 30.0, 30.0);
+            }
+
+            canvas.text_centered("เกียรติบัตร", 30.0, 70.0, &accent);
+            canvas.text_centered("Certificate of Participation", 16.0, 80.0, &accent);
+            canvas.text_centered(
+                "ขอมอบเกียรติบัตรฉบับนี้เพื่อแสดงว่า / This is to certify that",
+                12.0,
+                94.0,
+                &text,
+            );
+            canvas.text_centered(&name_th, 24.0, 109.0, &text);
+
+            if let Some(name_en) = &name_en {
+                canvas.text_centered(name_en, 16.0, 119.0, &text);
+            }
+
+            canvas.text_centered(
+                &format!("ได้เข้าร่วมเป็นสมาชิก{}", club.name_th),
+                14.0,
+                134.0,
+                &text,
+            );
+
+            if let Some(club_name_en) = &club.name_en {
+                canvas.text_centered(
+                    &format!("has participated as a member of {club_name_en}"),
+                    12.0,
+                    142.0,
+                    &text,
+                );
+            }
+
+            canvas.text_centered(
+                &format!("ปีการศึกษา {} / Academic Year {}", thai_year(year), year),
+                12.0,
+                153.0,
+                &text,
+            );
+
+            canvas.line(
+                width / 2.0 - 40.0,
+                180.0,
+                width / 2.0 + 40.0,
+                180.0,
+                0.5,
+                &text,
+            );
+            canvas.text_centered("ครูที่ปรึกษาชุมนุม / Club advisor", 10.0, 186.0, &text);
+        }
+
+        canvas.finish()
+    }
+}
@@ -0,0 +1,400 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::utils::{
+    date::get_current_academic_year,
+    ical::{Calendar, CalendarEvent},
+};
+
+use super::{
+    auth::{User, UserRoles},
+    common::MultiLangString,
+};
+
+#[derive(Debug)]
+pub enum ClubEventError {
+    Database(sqlx::Error),
+    InvalidTime,
+}
+
+impl From<sqlx::Error> for ClubEventError {
+    fn from(e: sqlx::Error) -> Self {
+        ClubEventError::Database(e)
+    }
+}
+
+impl std::fmt::Display for ClubEventError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ClubEventError::Database(e) => write!(f, "{}", e),
+            ClubEventError::InvalidTime => write!(f, "an event has to end after it starts"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatableClubEvent {
+    pub title: MultiLangString,
+    pub description: Option<MultiLangString>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    // left out for events in the club's main room
+    pub room: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdatableClubEvent {
+    pub title: Option<MultiLangString>,
+    pub description: Option<MultiLangString>,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub room: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryableClubEvent {
+    // events still running at or after from
+    pub from: Option<DateTime<Utc>>,
+    // events starting before to
+    pub to: Option<DateTime<Utc>>,
+    // matched the same way as main_room on clubs
+    pub room: Option<String>,
+}
+
+#[derive(FromRow)]
+struct ClubEventTable {
+    id: Uuid,
+    club_id: Uuid,
+    club_name_th: String,
+    club_name_en: Option<String>,
+    title_th: String,
+    title_en: Option<String>,
+    description_th: Option<String>,
+    description_en: Option<String>,
+    starts_at: DateTime<Utc>,
+    ends_at: DateTime<Utc>,
+    room: Option<String>,
+    created_by: Option<Uuid>,
+    created_at: DateTime<Utc>,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ClubEvent {
+    #[schema(value_type = String)]
+    pub id: Uuid,
+    #[schema(value_type = String)]
+    pub club_id: Uuid,
+    pub club_name: MultiLangString,
+    pub title: MultiLangString,
+    pub description: Option<MultiLangString>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    // the club's main room unless the event set its own
+    pub room: Option<String>,
+    #[schema(value_type = Option<String>)]
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<ClubEventTable> for ClubEvent {
+    fn from(event: ClubEventTable) -> Self {
+        ClubEvent {
+            id: event.id,
+            club_id: event.club_id,
+            club_name: MultiLangString::new(event.club_name_en, event.club_name_th),
+            title: MultiLangString::new(event.title_en, event.title_th),
+            description: event
+                .description_th
+                .map(|th| MultiLangString::new(event.description_en, th)),
+            starts_at: event.starts_at,
+            ends_at: event.ends_at,
+            room: event.room,
+            created_by: event.created_by,
+            created_at: event.created_at,
+            updated_at: event.updated_at,
+        }
+    }
+}
+
+const CLUB_EVENT_SELECT: &str = r#"
+    SELECT club_events.id, club_events.club_id, organizations.name_th AS club_name_th, organizations.name_en AS club_name_en,
+        title_th, title_en, club_events.description_th, club_events.description_en, starts_at, ends_at,
+        COALESCE(club_events.room, organizations.main_room) AS room, created_by, club_events.created_at, updated_at
+    FROM club_events
+    INNER JOIN clubs ON club_events.club_id = clubs.id
+    INNER JOIN organizations ON clubs.organization_id = organizations.id
+"#;
+
+impl ClubEvent {
+    pub async fn get_by_id(
+        pool: &sqlx::PgPool,
+        club_id: Uuid,
+        id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let res = sqlx::query_as::<_, ClubEventTable>(&format!(
+            "{CLUB_EVENT_SELECT} WHERE club_events.id = $1 AND club_events.club_id = $2"
+        ))
+        .bind(id)
+        .bind(club_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(res.into())
+    }
+
+    pub async fn get_by_club_id(
+        pool: &sqlx::PgPool,
+        club_id: Uuid,
+        filter: Option<&QueryableClubEvent>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let res = sqlx::query_as::<_, ClubEventTable>(&format!(
+            r#"
+            {CLUB_EVENT_SELECT}
+            WHERE club_events.club_id = $1
+                AND ($2::timestamptz IS NULL OR ends_at > $2)
+                AND ($3::timestamptz IS NULL OR starts_at < $3)
+                AND ($4::text IS NULL OR COALESCE(club_events.room, organizations.main_room) ILIKE $4)
+            ORDER BY starts_at, club_events.id
+            "#
+        ))
+        .bind(club_id)
+        .bind(filter.and_then(|filter| filter.from))
+        .bind(filter.and_then(|filter| filter.to))
+        .bind(filter.and_then(|filter| filter.room.as_ref().map(|room| format!("%{room}%"))))
+        .fetch_all(pool)
+        .await?;
+
+        Ok(res.into_iter().map(|event| event.into()).collect())
+    }
+
+    // events of every club the user is an approved member, staff or advisor of in the current
+    // academic year
+    pub async fn get_by_user(
+        pool: &sqlx::PgPool,
+        user: &User,
+        filter: Option<&QueryableClubEvent>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let (student_id, teacher_id) = match user.role {
+            UserRoles::Student => (user.student.map(|id| id as i64), None),
+            UserRoles::Teacher => (None, user.teacher.map(|id| id as i64)),
+        };
+
+        let year = get_current_academic_year() as i64;
+
+        let res = sqlx::query_as::<_, ClubEventTable>(&format!(
+            r#"
+            {CLUB_EVENT_SELECT}
+            WHERE club_events.club_id IN (
+                SELECT club_id FROM club_members
+                WHERE student_id = $1 AND year = $6 AND membership_status = 'approved'
+                UNION
+                SELECT club_id FROM club_staffs INNER JOIN clubs ON clubs.id = club_staffs.club_id
+                WHERE student_id = $1 AND club_staffs.year = $6 AND clubs.year = $6
+                UNION
+                SELECT club_id FROM club_advisors INNER JOIN clubs ON clubs.id = club_advisors.club_id
+                WHERE teacher_id = $2 AND club_advisors.year = $6 AND clubs.year = $6
+            )
+                AND ($3::timestamptz IS NULL OR ends_at > $3)
+                AND ($4::timestamptz IS NULL OR starts_at < $4)
+                AND ($5::text IS NULL OR COALESCE(club_events.room, organizations.main_room) ILIKE $5)
+            ORDER BY starts_at, club_events.id
+            "#
+        ))
+        .bind(student_id)
+        .bind(teacher_id)
+        .bind(filter.and_then(|filter| filter.from))
+        .bind(filter.and_then(|filter| filter.to))
+        .bind(filter.and_then(|filter| filter.room.as_ref().map(|room| format!("%{room}%"))))
+        .bind(year)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(res.into_iter().map(|event| event.into()).collect())
+    }
+
+    pub async fn create(
+        pool: &sqlx::PgPool,
+        club_id: Uuid,
+        event: &CreatableClubEvent,
+        actor: Uuid,
+    ) -> Result<Self, ClubEventError> {
+        if event.ends_at <= event.starts_at {
+            return Err(ClubEventError::InvalidTime);
+        }
+
+        let (id,) = sqlx::query_as::<_, (Uuid,)>(
+            r#"
+            INSERT INTO club_events (club_id, title_th, title_en, description_th, description_en, starts_at, ends_at, room, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id
+            "#,
+        )
+        .bind(club_id)
+        .bind(&event.title.th)
+        .bind(&event.title.en)
+        .bind(event.description.as_ref().map(|description| &description.th))
+        .bind(event.description.as_ref().and_then(|description| description.en.as_ref()))
+        .bind(event.starts_at)
+        .bind(event.ends_at)
+        .bind(&event.room)
+        .bind(actor)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(Self::get_by_id(pool, club_id, id).await?)
+    }
+
+    pub async fn update_by_id(
+        pool: &sqlx::PgPool,
+        club_id: Uuid,
+        id: Uuid,
+        event: &UpdatableClubEvent,
+    ) -> Result<Self, ClubEventError> {
+        let current = Self::get_by_id(pool, club_id, id).await?;
+
+        if event.ends_at.unwrap_or(current.ends_at) <= event.starts_at.unwrap_or(current.starts_at)
+        {
+            return Err(ClubEventError::InvalidTime);
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE club_events SET
+                title_th = COALESCE($3, title_th),
+                title_en = CASE WHEN $3::text IS NULL THEN title_en ELSE $4 END,
+                description_th = COALESCE($5, description_th),
+                description_en = CASE WHEN $5::text IS NULL THEN description_en ELSE $6 END,
+                starts_at = COALESCE($7, starts_at),
+                ends_at = COALESCE($8, ends_at),
+                room = COALESCE($9, room),
+                updated_at = now()
+            WHERE id = $1 AND club_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(club_id)
+        .bind(event.title.as_ref().map(|title| &title.th))
+        .bind(event.title.as_ref().and_then(|title| title.en.as_ref()))
+        .bind(
+            event
+                .description
+                .as_ref()
+                .map(|description| &description.th),
+        )
+        .bind(
+            event
+                .description
+                .as_ref()
+                .and_then(|description| description.en.as_ref()),
+        )
+        .bind(event.starts_at)
+        .bind(event.ends_at)
+        .bind(&event.room)
+        .execute(pool)
+        .await?;
+
+        Ok(Self::get_by_id(pool, club_id, id).await?)
+    }
+
+    pub async fn delete_by_id(
+        pool: &sqlx::PgPool,
+        club_id: Uuid,
+        id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        let res = sqlx::query(
+            r#"
+            DELETE FROM club_events WHERE id = $1 AND club_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(club_id)
+        .execute(pool)
+        .await?;
+
+        if res.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        Ok(())
+    }
+
+    // the thai text is used since that is what every club has, the club name is prefixed
+    // when a calendar mixes events of several clubs
+    pub fn to_ical(name: &str, events: &[Self], with_club_name: bool) -> String {
+        let mut calendar = Calendar::new(name);
+
+        for event in events {
+            let summary = match with_club_name {
+                true => format!("[{}] {}", event.club_name.th, event.title.th),
+                false => event.title.th.clone(),
+            };
+
+            calendar.add_event(&CalendarEvent {
+                uid: format!("{}@club-registrar.mysk.school", event.id),
+                starts_at: event.starts_at,
+                ends_at: event.ends_at,
+                summary: &summary,
+                description: event
+                    .description
+                    .as_ref()
+                    .map(|description| description.th.as_str()),
+                location: event.room.as_deref(),
+                last_modified: event.updated_at.unwrap_or(event.created_at),
+            });
+        }
+
+        calendar.finish()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CalendarSubscription {
+    #[schema(value_type = String)]
+    pub token: Uuid,
+    // path of the .ics feed, to be put behind the public url of the api
+    pub path: String,
+}
+
+pub struct CalendarToken;
+
+impl CalendarToken {
+    // a new token every call, so a leaked subscription url can be revoked by asking again
+    pub async fn rotate(
+        pool: &sqlx::PgPool,
+        user_id: Uuid,
+    ) -> Result<CalendarSubscription, sqlx::Error> {
+        let (token,) = sqlx::query_as::<_, (Uuid,)>(
+            r#"
+            INSERT INTO calendar_tokens (user_id) VALUES ($1)
+            ON CONFLICT (user_id) DO UPDATE SET token = gen_random_uuid(), created_at = now()
+            RETURNING token
+            "#,
+        )
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(CalendarSubscription {
+            token,
+            path: format!("/events/calendar/{token}.ics"),
+        })
+    }
+
+    pub async fn get_user(pool: &sqlx::PgPool, token: Uuid) -> Result<User, sqlx::Error> {
+        let (user_id,) = sqlx::query_as::<_, (Uuid,)>(
+            r#"
+            SELECT user_id FROM calendar_tokens WHERE token = $1
+            "#,
+        )
+        .bind(token)
+        .fetch_one(pool)
+        .await?;
+
+        User::from_id(user_id, pool).await
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::utils::date::get_current_academic_year;
+
+use super::{
+    clubs::{ActivityDayHouse, Club, CreatableClub},
+    common::{FlexibleMultiLangString, MultiLangString},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClubImportOptions {
+    // the current academic year when left out
+    pub year: Option<i64>,
+    // validate the file and report what would be created without changing anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+// one line of the csv, the header row names the columns
+#[derive(Debug, Deserialize)]
+struct ClubImportRecord {
+    name_th: String,
+    name_en: Option<String>,
+    description_th: Option<String>,
+    description_en: Option<String>,
+    house: Option<String>,
+    main_room: Option<String>,
+    // student ids as printed on student cards, separated by spaces or semicolons, the first
+    // becomes president
+    staff_student_ids: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ClubImportRow {
+    // line number in the file, the header is line 1
+    pub row: u64,
+    pub name_th: Option<String>,
+    // None when the row has errors or in a dry run
+    #[schema(value_type = Option<String>)]
+    pub club_id: Option<Uuid>,
+    // database ids of the staff students
+    pub staffs: Vec<i64>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ClubImportReport {
+    pub year: i64,
+    pub dry_run: bool,
+    // false when any row has errors, nothing is imported then
+    pub applied: bool,
+    pub rows: Vec<ClubImportRow>,
+}
+
+impl ClubImportReport {
+    pub fn has_errors(&self) -> bool {
+        self.rows.iter().any(|row| !row.errors.is_empty())
+    }
+}
+
+#[derive(Debug)]
+pub enum ClubImportError {
+    Database(sqlx::Error),
+    // the file itself can't be read, such as a missing header row
+    Csv(csv::Error),
+}
+
+impl From<sqlx::Error> for ClubImportError {
+    fn from(e: sqlx::Error) -> Self {
+        ClubImportError::Database(e)
+    }
+}
+
+impl From<csv::Error> for ClubImportError {
+    fn from(e: csv::Error) -> Self {
+        ClubImportError::Csv(e)
+    }
+}
+
+impl std::fmt::Display for ClubImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ClubImportError::Database(e) => write!(f, "{}", e),
+            ClubImportError::Csv(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+pub struct ClubImport;
+
+impl ClubImport {
+    // Every row is validated before anything is written, a single bad row rejects the whole
+    // file so an import never leaves half of the clubs behind.
+    pub async fn run(
+        pool: &sqlx::PgPool,
+        csv: &[u8],
+        options: &ClubImportOptions,
+    ) -> Result<ClubImportReport, ClubImportError> {
+        let year = options.year.unwrap_or(get_current_academic_year() as i64);
+
+        // excel saves utf-8 csv files with a byte order mark
+        let csv = csv.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(csv);
+
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(csv);
+        let headers = reader.headers()?.clone();
+
+        let mut rows = vec![];
+        let mut clubs = vec![];
+
+        for record in reader.records() {
+            // quoted values can span several lines, so rows are numbered by where they start
+            let (row, record) = match record {
+                Ok(record) => (
+                    record.position().map(|position| position.line()),
+                    record.deserialize::<ClubImportRecord>(Some(&headers)),
+                ),
+                Err(e) => (e.position().map(|position| position.line()), Err(e)),
+            };
+            let row = row.unwrap_or_default();
+
+            match record {
+                Ok(record) => {
+                    rows.push(ClubImportRow {
+                        row,
+                        name_th: non_empty(Some(record.name_th.clone())),
+                        club_id: None,
+                        staffs: vec![],
+                        errors: vec![],
+                    });
+                    clubs.push(Some(record));
+                }
+                Err(e) => {
+                    rows.push(ClubImportRow {
+                        row,
+                        name_th: None,
+                        club_id: None,
+                        staffs: vec![],
+                        errors: vec![e.to_string()],
+                    });
+                    clubs.push(None);
+                }
+            }
+        }
+
+        let staff_student_ids = clubs
+            .iter()
+            .flatten()
+            .filter_map(|record| record.staff_student_ids.as_ref())
+            .flat_map(|ids| ids.split(|c: char| c == ';' || c.is_whitespace()))
+            .filter(|id| !id.is_empty())
+            .map(|id| id.to_string())
+            .collect::<Vec<String>>();
+
+        // the names are checked and the clubs inserted in one transaction, with club inserts
+        // locked out in between so two imports can't create the same club
+        let mut transaction = pool.begin().await?;
+
+        if !options.dry_run {
+            sqlx::query("LOCK TABLE clubs IN SHARE ROW EXCLUSIVE MODE")
+                .execute(&mut transaction)
+                .await?;
+        }
+
+        let students: HashMap<String, i64> = sqlx::query_as::<_, (String, i64)>(
+            r#"
+            SELECT std_id, id FROM student WHERE std_id = ANY($1)
+            "#,
+        )
+        .bind(&staff_student_ids)
+        .fetch_all(&mut transaction)
+        .await?
+        .into_iter()
+        .collect();
+
+        let existing_names: HashSet<String> = sqlx::query_as::<_, (String,)>(
+            r#"
+            SELECT name_th FROM clubs INNER JOIN organizations ON clubs.organization_id = organizations.id
+            WHERE clubs.year = $1
+            "#,
+        )
+        .bind(year)
+        .fetch_all(&mut transaction)
+        .await?
+        .into_iter()
+        .map(|(name,)| name)
+        .collect();
+
+        let mut seen_names = HashSet::new();
+        let mut creatable = vec![];
+
+        for (row, record) in rows.iter_mut().zip(clubs) {
+            let record = match record {
+                Some(record) => record,
+                None => continue,
+            };
+
+            match &row.name_th {
+                None => row.errors.push("name_th is required".to_string()),
+                Some(name) if existing_names.contains(name) => row
+                    .errors
+                    .push(format!("a club named {name} already exists in {year}")),
+                Some(name) if !seen_names.insert(name.clone()) => row
+                    .errors
+                    .push(format!("a club named {name} appears more than once")),
+                Some(_) => (),
+            }
+
+            let house = match non_empty(record.house) {
+                Some(house) => match ActivityDayHouse::from_string(&house.to_lowercase()) {
+                    Some(house) => Some(house),
+                    None => {
+                        row.errors.push(format!(
+                            "house {house} is not one of felis, cornicula, sciurus or cyprinus"
+                        ));
+                        None
+                    }
+                },
+                None => None,
+            };
+
+            for student_id in record
+                .staff_student_ids
+                .as_deref()
+                .unwrap_or_default()
+                .split(|c: char| c == ';' || c.is_whitespace())
+                .filter(|id| !id.is_empty())
+            {
+                match students.get(student_id) {
+                    Some(id) if row.staffs.contains(id) => (),
+                    Some(id) => row.staffs.push(*id),
+                    None => row
+                        .errors
+                        .push(format!("student {student_id} does not exist")),
+                }
+            }
+
+            if !row.errors.is_empty() {
+                continue;
+            }
+
+            creatable.push(CreatableClub {
+                name: MultiLangString::new(
+                    non_empty(record.name_en),
+                    row.name_th.clone().unwrap_or_default(),
+                ),
+                description: match (
+                    non_empty(record.description_th),
+                    non_empty(record.description_en),
+                ) {
+                    (None, None) => None,
+                    (th, en) => Some(FlexibleMultiLangString { th, en }),
+                },
+                main_room: non_empty(record.main_room),
+                logo_url: None,
+                background_color: None,
+                accent_color: None,
+                house,
+                map_location: None,
+                capacity: None,
+                year: Some(year),
+                semester: None,
+                staffs: row.staffs.clone(),
+            });
+        }
+
+        let mut report = ClubImportReport {
+            year,
+            dry_run: options.dry_run,
+            applied: false,
+            rows,
+        };
+
+        if report.has_errors() || options.dry_run {
+            return Ok(report);
+        }
+
+        let mut club_ids = vec![];
+
+        for club in creatable.iter() {
+            club_ids.push(Club::insert(&mut transaction, club, year).await?);
+        }
+
+        transaction.commit().await?;
+
+        // rows without errors are exactly the ones that were turned into clubs, in order
+        for (row, club_id) in report.rows.iter_mut().zip(club_ids) {
+            row.club_id = Some(club_id);
+        }
+
+        report.applied = true;
+
+        Ok(report)
+    }
+}
@@ -0,0 +1,117 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, Postgres, Transaction};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{
+    clubs::SubmissionStatus,
+    common::{PaginationConfig, RequestType},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryableClubMemberHistory {
+    pub club_id: Option<Uuid>,
+    pub student_id: Option<i64>,
+    pub actor: Option<Uuid>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ClubMemberHistory {
+    #[schema(value_type = String)]
+    pub id: Uuid,
+    #[schema(value_type = String)]
+    pub club_member_id: Uuid,
+    #[schema(value_type = String)]
+    pub club_id: Uuid,
+    pub student_id: i64,
+    pub year: i64,
+    // None for the entry written when the request was created
+    pub previous_status: Option<SubmissionStatus>,
+    pub membership_status: SubmissionStatus,
+    #[schema(value_type = Option<String>)]
+    pub actor: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ClubMemberHistory {
+    // the history itself is written by a trigger on club_members, which picks the actor up
+    // from this transaction-local setting
+    pub async fn set_actor(
+        transaction: &mut Transaction<'_, Postgres>,
+        actor: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            SELECT set_config('app.actor_id', $1, true)
+            "#,
+        )
+        .bind(actor.to_string())
+        .execute(&mut *transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_by_club_member_id(
+        pool: &sqlx::PgPool,
+        club_member_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT id, club_member_id, club_id, student_id, year, previous_status, membership_status, actor, created_at
+            FROM club_member_history WHERE club_member_id = $1
+            ORDER BY created_at, id
+            "#,
+        )
+        .bind(club_member_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn query(
+        pool: &sqlx::PgPool,
+        request: &RequestType<Self, QueryableClubMemberHistory, String>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let filter = request
+            .filter
+            .as_ref()
+            .and_then(|filter| filter.data.as_ref());
+
+        let pagination = match &request.pagination {
+            Some(pagination) => pagination,
+            None => &PaginationConfig {
+                size: Some(50),
+                p: 1,
+            },
+        };
+
+        let size = pagination.size.unwrap_or(50);
+        let page = pagination.p;
+
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT id, club_member_id, club_id, student_id, year, previous_status, membership_status, actor, created_at
+            FROM club_member_history
+            WHERE ($1::uuid IS NULL OR club_id = $1)
+                AND ($2::bigint IS NULL OR student_id = $2)
+                AND ($3::uuid IS NULL OR actor = $3)
+                AND ($4::timestamptz IS NULL OR created_at >= $4)
+                AND ($5::timestamptz IS NULL OR created_at < $5)
+            ORDER BY created_at DESC, id
+            LIMIT $6 OFFSET $7
+            "#,
+        )
+        .bind(filter.and_then(|filter| filter.club_id))
+        .bind(filter.and_then(|filter| filter.student_id))
+        .bind(filter.and_then(|filter| filter.actor))
+        .bind(filter.and_then(|filter| filter.from))
+        .bind(filter.and_then(|filter| filter.to))
+        .bind(size as i64)
+        .bind((page.max(1) - 1) as i64 * size as i64)
+        .fetch_all(pool)
+        .await
+    }
+}
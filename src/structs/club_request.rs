@@ -1,23 +1,121 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::{Acquire, FromRow, Postgres, Transaction};
 use utoipa::ToSchema;
 use uuid::Uuid;
 
-use crate::utils::date::get_current_academic_year;
-
 use super::{
+    auth::User,
+    club_member_history::ClubMemberHistory,
     clubs::{Club, SubmissionStatus},
-    common::{FetchLevel, PaginationConfig, RequestType},
+    common::{ErrorType, FetchLevel, MultiLangString, PaginationConfig, RequestType},
+    registration::{RegistrationSettings, RegistrationStatus},
     student::Student,
 };
 
+#[derive(Debug)]
+pub enum ClubRequestError {
+    Database(sqlx::Error),
+    ClubFull {
+        capacity: i64,
+        remaining_seats: i64,
+    },
+    InvalidTransition {
+        from: SubmissionStatus,
+        to: SubmissionStatus,
+    },
+    ClubLimitReached {
+        max_clubs_per_student: i64,
+        // the semester of the request, None for whole year requests
+        semester: Option<i64>,
+    },
+    NotFound,
+    NotClubStaff,
+    ClubArchived,
+    WrongYear {
+        club_year: i64,
+    },
+    InvalidSemester,
+    WrongSemester {
+        club_semester: i64,
+    },
+    // approved members can't leave outside the registration window
+    RegistrationClosed {
+        next_opening: Option<DateTime<Utc>>,
+    },
+}
+
+impl From<sqlx::Error> for ClubRequestError {
+    fn from(e: sqlx::Error) -> Self {
+        ClubRequestError::Database(e)
+    }
+}
+
+impl std::fmt::Display for ClubRequestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ClubRequestError::Database(e) => write!(f, "{}", e),
+            ClubRequestError::ClubFull {
+                capacity,
+                remaining_seats,
+            } => write!(
+                f,
+                "club has reached its capacity of {capacity} members, {remaining_seats} seats remaining"
+            ),
+            ClubRequestError::InvalidTransition { from, to } => write!(
+                f,
+                "membership_status can not be changed from {} to {}",
+                from.to_string(),
+                to.to_string()
+            ),
+            ClubRequestError::ClubLimitReached {
+                max_clubs_per_student,
+                semester: Some(semester),
+            } => write!(
+                f,
+                "student has already joined the maximum of {max_clubs_per_student} clubs in semester {semester}"
+            ),
+            ClubRequestError::ClubLimitReached {
+                max_clubs_per_student,
+                semester: None,
+            } => write!(
+                f,
+                "student has already joined the maximum of {max_clubs_per_student} clubs in one of the semesters"
+            ),
+            ClubRequestError::NotFound => write!(f, "join request not found"),
+            ClubRequestError::NotClubStaff => write!(f, "the user is not club staff or advisor"),
+            ClubRequestError::ClubArchived => write!(f, "the club is archived"),
+            ClubRequestError::WrongYear { club_year } => {
+                write!(f, "the club runs in academic year {club_year}")
+            }
+            ClubRequestError::InvalidSemester => write!(f, "semester must be 1 or 2"),
+            ClubRequestError::WrongSemester { club_semester } => {
+                write!(f, "the club only runs in semester {club_semester}")
+            }
+            ClubRequestError::RegistrationClosed { next_opening } => write!(
+                f,
+                "{}",
+                RegistrationStatus::Closed {
+                    next_opening: *next_opening
+                }
+                .to_string()
+            ),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueryableClubRequest {
     pub id: Option<Uuid>,
     pub club_id: Option<Uuid>,
     pub student_id: Option<i64>,
     pub year: Option<i64>,
+    // memberships covering the semester, whole year memberships included
+    pub semester: Option<i64>,
+    // students of the classroom, for homeroom teachers
+    pub classroom_id: Option<i64>,
     pub membership_status: Option<SubmissionStatus>,
     // pub created_at: Option<DateTime<Utc>>,
 }
@@ -27,11 +125,37 @@ pub struct CreatableClubRequest {
     pub club_id: Uuid,
     pub student_id: i64,
     pub year: Option<i64>,
+    // the semester of the club for semester clubs, the whole year when left out
+    pub semester: Option<i64>,
+}
+
+// body of a join request made by the student themselves
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JoinableClub {
+    pub semester: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdatableClubRequest {
+    pub membership_status: Option<SubmissionStatus>,
+    // only allowed together with a declined membership_status
+    pub decline_reason: Option<MultiLangString>,
+    pub staff_note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkUpdatableClubRequest {
+    pub join_request_ids: Vec<Uuid>,
     pub membership_status: SubmissionStatus,
+    pub decline_reason: Option<MultiLangString>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BulkUpdateClubRequestResult {
+    #[schema(value_type = String)]
+    pub id: Uuid,
+    pub membership_status: Option<SubmissionStatus>,
+    pub error: Option<ErrorType<String>>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -41,16 +165,31 @@ pub enum ClubRequestSortableField {
     ClubId,
     StudentId,
     Year,
+    Semester,
     MembershipStatus,
     CreatedAt,
 }
 
+#[derive(FromRow)]
+struct ClubRequestNotes {
+    decline_reason_th: Option<String>,
+    decline_reason_en: Option<String>,
+    staff_note: Option<String>,
+}
+
+struct ClubSeats {
+    capacity: i64,
+    approved: i64,
+    pending: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct ClubRequestTable {
     pub id: Uuid,
     pub club_id: Uuid,
     pub student_id: i64,
     pub year: i64,
+    pub semester: Option<i64>,
     pub membership_status: SubmissionStatus,
     pub created_at: Option<DateTime<Utc>>,
 }
@@ -60,7 +199,7 @@ impl ClubRequestTable {
         Ok(sqlx::query_as!(
             Self,
             r#"
-                SELECT id, club_id, student_id, year, membership_status as "membership_status: _", created_at FROM club_members WHERE id = $1
+                SELECT id, club_id, student_id, year, semester, membership_status as "membership_status: _", created_at FROM club_members WHERE id = $1
             "#,
             id
         )
@@ -70,6 +209,7 @@ impl ClubRequestTable {
 
     fn construct_query_string(
         request_params: &RequestType<ClubRequest, QueryableClubRequest, ClubRequestSortableField>,
+        paginate: bool,
     ) -> (
         String,
         Vec<&Uuid>,
@@ -78,7 +218,7 @@ impl ClubRequestTable {
         Vec<u32>,
     ) {
         let query = r#"
-            SELECT id, club_id, student_id, year, membership_status, created_at FROM club_members
+            SELECT id, club_id, student_id, year, semester, membership_status, created_at FROM club_members
         "#;
 
         let mut query = String::from(query);
@@ -139,6 +279,38 @@ impl ClubRequestTable {
                     query_counts += 1;
                 }
 
+                if let Some(semester) = &data.semester {
+                    i64_params.push(semester);
+
+                    if query.contains("WHERE") {
+                        query.push_str(&format!(
+                            " AND (semester IS NULL OR semester = ${query_counts})"
+                        ));
+                    } else {
+                        query.push_str(&format!(
+                            " WHERE (semester IS NULL OR semester = ${query_counts})"
+                        ));
+                    }
+
+                    query_counts += 1;
+                }
+
+                if let Some(classroom_id) = &data.classroom_id {
+                    i64_params.push(classroom_id);
+
+                    let clause = format!(
+                        "student_id IN (SELECT unnest(students) FROM classroom WHERE id = ${query_counts})"
+                    );
+
+                    if query.contains("WHERE") {
+                        query.push_str(&format!(" AND {clause}"));
+                    } else {
+                        query.push_str(&format!(" WHERE {clause}"));
+                    }
+
+                    query_counts += 1;
+                }
+
                 if let Some(membership_status) = &data.membership_status {
                     submission_status_params.push(membership_status);
 
@@ -175,6 +347,7 @@ impl ClubRequestTable {
                         ClubRequestSortableField::ClubId => query.push_str(" club_id"),
                         ClubRequestSortableField::StudentId => query.push_str(" student_id"),
                         ClubRequestSortableField::Year => query.push_str(" year"),
+                        ClubRequestSortableField::Semester => query.push_str(" semester"),
                         ClubRequestSortableField::MembershipStatus => {
                             query.push_str(" membership_status")
                         }
@@ -186,23 +359,25 @@ impl ClubRequestTable {
             }
         }
 
-        let pagination = match &request_params.pagination {
-            Some(pagination) => pagination,
-            None => &PaginationConfig {
-                size: Some(50),
-                p: 1,
-            },
-        };
+        if paginate {
+            let pagination = match &request_params.pagination {
+                Some(pagination) => pagination,
+                None => &PaginationConfig {
+                    size: Some(50),
+                    p: 1,
+                },
+            };
 
-        let size = pagination.size.unwrap_or(50);
-        let page = pagination.p;
+            let size = pagination.size.unwrap_or(50);
+            let page = pagination.p;
 
-        let next_count = query_counts + 1;
+            let next_count = query_counts + 1;
 
-        query.push_str(&format!(" LIMIT ${query_counts} OFFSET ${next_count}",));
+            query.push_str(&format!(" LIMIT ${query_counts} OFFSET ${next_count}",));
 
-        pagination_params.push(size);
-        pagination_params.push((page - 1) * size);
+            pagination_params.push(size);
+            pagination_params.push((page - 1) * size);
+        }
 
         (
             query,
@@ -216,9 +391,25 @@ impl ClubRequestTable {
     pub async fn query(
         pool: &sqlx::PgPool,
         request_params: &RequestType<ClubRequest, QueryableClubRequest, ClubRequestSortableField>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        Self::query_with_pagination(pool, request_params, true).await
+    }
+
+    // every matching row, for exports where a page would silently cut the list short
+    pub async fn query_all(
+        pool: &sqlx::PgPool,
+        request_params: &RequestType<ClubRequest, QueryableClubRequest, ClubRequestSortableField>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        Self::query_with_pagination(pool, request_params, false).await
+    }
+
+    async fn query_with_pagination(
+        pool: &sqlx::PgPool,
+        request_params: &RequestType<ClubRequest, QueryableClubRequest, ClubRequestSortableField>,
+        paginate: bool,
     ) -> Result<Vec<Self>, sqlx::Error> {
         let (query, uuid_params, i64_params, submission_status_params, pagination_params) =
-            Self::construct_query_string(request_params);
+            Self::construct_query_string(request_params, paginate);
 
         let mut res = sqlx::query_as::<_, Self>(&query);
 
@@ -243,28 +434,621 @@ impl ClubRequestTable {
         Ok(res.fetch_all(pool).await?)
     }
 
+    // (approved, pending) requests taking a seat in the given year and semester, whole year
+    // members take a seat in both semesters and a whole year request counts everyone
+    pub(crate) async fn count_taken_seats<'c, E>(
+        executor: E,
+        club_id: Uuid,
+        year: i64,
+        semester: Option<i64>,
+        exclude_request_id: Option<Uuid>,
+    ) -> Result<(i64, i64), sqlx::Error>
+    where
+        E: sqlx::Executor<'c, Database = Postgres>,
+    {
+        sqlx::query_as::<_, (i64, i64)>(
+            r#"
+            SELECT
+                COUNT(id) FILTER (WHERE membership_status = 'approved'),
+                COUNT(id) FILTER (WHERE membership_status = 'pending')
+            FROM club_members
+            WHERE club_id = $1 AND year = $2 AND id IS DISTINCT FROM $3
+                AND ($4::bigint IS NULL OR semester IS NOT DISTINCT FROM $4 OR semester IS NULL)
+            "#,
+        )
+        .bind(club_id)
+        .bind(year)
+        .bind(exclude_request_id)
+        .bind(semester)
+        .fetch_one(executor)
+        .await
+    }
+
+    // lock the club row so that concurrent joins and approvals for the same club are
+    // serialized, then count the seats already taken in the given year and semester
+    // None means the club has no capacity limit
+    async fn lock_club_seats(
+        transaction: &mut Transaction<'_, Postgres>,
+        club_id: Uuid,
+        year: i64,
+        semester: Option<i64>,
+        exclude_request_id: Option<Uuid>,
+    ) -> Result<Option<ClubSeats>, sqlx::Error> {
+        let (capacity,) = sqlx::query_as::<_, (Option<i64>,)>(
+            r#"
+            SELECT capacity FROM clubs WHERE id = $1 FOR UPDATE
+            "#,
+        )
+        .bind(club_id)
+        .fetch_one(&mut *transaction)
+        .await?;
+
+        let capacity = match capacity {
+            Some(capacity) => capacity,
+            None => return Ok(None),
+        };
+
+        let (approved, pending) = Self::count_taken_seats(
+            &mut *transaction,
+            club_id,
+            year,
+            semester,
+            exclude_request_id,
+        )
+        .await?;
+
+        Ok(Some(ClubSeats {
+            capacity,
+            approved,
+            pending,
+        }))
+    }
+
+    // hand a seat freed in the given semester to the waitlists it could go to, a whole year
+    // seat can go to either semester and a semester seat may be what a whole year request
+    // was waiting for
+    pub async fn promote_waitlisted(
+        transaction: &mut Transaction<'_, Postgres>,
+        club_id: Uuid,
+        year: i64,
+        semester: Option<i64>,
+    ) -> Result<(), sqlx::Error> {
+        let waitlists = match semester {
+            Some(semester) => vec![Some(semester), None],
+            None => vec![None, Some(1), Some(2)],
+        };
+
+        for waitlist in waitlists {
+            Self::promote_waitlist(transaction, club_id, year, waitlist).await?;
+        }
+
+        Ok(())
+    }
+
+    // move the oldest waitlisted requests of a semester back to pending for as long as the club
+    // has open seats in it
+    async fn promote_waitlist(
+        transaction: &mut Transaction<'_, Postgres>,
+        club_id: Uuid,
+        year: i64,
+        semester: Option<i64>,
+    ) -> Result<(), sqlx::Error> {
+        let open_seats =
+            match Self::lock_club_seats(transaction, club_id, year, semester, None).await? {
+                Some(seats) => Some(seats.capacity - seats.approved - seats.pending),
+                None => None,
+            };
+
+        if let Some(open_seats) = open_seats {
+            if open_seats <= 0 {
+                return Ok(());
+            }
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE club_members SET membership_status = 'pending'
+            WHERE id IN (
+                SELECT id FROM club_members
+                WHERE club_id = $1 AND year = $2 AND membership_status = 'waitlisted'
+                    AND semester IS NOT DISTINCT FROM $4
+                ORDER BY created_at, id
+                LIMIT $3
+            )
+            "#,
+        )
+        .bind(club_id)
+        .bind(year)
+        .bind(open_seats)
+        .bind(semester)
+        .execute(&mut *transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_waitlist_position(
+        pool: &sqlx::PgPool,
+        id: Uuid,
+    ) -> Result<Option<u32>, sqlx::Error> {
+        let res = sqlx::query_as::<_, (i64,)>(
+            r#"
+            SELECT position FROM (
+                SELECT waitlist.id, ROW_NUMBER() OVER (ORDER BY waitlist.created_at, waitlist.id) AS position
+                FROM club_members waitlist
+                INNER JOIN club_members request ON waitlist.club_id = request.club_id AND waitlist.year = request.year
+                    AND waitlist.semester IS NOT DISTINCT FROM request.semester
+                WHERE request.id = $1 AND waitlist.membership_status = 'waitlisted'
+            ) queue WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(res.map(|(position,)| position as u32))
+    }
+
+    async fn lock_by_id(
+        transaction: &mut Transaction<'_, Postgres>,
+        id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, ClubRequestTable>(
+            r#"
+            SELECT id, club_id, student_id, year, semester, membership_status, created_at FROM club_members WHERE id = $1 FOR UPDATE
+            "#,
+        )
+        .bind(id)
+        .fetch_one(&mut *transaction)
+        .await
+    }
+
+    pub async fn approve(
+        transaction: &mut Transaction<'_, Postgres>,
+        id: Uuid,
+    ) -> Result<(), ClubRequestError> {
+        let request = Self::lock_by_id(transaction, id).await?;
+
+        // waitlisted students have to be promoted to pending first so the queue order is kept
+        // and withdrawn requests can only be made again by the student
+        match request.membership_status {
+            SubmissionStatus::Waitlisted | SubmissionStatus::Withdrawn => {
+                return Err(ClubRequestError::InvalidTransition {
+                    from: request.membership_status,
+                    to: SubmissionStatus::Approved,
+                });
+            }
+            _ => (),
+        }
+
+        let seats = Self::lock_club_seats(
+            transaction,
+            request.club_id,
+            request.year,
+            request.semester,
+            Some(id),
+        )
+        .await?;
+
+        if let Some(seats) = seats {
+            if seats.approved >= seats.capacity {
+                return Err(ClubRequestError::ClubFull {
+                    capacity: seats.capacity,
+                    remaining_seats: (seats.capacity - seats.approved).max(0),
+                });
+            }
+        }
+
+        let settings = RegistrationSettings::get_by_year(&mut *transaction, request.year).await?;
+        let joined_clubs = Self::count_joined_clubs(
+            transaction,
+            request.student_id,
+            request.year,
+            request.semester,
+            Some(id),
+        )
+        .await?;
+
+        if joined_clubs >= settings.max_clubs_per_student {
+            return Err(ClubRequestError::ClubLimitReached {
+                max_clubs_per_student: settings.max_clubs_per_student,
+                semester: request.semester,
+            });
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE club_members
+            SET membership_status = 'approved', decline_reason_th = NULL, decline_reason_en = NULL
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(&mut *transaction)
+        .await?;
+
+        Self::withdraw_open_requests(
+            transaction,
+            request.student_id,
+            request.year,
+            settings.max_clubs_per_student,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    // approved clubs of the student in semester 1 and 2, whole year clubs count in both
+    async fn count_joined_clubs_per_semester(
+        transaction: &mut Transaction<'_, Postgres>,
+        student_id: i64,
+        year: i64,
+        exclude_request_id: Option<Uuid>,
+    ) -> Result<(i64, i64), sqlx::Error> {
+        sqlx::query_as::<_, (i64, i64)>(
+            r#"
+            SELECT
+                COUNT(id) FILTER (WHERE semester IS DISTINCT FROM 2),
+                COUNT(id) FILTER (WHERE semester IS DISTINCT FROM 1)
+            FROM club_members
+            WHERE student_id = $1 AND year = $2 AND membership_status = 'approved' AND id IS DISTINCT FROM $3
+            "#,
+        )
+        .bind(student_id)
+        .bind(year)
+        .bind(exclude_request_id)
+        .fetch_one(&mut *transaction)
+        .await
+    }
+
+    // the club limit applies per semester, so a whole year membership is checked against
+    // the fuller of the two semesters
+    async fn count_joined_clubs(
+        transaction: &mut Transaction<'_, Postgres>,
+        student_id: i64,
+        year: i64,
+        semester: Option<i64>,
+        exclude_request_id: Option<Uuid>,
+    ) -> Result<i64, sqlx::Error> {
+        let (first, second) = Self::count_joined_clubs_per_semester(
+            transaction,
+            student_id,
+            year,
+            exclude_request_id,
+        )
+        .await?;
+
+        Ok(match semester {
+            Some(1) => first,
+            Some(_) => second,
+            None => first.max(second),
+        })
+    }
+
+    // withdraw the pending and waitlisted requests of the student in the given year that
+    // overlap a semester in which the student already reached the club limit, and hand the
+    // freed seats over to the waitlists of those clubs
+    pub async fn withdraw_open_requests(
+        transaction: &mut Transaction<'_, Postgres>,
+        student_id: i64,
+        year: i64,
+        max_clubs_per_student: i64,
+    ) -> Result<(), sqlx::Error> {
+        let (first, second) =
+            Self::count_joined_clubs_per_semester(transaction, student_id, year, None).await?;
+
+        if first < max_clubs_per_student && second < max_clubs_per_student {
+            return Ok(());
+        }
+
+        let mut clubs = sqlx::query_as::<_, (Uuid, Option<i64>)>(
+            r#"
+            UPDATE club_members SET membership_status = 'withdrawn'
+            WHERE student_id = $1 AND year = $2 AND (membership_status = 'pending' OR membership_status = 'waitlisted')
+                AND ((semester IS DISTINCT FROM 2 AND $3) OR (semester IS DISTINCT FROM 1 AND $4))
+            RETURNING club_id, semester
+            "#,
+        )
+        .bind(student_id)
+        .bind(year)
+        .bind(first >= max_clubs_per_student)
+        .bind(second >= max_clubs_per_student)
+        .fetch_all(&mut *transaction)
+        .await?;
+
+        clubs.sort();
+        clubs.dedup();
+
+        for (club_id, semester) in clubs {
+            Self::promote_waitlisted(transaction, club_id, year, semester).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn decline(
+        transaction: &mut Transaction<'_, Postgres>,
+        id: Uuid,
+        decline_reason: Option<&MultiLangString>,
+    ) -> Result<(), ClubRequestError> {
+        let request = Self::lock_by_id(transaction, id).await?;
+
+        // a withdrawn request is the student's decision and stays that way
+        match request.membership_status {
+            SubmissionStatus::Withdrawn => {
+                return Err(ClubRequestError::InvalidTransition {
+                    from: request.membership_status,
+                    to: SubmissionStatus::Declined,
+                });
+            }
+            SubmissionStatus::Declined => return Ok(()),
+            _ => (),
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE club_members
+            SET membership_status = 'declined', decline_reason_th = $2, decline_reason_en = $3
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(decline_reason.map(|reason| reason.th.clone()))
+        .bind(decline_reason.and_then(|reason| reason.en.clone()))
+        .execute(&mut *transaction)
+        .await?;
+
+        // a declined request or a removed member frees up a seat for the waitlist
+        Self::promote_waitlisted(transaction, request.club_id, request.year, request.semester)
+            .await?;
+
+        Ok(())
+    }
+
+    // admin override, sets any status without the transition, capacity and per student limit
+    // checks, the waitlist and the other requests of the student are still kept consistent
+    pub async fn force_status(
+        transaction: &mut Transaction<'_, Postgres>,
+        id: Uuid,
+        membership_status: SubmissionStatus,
+        decline_reason: Option<&MultiLangString>,
+    ) -> Result<(), sqlx::Error> {
+        let request = Self::lock_by_id(transaction, id).await?;
+        let decline_reason = match membership_status {
+            SubmissionStatus::Declined => decline_reason,
+            _ => None,
+        };
+
+        sqlx::query(
+            r#"
+            UPDATE club_members
+            SET membership_status = $2, decline_reason_th = $3, decline_reason_en = $4
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(&membership_status)
+        .bind(decline_reason.map(|reason| reason.th.clone()))
+        .bind(decline_reason.and_then(|reason| reason.en.clone()))
+        .execute(&mut *transaction)
+        .await?;
+
+        if let SubmissionStatus::Approved = membership_status {
+            let settings =
+                RegistrationSettings::get_by_year(&mut *transaction, request.year).await?;
+
+            Self::withdraw_open_requests(
+                transaction,
+                request.student_id,
+                request.year,
+                settings.max_clubs_per_student,
+            )
+            .await?;
+        }
+
+        Self::promote_waitlisted(transaction, request.club_id, request.year, request.semester).await
+    }
+
+    pub async fn set_staff_note(
+        transaction: &mut Transaction<'_, Postgres>,
+        id: Uuid,
+        staff_note: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE club_members SET staff_note = NULLIF($2, '') WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(staff_note)
+        .execute(&mut *transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_notes(pool: &sqlx::PgPool, id: Uuid) -> Result<ClubRequestNotes, sqlx::Error> {
+        sqlx::query_as::<_, ClubRequestNotes>(
+            r#"
+            SELECT decline_reason_th, decline_reason_en, staff_note FROM club_members WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_one(pool)
+        .await
+    }
+
+    // set the status of a student in a club directly, creating the row if the student never
+    // made a request, used by allocation runs which decide the outcome themselves
+    pub async fn set_status(
+        transaction: &mut Transaction<'_, Postgres>,
+        club_id: Uuid,
+        student_id: i64,
+        year: i64,
+        membership_status: SubmissionStatus,
+    ) -> Result<(), sqlx::Error> {
+        let existing = sqlx::query_as::<_, (Uuid,)>(
+            r#"
+            SELECT id FROM club_members
+            WHERE club_id = $1 AND student_id = $2 AND year = $3
+            ORDER BY created_at DESC LIMIT 1
+            FOR UPDATE
+            "#,
+        )
+        .bind(club_id)
+        .bind(student_id)
+        .bind(year)
+        .fetch_optional(&mut *transaction)
+        .await?;
+
+        match existing {
+            Some((id,)) => {
+                sqlx::query(
+                    r#"
+                    UPDATE club_members SET membership_status = $2 WHERE id = $1
+                    "#,
+                )
+                .bind(id)
+                .bind(membership_status)
+                .execute(&mut *transaction)
+                .await?;
+            }
+            None => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO club_members (club_id, student_id, year, semester, membership_status)
+                    VALUES ($1, $2, $3, (SELECT semester FROM clubs WHERE id = $1), $4)
+                    "#,
+                )
+                .bind(club_id)
+                .bind(student_id)
+                .bind(year)
+                .bind(membership_status)
+                .execute(&mut *transaction)
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // registration_status is the student's registration window in the year of the request,
+    // approved members can only leave while it is open unless the year allows leaving anytime
+    pub async fn withdraw(
+        transaction: &mut Transaction<'_, Postgres>,
+        id: Uuid,
+        settings: &RegistrationSettings,
+        registration_status: &RegistrationStatus,
+    ) -> Result<Self, ClubRequestError> {
+        let request = Self::lock_by_id(transaction, id).await?;
+
+        match (&request.membership_status, registration_status) {
+            (SubmissionStatus::Pending | SubmissionStatus::Waitlisted, _) => (),
+            (SubmissionStatus::Approved, _) if settings.allow_leaving_after_close => (),
+            (SubmissionStatus::Approved, RegistrationStatus::Open) => (),
+            (SubmissionStatus::Approved, RegistrationStatus::Closed { next_opening }) => {
+                return Err(ClubRequestError::RegistrationClosed {
+                    next_opening: *next_opening,
+                });
+            }
+            _ => {
+                return Err(ClubRequestError::InvalidTransition {
+                    from: request.membership_status,
+                    to: SubmissionStatus::Withdrawn,
+                });
+            }
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE club_members SET membership_status = 'withdrawn' WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(&mut *transaction)
+        .await?;
+
+        Self::promote_waitlisted(transaction, request.club_id, request.year, request.semester)
+            .await?;
+
+        Ok(request)
+    }
+
     pub async fn create(
         pool: &sqlx::PgPool,
         request: CreatableClubRequest,
-    ) -> Result<Self, sqlx::Error> {
+        actor: Uuid,
+    ) -> Result<Self, ClubRequestError> {
         let mut transaction = pool.begin().await?;
+        ClubMemberHistory::set_actor(&mut transaction, actor).await?;
+
+        let (archived, club_year, club_semester) = sqlx::query_as::<_, (bool, i64, Option<i64>)>(
+            r#"
+            SELECT archived_at IS NOT NULL, year, semester FROM clubs WHERE id = $1
+            "#,
+        )
+        .bind(request.club_id)
+        .fetch_one(&mut transaction)
+        .await?;
+
+        if archived {
+            return Err(ClubRequestError::ClubArchived);
+        }
+
+        // requests always belong to the academic year the club runs in
+        let year = request.year.unwrap_or(club_year);
 
-        let year = match request.year {
-            Some(year) => year,
-            None => get_current_academic_year() as i64,
+        if year != club_year {
+            return Err(ClubRequestError::WrongYear { club_year });
+        }
+
+        // semester clubs take their own semester, whole year clubs may be joined for just one
+        let semester = match (club_semester, request.semester) {
+            (_, Some(semester)) if !(1..=2).contains(&semester) => {
+                return Err(ClubRequestError::InvalidSemester);
+            }
+            (Some(club_semester), Some(semester)) if semester != club_semester => {
+                return Err(ClubRequestError::WrongSemester { club_semester });
+            }
+            (Some(club_semester), _) => Some(club_semester),
+            (None, semester) => semester,
         };
 
+        let settings = RegistrationSettings::get_by_year(&mut transaction, year).await?;
+        let joined_clubs =
+            Self::count_joined_clubs(&mut transaction, request.student_id, year, semester, None)
+                .await?;
+
+        if joined_clubs >= settings.max_clubs_per_student {
+            return Err(ClubRequestError::ClubLimitReached {
+                max_clubs_per_student: settings.max_clubs_per_student,
+                semester,
+            });
+        }
+
+        // once approved and pending requests fill up the club, new requests join the waitlist
+        let membership_status =
+            match Self::lock_club_seats(&mut transaction, request.club_id, year, semester, None)
+                .await?
+            {
+                Some(seats) if seats.approved + seats.pending >= seats.capacity => {
+                    SubmissionStatus::Waitlisted
+                }
+                _ => SubmissionStatus::Pending,
+            };
+
         let res = sqlx::query_as::<_, ClubRequestTable>(
             r#"
-            INSERT INTO club_members (club_id, student_id, year, membership_status)
-            VALUES ($1, $2, $3, 'pending')
-            RETURNING id, created_at, club_id, student_id, year, membership_status
+            INSERT INTO club_members (club_id, student_id, year, semester, membership_status)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, created_at, club_id, student_id, year, semester, membership_status
             "#,
         )
         .bind(&request.club_id)
         .bind(&request.student_id)
         .bind(&year)
-        // .bind(&request.membership_status)
+        .bind(semester)
+        .bind(&membership_status)
         .fetch_one(&mut transaction)
         .await?;
 
@@ -276,6 +1060,7 @@ impl ClubRequestTable {
             club_id: res.club_id,
             student_id: res.student_id,
             year: res.year,
+            semester: res.semester,
             membership_status: res.membership_status,
         })
     }
@@ -299,7 +1084,12 @@ pub struct DefaultClubRequest {
     pub club: Club,
     pub student: Student,
     pub year: i64,
+    pub semester: Option<i64>,
     pub membership_status: SubmissionStatus,
+    pub waitlist_position: Option<u32>,
+    pub decline_reason: Option<MultiLangString>,
+    // only visible to the staff of the club
+    pub staff_note: Option<String>,
 }
 
 impl DefaultClubRequest {
@@ -322,6 +1112,13 @@ impl DefaultClubRequest {
             Some(FetchLevel::IdOnly),
         )
         .await?;
+        let waitlist_position = match table.membership_status {
+            SubmissionStatus::Waitlisted => {
+                ClubRequestTable::get_waitlist_position(pool, table.id).await?
+            }
+            _ => None,
+        };
+        let notes = ClubRequestTable::get_notes(pool, table.id).await?;
 
         Ok(Self {
             id: table.id,
@@ -329,7 +1126,13 @@ impl DefaultClubRequest {
             club,
             student,
             year: table.year,
+            semester: table.semester,
             membership_status: table.membership_status,
+            waitlist_position,
+            decline_reason: notes
+                .decline_reason_th
+                .map(|th| MultiLangString::new(notes.decline_reason_en, th)),
+            staff_note: notes.staff_note,
         })
     }
 }
@@ -413,38 +1216,201 @@ impl ClubRequest {
         Ok(res)
     }
 
-    pub async fn approve_request(
+    pub fn club_id(&self) -> Option<Uuid> {
+        match self {
+            ClubRequest::Default(request) | ClubRequest::Compact(request) => match &request.club {
+                Club::IdOnly(club) => Some(club.id),
+                Club::Compact(club) => Some(club.id),
+                Club::Default(club) => Some(club.id),
+            },
+            ClubRequest::IdOnly(_) => None,
+        }
+    }
+
+    // staff notes are private to the club, everyone else only gets the decline reason
+    pub fn hide_staff_note(&mut self) {
+        match self {
+            ClubRequest::Default(request) | ClubRequest::Compact(request) => {
+                request.staff_note = None
+            }
+            ClubRequest::IdOnly(_) => (),
+        }
+    }
+
+    pub async fn update_request(
         pool: &sqlx::PgPool,
         id: Uuid,
+        update: &UpdatableClubRequest,
+        actor: Uuid,
         fetch_level: Option<FetchLevel>,
         descendant_fetch_level: Option<FetchLevel>,
-    ) -> Result<Self, sqlx::Error> {
-        sqlx::query(
-            r#"
-            UPDATE club_members SET membership_status = 'approved' WHERE id = $1
-            "#,
-        )
-        .bind(id)
-        .execute(pool)
-        .await?;
+    ) -> Result<Self, ClubRequestError> {
+        let mut transaction = pool.begin().await?;
+        ClubMemberHistory::set_actor(&mut transaction, actor).await?;
+
+        match &update.membership_status {
+            Some(SubmissionStatus::Approved) => {
+                ClubRequestTable::approve(&mut transaction, id).await?
+            }
+            Some(SubmissionStatus::Declined) => {
+                ClubRequestTable::decline(&mut transaction, id, update.decline_reason.as_ref())
+                    .await?
+            }
+            Some(membership_status) => {
+                let request = ClubRequestTable::lock_by_id(&mut transaction, id).await?;
+
+                return Err(ClubRequestError::InvalidTransition {
+                    from: request.membership_status,
+                    to: membership_status.clone(),
+                });
+            }
+            None => (),
+        }
+
+        if let Some(staff_note) = &update.staff_note {
+            ClubRequestTable::set_staff_note(&mut transaction, id, staff_note).await?;
+        }
+
+        transaction.commit().await?;
 
         Ok(ClubRequest::get_by_id(pool, id, fetch_level, descendant_fetch_level).await?)
     }
 
-    pub async fn deny_request(
+    pub async fn override_request(
         pool: &sqlx::PgPool,
         id: Uuid,
+        update: &UpdatableClubRequest,
+        actor: Uuid,
         fetch_level: Option<FetchLevel>,
         descendant_fetch_level: Option<FetchLevel>,
-    ) -> Result<Self, sqlx::Error> {
-        sqlx::query(
+    ) -> Result<Self, ClubRequestError> {
+        let mut transaction = pool.begin().await?;
+        ClubMemberHistory::set_actor(&mut transaction, actor).await?;
+
+        if let Some(membership_status) = &update.membership_status {
+            ClubRequestTable::force_status(
+                &mut transaction,
+                id,
+                membership_status.clone(),
+                update.decline_reason.as_ref(),
+            )
+            .await?;
+        }
+
+        if let Some(staff_note) = &update.staff_note {
+            ClubRequestTable::set_staff_note(&mut transaction, id, staff_note).await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(ClubRequest::get_by_id(pool, id, fetch_level, descendant_fetch_level).await?)
+    }
+
+    // Applies the same status to many requests in one transaction. Every request runs in its
+    // own savepoint so a failing item is rolled back on its own and reported next to the others.
+    pub async fn bulk_update(
+        pool: &sqlx::PgPool,
+        ids: &[Uuid],
+        membership_status: SubmissionStatus,
+        decline_reason: Option<&MultiLangString>,
+        user: &User,
+    ) -> Result<Vec<(Uuid, Result<SubmissionStatus, ClubRequestError>)>, sqlx::Error> {
+        let mut transaction = pool.begin().await?;
+        ClubMemberHistory::set_actor(&mut transaction, user.id).await?;
+
+        let requests = sqlx::query_as::<_, (Uuid, Uuid)>(
             r#"
-            UPDATE club_members SET membership_status = 'declined' WHERE id = $1
+            SELECT id, club_id FROM club_members WHERE id = ANY($1)
             "#,
         )
-        .bind(id)
-        .execute(pool)
-        .await?;
+        .bind(ids)
+        .fetch_all(&mut transaction)
+        .await?
+        .into_iter()
+        .collect::<HashMap<Uuid, Uuid>>();
+
+        let mut club_ids = requests.values().copied().collect::<Vec<Uuid>>();
+        club_ids.sort();
+        club_ids.dedup();
+
+        // staff membership is checked once per club instead of once per request
+        let staffed_clubs = Club::get_managed_club_ids(&mut transaction, user, &club_ids).await?;
+
+        // work through the requests club by club so concurrent batches lock clubs in the same order
+        let mut order = ids
+            .iter()
+            .copied()
+            .enumerate()
+            .collect::<Vec<(usize, Uuid)>>();
+        order.sort_by_key(|(i, id)| (requests.get(id).copied(), *i));
+
+        let mut results = Vec::with_capacity(ids.len());
+
+        for (i, id) in order {
+            let result = match requests.get(&id) {
+                None => Err(ClubRequestError::NotFound),
+                Some(club_id) if !staffed_clubs.contains(club_id) => {
+                    Err(ClubRequestError::NotClubStaff)
+                }
+                Some(_) => {
+                    let mut savepoint = Acquire::begin(&mut transaction).await?;
+                    let request = ClubRequestTable::lock_by_id(&mut savepoint, id).await?;
+
+                    // the route only lets approvals and declines through
+                    let res = match membership_status {
+                        SubmissionStatus::Approved => {
+                            ClubRequestTable::approve(&mut savepoint, id).await
+                        }
+                        SubmissionStatus::Declined => {
+                            ClubRequestTable::decline(&mut savepoint, id, decline_reason).await
+                        }
+                        _ => Err(ClubRequestError::InvalidTransition {
+                            from: request.membership_status,
+                            to: membership_status.clone(),
+                        }),
+                    };
+
+                    match res {
+                        Ok(()) => {
+                            savepoint.commit().await?;
+                            Ok(membership_status.clone())
+                        }
+                        Err(e) => {
+                            savepoint.rollback().await?;
+                            Err(e)
+                        }
+                    }
+                }
+            };
+
+            results.push((i, id, result));
+        }
+
+        transaction.commit().await?;
+
+        results.sort_by_key(|(i, _, _)| *i);
+
+        Ok(results
+            .into_iter()
+            .map(|(_, id, result)| (id, result))
+            .collect())
+    }
+
+    pub async fn withdraw_request(
+        pool: &sqlx::PgPool,
+        id: Uuid,
+        settings: &RegistrationSettings,
+        registration_status: &RegistrationStatus,
+        actor: Uuid,
+        fetch_level: Option<FetchLevel>,
+        descendant_fetch_level: Option<FetchLevel>,
+    ) -> Result<Self, ClubRequestError> {
+        let mut transaction = pool.begin().await?;
+        ClubMemberHistory::set_actor(&mut transaction, actor).await?;
+
+        ClubRequestTable::withdraw(&mut transaction, id, settings, registration_status).await?;
+
+        transaction.commit().await?;
 
         Ok(ClubRequest::get_by_id(pool, id, fetch_level, descendant_fetch_level).await?)
     }
@@ -452,10 +1418,11 @@ impl ClubRequest {
     pub async fn create(
         pool: &sqlx::PgPool,
         request: CreatableClubRequest,
+        actor: Uuid,
         fetch_level: Option<FetchLevel>,
         descendant_fetch_level: Option<FetchLevel>,
-    ) -> Result<Self, sqlx::Error> {
-        let res = ClubRequestTable::create(pool, request).await?;
+    ) -> Result<Self, ClubRequestError> {
+        let res = ClubRequestTable::create(pool, request, actor).await?;
 
         Ok(ClubRequest::get_by_id(pool, res.id, fetch_level, descendant_fetch_level).await?)
     }
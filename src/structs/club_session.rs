@@ -0,0 +1,535 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Decode, Encode, FromRow, Postgres, Type};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::utils::date::AcademicCalendar;
+
+use super::{common::FetchLevel, student::Student};
+
+#[derive(Debug, Clone, Copy, PartialEq, ToSchema)]
+pub enum AttendanceStatus {
+    Present,
+    Late,
+    Absent,
+    // absent with a reason accepted by the staff
+    Excused,
+}
+
+impl AttendanceStatus {
+    pub fn to_string(&self) -> String {
+        match self {
+            AttendanceStatus::Present => "present".to_string(),
+            AttendanceStatus::Late => "late".to_string(),
+            AttendanceStatus::Absent => "absent".to_string(),
+            AttendanceStatus::Excused => "excused".to_string(),
+        }
+    }
+
+    pub fn from_string(s: &str) -> Option<AttendanceStatus> {
+        match s {
+            "present" => Some(AttendanceStatus::Present),
+            "late" => Some(AttendanceStatus::Late),
+            "absent" => Some(AttendanceStatus::Absent),
+            "excused" => Some(AttendanceStatus::Excused),
+            _ => None,
+        }
+    }
+}
+
+impl Type<Postgres> for AttendanceStatus {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        sqlx::postgres::PgTypeInfo::with_name("attendance_status")
+    }
+}
+
+impl Encode<'_, Postgres> for AttendanceStatus {
+    fn encode_by_ref(
+        &self,
+        buf: &mut <sqlx::Postgres as sqlx::database::HasArguments<'_>>::ArgumentBuffer,
+    ) -> sqlx::encode::IsNull {
+        let s = self.to_string();
+        <String as sqlx::Encode<sqlx::Postgres>>::encode(s, buf)
+    }
+}
+
+impl Decode<'_, Postgres> for AttendanceStatus {
+    fn decode(
+        value: <Postgres as sqlx::database::HasValueRef<'_>>::ValueRef,
+    ) -> Result<Self, sqlx::error::BoxDynError> {
+        let s = <String as Decode<Postgres>>::decode(value)?;
+
+        match AttendanceStatus::from_string(&s) {
+            Some(status) => Ok(status),
+            None => Err("Invalid attendance status".into()),
+        }
+    }
+}
+
+impl Serialize for AttendanceStatus {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for AttendanceStatus {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+
+        match AttendanceStatus::from_string(&s) {
+            Some(status) => Ok(status),
+            None => Err(serde::de::Error::custom("Invalid attendance status")),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ClubSessionError {
+    Database(sqlx::Error),
+    WrongYear { club_year: i64 },
+    WrongSemester { club_semester: i64 },
+    SessionExists { date: NaiveDate },
+    NotMember { student_ids: Vec<i64> },
+}
+
+impl From<sqlx::Error> for ClubSessionError {
+    fn from(e: sqlx::Error) -> Self {
+        ClubSessionError::Database(e)
+    }
+}
+
+impl std::fmt::Display for ClubSessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ClubSessionError::Database(e) => write!(f, "{}", e),
+            ClubSessionError::WrongYear { club_year } => write!(
+                f,
+                "the date is outside academic year {club_year} the club runs in"
+            ),
+            ClubSessionError::WrongSemester { club_semester } => {
+                write!(f, "the club only runs in semester {club_semester}")
+            }
+            ClubSessionError::SessionExists { date } => {
+                write!(f, "the club already has a session on {date}")
+            }
+            ClubSessionError::NotMember { student_ids } => write!(
+                f,
+                "students {:?} are not members of the club in the semester of the session",
+                student_ids
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatableClubSession {
+    pub date: NaiveDate,
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryableClubAttendance {
+    pub student_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdatableClubAttendance {
+    pub student_id: i64,
+    pub status: AttendanceStatus,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ClubSession {
+    #[schema(value_type = String)]
+    pub id: Uuid,
+    #[schema(value_type = String)]
+    pub club_id: Uuid,
+    #[schema(value_type = String, example = "2023-06-14")]
+    pub date: NaiveDate,
+    pub semester: i64,
+    pub title: Option<String>,
+    #[schema(value_type = Option<String>)]
+    pub created_by: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl ClubSession {
+    pub async fn get_by_id(
+        pool: &sqlx::PgPool,
+        club_id: Uuid,
+        id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT id, club_id, date, semester, title, created_by, created_at
+            FROM club_sessions WHERE id = $1 AND club_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(club_id)
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn get_by_club_id(
+        pool: &sqlx::PgPool,
+        club_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT id, club_id, date, semester, title, created_by, created_at
+            FROM club_sessions WHERE club_id = $1
+            ORDER BY date
+            "#,
+        )
+        .bind(club_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    // sessions have to fall within the academic year and the semester of the club
+    pub async fn create(
+        pool: &sqlx::PgPool,
+        club_id: Uuid,
+        session: &CreatableClubSession,
+        actor: Uuid,
+    ) -> Result<Self, ClubSessionError> {
+        let (club_year, club_semester) = sqlx::query_as::<_, (i64, Option<i64>)>(
+            r#"
+            SELECT year, semester FROM clubs WHERE id = $1
+            "#,
+        )
+        .bind(club_id)
+        .fetch_one(pool)
+        .await?;
+
+        let calendar = AcademicCalendar::get();
+
+        if calendar.academic_year_of_date(session.date) as i64 != club_year {
+            return Err(ClubSessionError::WrongYear { club_year });
+        }
+
+        let semester = calendar.semester_of_date(session.date) as i64;
+
+        if let Some(club_semester) = club_semester {
+            if semester != club_semester {
+                return Err(ClubSessionError::WrongSemester { club_semester });
+            }
+        }
+
+        let res = sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO club_sessions (club_id, date, semester, title, created_by)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (club_id, date) DO NOTHING
+            RETURNING id, club_id, date, semester, title, created_by, created_at
+            "#,
+        )
+        .bind(club_id)
+        .bind(session.date)
+        .bind(semester)
+        .bind(&session.title)
+        .bind(actor)
+        .fetch_optional(pool)
+        .await?;
+
+        res.ok_or(ClubSessionError::SessionExists { date: session.date })
+    }
+
+    pub async fn delete_by_id(
+        pool: &sqlx::PgPool,
+        club_id: Uuid,
+        id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        let res = sqlx::query(
+            r#"
+            DELETE FROM club_sessions WHERE id = $1 AND club_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(club_id)
+        .execute(pool)
+        .await?;
+
+        if res.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(FromRow)]
+struct ClubAttendanceTable {
+    student_id: i64,
+    status: Option<AttendanceStatus>,
+    note: Option<String>,
+    recorded_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClubAttendance {
+    pub student: Student,
+    // None until the staff mark the student
+    pub status: Option<AttendanceStatus>,
+    pub note: Option<String>,
+    pub recorded_at: Option<DateTime<Utc>>,
+}
+
+impl ClubAttendance {
+    // every member expected at the session, plus anyone marked before they left the club
+    pub async fn get_by_session(
+        pool: &sqlx::PgPool,
+        session: &ClubSession,
+        fetch_level: Option<FetchLevel>,
+        descendant_fetch_level: Option<FetchLevel>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query_as::<_, ClubAttendanceTable>(
+            r#"
+            SELECT students.student_id, club_attendance.status, club_attendance.note, club_attendance.recorded_at
+            FROM (
+                SELECT student_id FROM club_members
+                WHERE club_id = $1 AND year = (SELECT year FROM clubs WHERE id = $1) AND membership_status = 'approved'
+                    AND (semester IS NULL OR semester = $2)
+                UNION
+                SELECT student_id FROM club_attendance WHERE session_id = $3
+            ) students
+            LEFT JOIN club_attendance ON club_attendance.session_id = $3 AND club_attendance.student_id = students.student_id
+            ORDER BY students.student_id
+            "#,
+        )
+        .bind(session.club_id)
+        .bind(session.semester)
+        .bind(session.id)
+        .fetch_all(pool)
+        .await?;
+
+        let mut attendance = vec![];
+
+        for row in rows {
+            attendance.push(Self {
+                student: Student::get_by_id(
+                    pool,
+                    row.student_id as u32,
+                    fetch_level.clone(),
+                    descendant_fetch_level.clone(),
+                )
+                .await?,
+                status: row.status,
+                note: row.note,
+                recorded_at: row.recorded_at,
+            });
+        }
+
+        Ok(attendance)
+    }
+
+    // marks are all or nothing, one student outside the session fails the whole batch
+    pub async fn record(
+        pool: &sqlx::PgPool,
+        session: &ClubSession,
+        records: &[UpdatableClubAttendance],
+        actor: Uuid,
+    ) -> Result<(), ClubSessionError> {
+        let mut transaction = pool.begin().await?;
+
+        let members = sqlx::query_as::<_, (i64,)>(
+            r#"
+            SELECT student_id FROM club_members
+            WHERE club_id = $1 AND year = (SELECT year FROM clubs WHERE id = $1) AND membership_status = 'approved'
+                AND (semester IS NULL OR semester = $2)
+            "#,
+        )
+        .bind(session.club_id)
+        .bind(session.semester)
+        .fetch_all(&mut transaction)
+        .await?
+        .into_iter()
+        .map(|(student_id,)| student_id)
+        .collect::<HashSet<i64>>();
+
+        let mut student_ids = records
+            .iter()
+            .map(|record| record.student_id)
+            .filter(|student_id| !members.contains(student_id))
+            .collect::<Vec<i64>>();
+
+        if !student_ids.is_empty() {
+            student_ids.sort();
+            student_ids.dedup();
+
+            return Err(ClubSessionError::NotMember { student_ids });
+        }
+
+        for record in records {
+            sqlx::query(
+                r#"
+                INSERT INTO club_attendance (session_id, student_id, status, note, recorded_by)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (session_id, student_id) DO UPDATE SET
+                    status = EXCLUDED.status,
+                    note = EXCLUDED.note,
+                    recorded_by = EXCLUDED.recorded_by,
+                    recorded_at = now()
+                "#,
+            )
+            .bind(session.id)
+            .bind(record.student_id)
+            .bind(record.status)
+            .bind(&record.note)
+            .bind(actor)
+            .execute(&mut transaction)
+            .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[derive(FromRow)]
+struct AttendanceRateTable {
+    student_id: i64,
+    sessions: i64,
+    present: i64,
+    late: i64,
+    absent: i64,
+    excused: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttendanceRate {
+    pub student: Student,
+    // sessions held so far in the semesters the student is a member for, since their approval
+    pub sessions: i64,
+    pub present: i64,
+    pub late: i64,
+    pub absent: i64,
+    pub excused: i64,
+    pub unmarked: i64,
+    // see AttendanceRate::calculate
+    pub rate: Option<f64>,
+}
+
+impl AttendanceRate {
+    // the share of sessions the student came to, late included. excused sessions are left out
+    // of the count altogether so they neither help nor hurt, None when no session counts yet.
+    // shared with the evaluation reports so the pass mark is checked against the same number.
+    pub fn calculate(sessions: i64, attended: i64, excused: i64) -> Option<f64> {
+        match sessions - excused {
+            expected if expected <= 0 => None,
+            expected => Some(attended as f64 / expected as f64),
+        }
+    }
+
+    pub async fn get_by_club_id(
+        pool: &sqlx::PgPool,
+        club_id: Uuid,
+        filter: Option<&QueryableClubAttendance>,
+        fetch_level: Option<FetchLevel>,
+        descendant_fetch_level: Option<FetchLevel>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let today = AcademicCalendar::get().date_of(Utc::now());
+
+        let rows = sqlx::query_as::<_, AttendanceRateTable>(
+            r#"
+            SELECT
+                club_members.student_id,
+                COUNT(club_sessions.id) AS sessions,
+                COUNT(club_attendance.status) FILTER (WHERE club_attendance.status = 'present') AS present,
+                COUNT(club_attendance.status) FILTER (WHERE club_attendance.status = 'late') AS late,
+                COUNT(club_attendance.status) FILTER (WHERE club_attendance.status = 'absent') AS absent,
+                COUNT(club_attendance.status) FILTER (WHERE club_attendance.status = 'excused') AS excused
+            FROM club_members
+            INNER JOIN clubs ON clubs.id = club_members.club_id AND clubs.year = club_members.year
+            -- sessions before the latest approval don't count, members without a recorded
+            -- approval count from the start of the year
+            LEFT JOIN LATERAL (
+                SELECT ((MAX(created_at) AT TIME ZONE 'UTC') + make_interval(secs => $4))::date AS approved_on
+                FROM club_member_history
+                WHERE club_member_id = club_members.id AND membership_status = 'approved'
+            ) approval ON true
+            LEFT JOIN club_sessions ON club_sessions.club_id = club_members.club_id AND club_sessions.date <= $3
+                AND (approval.approved_on IS NULL OR club_sessions.date >= approval.approved_on)
+                AND (club_members.semester IS NULL OR club_sessions.semester = club_members.semester)
+            LEFT JOIN club_attendance ON club_attendance.session_id = club_sessions.id AND club_attendance.student_id = club_members.student_id
+            WHERE club_members.club_id = $1 AND club_members.membership_status = 'approved'
+                AND ($2::bigint IS NULL OR club_members.student_id = $2)
+            GROUP BY club_members.student_id
+            ORDER BY club_members.student_id
+            "#,
+        )
+        .bind(club_id)
+        .bind(filter.and_then(|filter| filter.student_id))
+        .bind(today)
+        .bind(AcademicCalendar::get().utc_offset.local_minus_utc() as f64)
+        .fetch_all(pool)
+        .await?;
+
+        let mut rates = vec![];
+
+        for row in rows {
+            let attended = row.present + row.late;
+
+            rates.push(Self {
+                student: Student::get_by_id(
+                    pool,
+                    row.student_id as u32,
+                    fetch_level.clone(),
+                    descendant_fetch_level.clone(),
+                )
+                .await?,
+                sessions: row.sessions,
+                present: row.present,
+                late: row.late,
+                absent: row.absent,
+                excused: row.excused,
+                unmarked: row.sessions - row.present - row.late - row.absent - row.excused,
+                rate: Self::calculate(row.sessions, attended, row.excused),
+            });
+        }
+
+        Ok(rates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::registration::DEFAULT_PASS_ATTENDANCE_RATE;
+
+    #[test]
+    fn no_sessions_have_no_rate() {
+        assert_eq!(AttendanceRate::calculate(0, 0, 0), None);
+    }
+
+    #[test]
+    fn all_sessions_excused_have_no_rate() {
+        assert_eq!(AttendanceRate::calculate(4, 0, 4), None);
+    }
+
+    #[test]
+    fn excused_sessions_are_left_out() {
+        // 3 of the 4 sessions that count, the fifth was excused
+        assert_eq!(AttendanceRate::calculate(5, 3, 1), Some(0.75));
+    }
+
+    #[test]
+    fn late_counts_as_attended() {
+        let (present, late) = (6, 2);
+
+        assert_eq!(AttendanceRate::calculate(8, present + late, 0), Some(1.0));
+    }
+
+    #[test]
+    fn rate_at_the_threshold_passes() {
+        let rate = AttendanceRate::calculate(10, 8, 0).unwrap();
+
+        assert!(rate >= DEFAULT_PASS_ATTENDANCE_RATE);
+    }
+}
@@ -0,0 +1,257 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+#[derive(Debug)]
+pub enum ClubStaffError {
+    Database(sqlx::Error),
+    AlreadyStaff,
+    NotStaff,
+    LastStaff,
+    IsPresident,
+    AlreadyAdvisor,
+    NotAdvisor,
+}
+
+impl From<sqlx::Error> for ClubStaffError {
+    fn from(e: sqlx::Error) -> Self {
+        ClubStaffError::Database(e)
+    }
+}
+
+impl std::fmt::Display for ClubStaffError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ClubStaffError::Database(e) => write!(f, "{}", e),
+            ClubStaffError::AlreadyStaff => write!(f, "the student is already club staff"),
+            ClubStaffError::NotStaff => write!(f, "the student is not club staff"),
+            ClubStaffError::LastStaff => write!(f, "a club must keep at least one staff"),
+            ClubStaffError::IsPresident => write!(
+                f,
+                "the president can not be removed, transfer the president role first"
+            ),
+            ClubStaffError::AlreadyAdvisor => write!(f, "the teacher is already club advisor"),
+            ClubStaffError::NotAdvisor => write!(f, "the teacher is not club advisor"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatableClubStaff {
+    pub student_id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatableClubAdvisor {
+    pub teacher_id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdatableClubPresident {
+    pub student_id: i64,
+}
+
+pub struct ClubStaff;
+
+impl ClubStaff {
+    // lock every staff row of the club so that concurrent removals can not both pass the
+    // last staff check, returns (student_id, is_president) pairs
+    async fn lock_staffs(
+        transaction: &mut Transaction<'_, Postgres>,
+        club_id: Uuid,
+        year: i64,
+    ) -> Result<Vec<(i64, bool)>, sqlx::Error> {
+        sqlx::query_as::<_, (i64, bool)>(
+            r#"
+            SELECT student_id, is_president FROM club_staffs
+            WHERE club_id = $1 AND year = $2
+            FOR UPDATE
+            "#,
+        )
+        .bind(club_id)
+        .bind(year)
+        .fetch_all(&mut *transaction)
+        .await
+    }
+
+    pub async fn is_president(
+        pool: &sqlx::PgPool,
+        club_id: Uuid,
+        student_id: i64,
+        year: i64,
+    ) -> Result<bool, sqlx::Error> {
+        let res = sqlx::query_as::<_, (bool,)>(
+            r#"
+            SELECT is_president FROM club_staffs WHERE club_id = $1 AND student_id = $2 AND year = $3
+            "#,
+        )
+        .bind(club_id)
+        .bind(student_id)
+        .bind(year)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(matches!(res, Some((true,))))
+    }
+
+    pub async fn add(
+        pool: &sqlx::PgPool,
+        club_id: Uuid,
+        student_id: i64,
+        year: i64,
+    ) -> Result<(), ClubStaffError> {
+        let mut transaction = pool.begin().await?;
+
+        let staffs = Self::lock_staffs(&mut transaction, club_id, year).await?;
+
+        if staffs.iter().any(|(staff_id, _)| *staff_id == student_id) {
+            return Err(ClubStaffError::AlreadyStaff);
+        }
+
+        // the first staff of a club becomes its president
+        sqlx::query(
+            r#"
+            INSERT INTO club_staffs (club_id, student_id, year, is_president) VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(club_id)
+        .bind(student_id)
+        .bind(year)
+        .bind(staffs.is_empty())
+        .execute(&mut transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn remove(
+        pool: &sqlx::PgPool,
+        club_id: Uuid,
+        student_id: i64,
+        year: i64,
+    ) -> Result<(), ClubStaffError> {
+        let mut transaction = pool.begin().await?;
+
+        let staffs = Self::lock_staffs(&mut transaction, club_id, year).await?;
+
+        match staffs.iter().find(|(staff_id, _)| *staff_id == student_id) {
+            None => return Err(ClubStaffError::NotStaff),
+            Some(_) if staffs.len() == 1 => return Err(ClubStaffError::LastStaff),
+            Some((_, true)) => return Err(ClubStaffError::IsPresident),
+            Some((_, false)) => (),
+        }
+
+        sqlx::query(
+            r#"
+            DELETE FROM club_staffs WHERE club_id = $1 AND student_id = $2 AND year = $3
+            "#,
+        )
+        .bind(club_id)
+        .bind(student_id)
+        .bind(year)
+        .execute(&mut transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn transfer_president(
+        pool: &sqlx::PgPool,
+        club_id: Uuid,
+        student_id: i64,
+        year: i64,
+    ) -> Result<(), ClubStaffError> {
+        let mut transaction = pool.begin().await?;
+
+        let staffs = Self::lock_staffs(&mut transaction, club_id, year).await?;
+
+        if !staffs.iter().any(|(staff_id, _)| *staff_id == student_id) {
+            return Err(ClubStaffError::NotStaff);
+        }
+
+        // clear the old president first, the unique index allows only one per club and year
+        sqlx::query(
+            r#"
+            UPDATE club_staffs SET is_president = false
+            WHERE club_id = $1 AND year = $2 AND is_president
+            "#,
+        )
+        .bind(club_id)
+        .bind(year)
+        .execute(&mut transaction)
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE club_staffs SET is_president = true
+            WHERE club_id = $1 AND student_id = $2 AND year = $3
+            "#,
+        )
+        .bind(club_id)
+        .bind(student_id)
+        .bind(year)
+        .execute(&mut transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+}
+
+pub struct ClubAdvisor;
+
+impl ClubAdvisor {
+    pub async fn add(
+        pool: &sqlx::PgPool,
+        club_id: Uuid,
+        teacher_id: i64,
+        year: i64,
+    ) -> Result<(), ClubStaffError> {
+        let res = sqlx::query(
+            r#"
+            INSERT INTO club_advisors (club_id, teacher_id, year) VALUES ($1, $2, $3)
+            ON CONFLICT (club_id, teacher_id, year) DO NOTHING
+            "#,
+        )
+        .bind(club_id)
+        .bind(teacher_id)
+        .bind(year)
+        .execute(pool)
+        .await?;
+
+        if res.rows_affected() == 0 {
+            return Err(ClubStaffError::AlreadyAdvisor);
+        }
+
+        Ok(())
+    }
+
+    pub async fn remove(
+        pool: &sqlx::PgPool,
+        club_id: Uuid,
+        teacher_id: i64,
+        year: i64,
+    ) -> Result<(), ClubStaffError> {
+        let res = sqlx::query(
+            r#"
+            DELETE FROM club_advisors WHERE club_id = $1 AND teacher_id = $2 AND year = $3
+            "#,
+        )
+        .bind(club_id)
+        .bind(teacher_id)
+        .bind(year)
+        .execute(pool)
+        .await?;
+
+        if res.rows_affected() == 0 {
+            return Err(ClubStaffError::NotAdvisor);
+        }
+
+        Ok(())
+    }
+}
@@ -1,17 +1,21 @@
+use std::collections::HashSet;
 use std::vec;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{Decode, Encode, FromRow, Postgres, Type};
+use sqlx::{Decode, Encode, FromRow, Postgres, Transaction, Type};
 use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::{structs::common::PaginationConfig, utils::date::get_current_academic_year};
 
 use super::{
+    auth::{User, UserRoles},
+    club_request::ClubRequestTable,
     common::{FetchLevel, FlexibleMultiLangString, MultiLangString, RequestType},
     contacts::Contact,
     student::Student,
+    teacher::Teacher,
 };
 
 #[derive(Debug, Clone, Copy, ToSchema)]
@@ -93,6 +97,8 @@ pub enum SubmissionStatus {
     Pending,
     Approved,
     Declined,
+    Waitlisted,
+    Withdrawn,
 }
 
 impl SubmissionStatus {
@@ -101,6 +107,8 @@ impl SubmissionStatus {
             SubmissionStatus::Pending => "pending".to_string(),
             SubmissionStatus::Approved => "approved".to_string(),
             SubmissionStatus::Declined => "declined".to_string(),
+            SubmissionStatus::Waitlisted => "waitlisted".to_string(),
+            SubmissionStatus::Withdrawn => "withdrawn".to_string(),
         }
     }
 
@@ -109,6 +117,8 @@ impl SubmissionStatus {
             "pending" => Some(SubmissionStatus::Pending),
             "approved" => Some(SubmissionStatus::Approved),
             "declined" => Some(SubmissionStatus::Declined),
+            "waitlisted" => Some(SubmissionStatus::Waitlisted),
+            "withdrawn" => Some(SubmissionStatus::Withdrawn),
             _ => None,
         }
     }
@@ -173,6 +183,12 @@ pub struct QueryableClub {
     pub map_location: Option<i64>,
     pub staffs: Option<Vec<i64>>,
     pub members: Option<Vec<i64>>,
+    // only archived clubs when true, only active clubs otherwise
+    pub archived: Option<bool>,
+    // academic year the clubs run in, the current one when left out
+    pub year: Option<i64>,
+    // clubs running in the semester, whole year clubs included
+    pub semester: Option<i64>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -187,6 +203,7 @@ pub struct UpdatableClubTable {
     pub accent_color: Option<String>,
     pub house: Option<ActivityDayHouse>,
     pub map_location: Option<i64>,
+    pub capacity: Option<i64>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -199,6 +216,52 @@ pub struct UpdatableClub {
     pub accent_color: Option<String>,
     pub house: Option<ActivityDayHouse>,
     pub map_location: Option<i64>,
+    // maximum number of approved members per academic year
+    pub capacity: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreatableClub {
+    pub name: MultiLangString,
+    pub description: Option<FlexibleMultiLangString>,
+    pub main_room: Option<String>,
+    pub logo_url: Option<String>,
+    pub background_color: Option<String>,
+    pub accent_color: Option<String>,
+    pub house: Option<ActivityDayHouse>,
+    pub map_location: Option<i64>,
+    pub capacity: Option<u32>,
+    // academic year the club runs in, the current one when left out
+    pub year: Option<i64>,
+    // 1 or 2 for clubs that only run for one semester, the whole year when left out
+    pub semester: Option<i64>,
+    // student ids of the initial staff for the current academic year, the first one becomes president
+    #[serde(default)]
+    pub staffs: Vec<i64>,
+}
+
+#[derive(Debug)]
+pub enum ClubError {
+    Database(sqlx::Error),
+    HasMembers,
+}
+
+impl From<sqlx::Error> for ClubError {
+    fn from(e: sqlx::Error) -> Self {
+        ClubError::Database(e)
+    }
+}
+
+impl std::fmt::Display for ClubError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ClubError::Database(e) => write!(f, "{}", e),
+            ClubError::HasMembers => write!(
+                f,
+                "the club has membership history and can only be archived"
+            ),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -216,6 +279,7 @@ pub enum ClubSortableField {
     AccentColor,
     House,
     MapLocation,
+    Capacity,
 }
 
 #[derive(FromRow, Clone)]
@@ -232,6 +296,10 @@ struct ClubTable {
     pub accent_color: Option<String>,
     pub house: Option<ActivityDayHouse>,
     pub map_location: Option<i64>,
+    pub capacity: Option<i64>,
+    pub year: i64,
+    pub semester: Option<i64>,
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
 impl ClubTable {
@@ -239,7 +307,7 @@ impl ClubTable {
         let res = sqlx::query_as!(
             Self,
             r#"
-            SELECT clubs.id, clubs.created_at, name_th, name_en, description_th, description_en, main_room, logo_url, background_color, accent_color, house as "house: _", map_location
+            SELECT clubs.id, clubs.created_at, name_th, name_en, description_th, description_en, main_room, logo_url, background_color, accent_color, house as "house: _", map_location, capacity, clubs.year, clubs.semester, archived_at
             FROM clubs INNER JOIN organizations ON clubs.organization_id = organizations.id
             WHERE clubs.id = $1
             "#,
@@ -261,7 +329,7 @@ impl ClubTable {
         let request = request_params;
 
         let query_clause = r#"
-            SELECT clubs.id, clubs.created_at, name_th, name_en, description_th, description_en, main_room, logo_url, background_color, accent_color, house, map_location
+            SELECT clubs.id, clubs.created_at, name_th, name_en, description_th, description_en, main_room, logo_url, background_color, accent_color, house, map_location, capacity, clubs.year, clubs.semester, archived_at
             FROM clubs INNER JOIN organizations ON clubs.organization_id = organizations.id
             "#;
 
@@ -274,16 +342,26 @@ impl ClubTable {
 
         if let Some(filter) = &request.filter {
             if let Some(q) = &filter.q {
-                query.push_str(&format!("WHERE name_th ILIKE ${query_counts} OR name_en ILIKE ${query_counts} OR description_th ILIKE ${query_counts} OR description_en ILIKE ${query_counts} OR main_room ILIKE ${query_counts}"));
+                if query.contains("WHERE") {
+                    query.push_str(&format!(" AND (name_th ILIKE ${query_counts} OR name_en ILIKE ${query_counts} OR description_th ILIKE ${query_counts} OR description_en ILIKE ${query_counts} OR main_room ILIKE ${query_counts})"));
+                } else {
+                    query.push_str(&format!("WHERE (name_th ILIKE ${query_counts} OR name_en ILIKE ${query_counts} OR description_th ILIKE ${query_counts} OR description_en ILIKE ${query_counts} OR main_room ILIKE ${query_counts})"));
+                }
                 string_params.push(format!("%{}%", q));
                 query_counts += 1;
             }
 
             if let Some(data) = &filter.data {
                 if let Some(name) = &data.name {
-                    query.push_str(&format!(
-                        "WHERE (name_th ILIKE ${query_counts} OR name_en ILIKE ${query_counts})"
-                    ));
+                    if query.contains("WHERE") {
+                        query.push_str(&format!(
+                            " AND (name_th ILIKE ${query_counts} OR name_en ILIKE ${query_counts})"
+                        ));
+                    } else {
+                        query.push_str(&format!(
+                            "WHERE (name_th ILIKE ${query_counts} OR name_en ILIKE ${query_counts})"
+                        ));
+                    }
                     string_params.push(format!("%{}%", name));
                     query_counts += 1;
                 }
@@ -414,6 +492,50 @@ impl ClubTable {
             }
         }
 
+        // archived clubs are left out unless they are asked for explicitly
+        let archived = request
+            .filter
+            .as_ref()
+            .and_then(|filter| filter.data.as_ref())
+            .and_then(|data| data.archived)
+            .unwrap_or(false);
+
+        let archived_clause = match archived {
+            true => "clubs.archived_at IS NOT NULL",
+            false => "clubs.archived_at IS NULL",
+        };
+
+        if query.contains("WHERE") {
+            query.push_str(&format!(" AND {archived_clause}"));
+        } else {
+            query.push_str(&format!("WHERE {archived_clause}"));
+        }
+
+        let year = request
+            .filter
+            .as_ref()
+            .and_then(|filter| filter.data.as_ref())
+            .and_then(|data| data.year)
+            .unwrap_or(get_current_academic_year() as i64);
+
+        query.push_str(&format!(" AND clubs.year = ${query_counts}"));
+        int_params.push(year);
+        query_counts += 1;
+
+        let semester = request
+            .filter
+            .as_ref()
+            .and_then(|filter| filter.data.as_ref())
+            .and_then(|data| data.semester);
+
+        if let Some(semester) = semester {
+            query.push_str(&format!(
+                " AND (clubs.semester IS NULL OR clubs.semester = ${query_counts})"
+            ));
+            int_params.push(semester);
+            query_counts += 1;
+        }
+
         // if sort is not empty, add ORDER BY clause and check the sort fields are valid
         if let Some(sort) = &request.sorting {
             let sort_vec = match sort.by.clone() {
@@ -444,6 +566,7 @@ impl ClubTable {
                         ClubSortableField::AccentColor => query.push_str(" accent_color"),
                         ClubSortableField::House => query.push_str(" house"),
                         ClubSortableField::MapLocation => query.push_str(" map_location"),
+                        ClubSortableField::Capacity => query.push_str(" capacity"),
                     }
 
                     first = false;
@@ -524,6 +647,12 @@ impl ClubTable {
             int_params.push(map_location);
         }
 
+        if let Some(capacity) = &club.capacity {
+            update_query.push_str(&format!("capacity = ${}, ", query_counts));
+            query_counts += 1;
+            int_params.push(capacity);
+        }
+
         update_query.pop();
         update_query.pop();
 
@@ -755,6 +884,60 @@ impl ClubTable {
         .await?)
     }
 
+    async fn get_advisors(
+        pool: &sqlx::PgPool,
+        id: Uuid,
+        year: Option<u32>,
+        fetch_level: Option<FetchLevel>,
+        descendant_fetch_level: Option<FetchLevel>,
+    ) -> Result<Vec<Teacher>, sqlx::Error> {
+        let year = match year {
+            Some(year) => year,
+            None => get_current_academic_year(),
+        } as i64;
+
+        let res = sqlx::query_as::<_, (i64,)>(
+            r#"
+            SELECT teacher_id FROM club_advisors WHERE club_id = $1 AND year = $2
+            "#,
+        )
+        .bind(id)
+        .bind(year)
+        .fetch_all(pool)
+        .await?;
+
+        Teacher::get_from_ids(
+            pool,
+            res.into_iter().map(|(teacher_id,)| teacher_id).collect(),
+            fetch_level,
+            descendant_fetch_level,
+        )
+        .await
+    }
+
+    async fn get_president_id(
+        pool: &sqlx::PgPool,
+        id: Uuid,
+        year: Option<u32>,
+    ) -> Result<Option<u32>, sqlx::Error> {
+        let year = match year {
+            Some(year) => year,
+            None => get_current_academic_year(),
+        } as i64;
+
+        let res = sqlx::query_as::<_, (i64,)>(
+            r#"
+            SELECT student_id FROM club_staffs WHERE club_id = $1 AND year = $2 AND is_president
+            "#,
+        )
+        .bind(id)
+        .bind(year)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(res.map(|(student_id,)| student_id as u32))
+    }
+
     pub async fn get_contacts(
         pool: &sqlx::PgPool,
         id: Uuid,
@@ -870,29 +1053,73 @@ pub struct DefaultClub {
     pub description: Option<MultiLangString>,
     pub logo_url: Option<String>,
     pub staffs: Vec<Student>,
+    pub president_id: Option<u32>,
     pub members: Vec<Student>,
-    // pub advisors: Vec<Teacher>,
+    pub advisors: Vec<Teacher>,
     pub background_color: Option<String>,
     pub accent_color: Option<String>,
     pub contacts: Vec<Contact>,
     pub main_room: Option<String>,
     pub house: Option<ActivityDayHouse>,
     pub map_location: Option<u32>,
+    pub capacity: Option<u32>,
+    pub remaining_seats: Option<u32>,
+    pub year: u32,
+    pub semester: Option<u32>,
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
 impl DefaultClub {
+    // seats left for a request of the club's own semester, counted like a join or approval
+    async fn remaining_seats(
+        pool: &sqlx::PgPool,
+        club: &ClubTable,
+    ) -> Result<Option<u32>, sqlx::Error> {
+        let capacity = match club.capacity {
+            Some(capacity) => capacity,
+            None => return Ok(None),
+        };
+
+        let (approved, _) =
+            ClubRequestTable::count_taken_seats(pool, club.id, club.year, club.semester, None)
+                .await?;
+
+        Ok(Some((capacity - approved).max(0) as u32))
+    }
+
     async fn from_table(
         pool: &sqlx::PgPool,
         club: ClubTable,
         descendant_fetch_level: Option<FetchLevel>,
     ) -> Result<Self, sqlx::Error> {
-        let members =
-            ClubTable::get_members(pool, club.id, None, descendant_fetch_level.clone(), None)
-                .await?;
-        let staffs =
-            ClubTable::get_staffs(pool, club.id, None, descendant_fetch_level.clone(), None)
-                .await?;
+        let members = ClubTable::get_members(
+            pool,
+            club.id,
+            Some(club.year as u32),
+            descendant_fetch_level.clone(),
+            None,
+        )
+        .await?;
+        let staffs = ClubTable::get_staffs(
+            pool,
+            club.id,
+            Some(club.year as u32),
+            descendant_fetch_level.clone(),
+            None,
+        )
+        .await?;
+        let president_id =
+            ClubTable::get_president_id(pool, club.id, Some(club.year as u32)).await?;
+        let advisors = ClubTable::get_advisors(
+            pool,
+            club.id,
+            Some(club.year as u32),
+            descendant_fetch_level.clone(),
+            None,
+        )
+        .await?;
         let contacts = ClubTable::get_contacts(pool, club.id, descendant_fetch_level).await?;
+        let remaining_seats = Self::remaining_seats(pool, &club).await?;
 
         Ok(Self {
             id: club.id,
@@ -906,14 +1133,20 @@ impl DefaultClub {
             },
             logo_url: club.logo_url,
             staffs,
+            president_id,
             members,
-            // advisors: vec![],
+            advisors,
             background_color: club.background_color,
             accent_color: club.accent_color,
             contacts,
             main_room: club.main_room,
             house: club.house,
             map_location: club.map_location.map(|l| l as u32),
+            capacity: club.capacity.map(|c| c as u32),
+            remaining_seats,
+            year: club.year as u32,
+            semester: club.semester.map(|s| s as u32),
+            archived_at: club.archived_at,
         })
     }
 
@@ -924,11 +1157,33 @@ impl DefaultClub {
     ) -> Result<DefaultClub, sqlx::Error> {
         let res = ClubTable::get_by_id(pool, id).await?;
 
-        let members =
-            ClubTable::get_members(pool, id, None, descendant_fetch_level.clone(), None).await?;
-        let staffs =
-            ClubTable::get_staffs(pool, id, None, descendant_fetch_level.clone(), None).await?;
+        let members = ClubTable::get_members(
+            pool,
+            id,
+            Some(res.year as u32),
+            descendant_fetch_level.clone(),
+            None,
+        )
+        .await?;
+        let staffs = ClubTable::get_staffs(
+            pool,
+            id,
+            Some(res.year as u32),
+            descendant_fetch_level.clone(),
+            None,
+        )
+        .await?;
+        let president_id = ClubTable::get_president_id(pool, id, Some(res.year as u32)).await?;
+        let advisors = ClubTable::get_advisors(
+            pool,
+            id,
+            Some(res.year as u32),
+            descendant_fetch_level.clone(),
+            None,
+        )
+        .await?;
         let contacts = ClubTable::get_contacts(pool, id, descendant_fetch_level).await?;
+        let remaining_seats = Self::remaining_seats(pool, &res).await?;
 
         Ok(DefaultClub {
             id: res.id,
@@ -942,13 +1197,20 @@ impl DefaultClub {
             },
             logo_url: res.logo_url,
             staffs,
+            president_id,
             members,
+            advisors,
             background_color: res.background_color,
             accent_color: res.accent_color,
             contacts,
             main_room: res.main_room,
             house: res.house,
             map_location: res.map_location.map(|l| l as u32),
+            capacity: res.capacity.map(|c| c as u32),
+            remaining_seats,
+            year: res.year as u32,
+            semester: res.semester.map(|s| s as u32),
+            archived_at: res.archived_at,
         })
     }
 
@@ -964,14 +1226,34 @@ impl DefaultClub {
         let mut clubs = Vec::new();
 
         for r in res.iter() {
-            let members =
-                ClubTable::get_members(pool, r.id, None, descendant_fetch_level.clone(), None)
-                    .await?;
-            let staffs =
-                ClubTable::get_staffs(pool, r.id, None, descendant_fetch_level.clone(), None)
-                    .await?;
+            let members = ClubTable::get_members(
+                pool,
+                r.id,
+                Some(r.year as u32),
+                descendant_fetch_level.clone(),
+                None,
+            )
+            .await?;
+            let staffs = ClubTable::get_staffs(
+                pool,
+                r.id,
+                Some(r.year as u32),
+                descendant_fetch_level.clone(),
+                None,
+            )
+            .await?;
+            let president_id = ClubTable::get_president_id(pool, r.id, Some(r.year as u32)).await?;
+            let advisors = ClubTable::get_advisors(
+                pool,
+                r.id,
+                Some(r.year as u32),
+                descendant_fetch_level.clone(),
+                None,
+            )
+            .await?;
             let contacts =
                 ClubTable::get_contacts(pool, r.id, descendant_fetch_level.clone()).await?;
+            let remaining_seats = Self::remaining_seats(pool, r).await?;
 
             clubs.push(DefaultClub {
                 id: r.id,
@@ -985,13 +1267,20 @@ impl DefaultClub {
                 },
                 logo_url: r.logo_url.clone(),
                 staffs,
+                president_id,
                 members,
+                advisors,
                 background_color: r.background_color.clone(),
                 accent_color: r.accent_color.clone(),
                 contacts,
                 main_room: r.main_room.clone(),
                 house: r.house.clone(),
                 map_location: r.map_location.map(|l| l as u32),
+                capacity: r.capacity.map(|c| c as u32),
+                remaining_seats,
+                year: r.year as u32,
+                semester: r.semester.map(|s| s as u32),
+                archived_at: r.archived_at,
             });
         }
 
@@ -1048,6 +1337,285 @@ impl Club {
         }
     }
 
+    // staff actions on a club are open to its student staff and its teacher advisors
+    pub async fn is_manager(
+        pool: &sqlx::PgPool,
+        id: Uuid,
+        user: &User,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(Self::get_managed_club_ids(pool, user, &[id])
+            .await?
+            .contains(&id))
+    }
+
+    // the subset of club_ids the user is staff or advisor of, only staff and advisor rows of
+    // each club's own year count so last year's staff don't manage this year's club
+    pub async fn get_managed_club_ids<'e, E>(
+        executor: E,
+        user: &User,
+        club_ids: &[Uuid],
+    ) -> Result<HashSet<Uuid>, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Postgres>,
+    {
+        let (student_id, teacher_id) = match user.role {
+            UserRoles::Student => (user.student.map(|id| id as i64), None),
+            UserRoles::Teacher => (None, user.teacher.map(|id| id as i64)),
+        };
+
+        let res = sqlx::query_as::<_, (Uuid,)>(
+            r#"
+            SELECT club_id FROM club_staffs INNER JOIN clubs ON club_staffs.club_id = clubs.id
+            WHERE student_id = $1 AND club_id = ANY($3) AND club_staffs.year = clubs.year
+            UNION
+            SELECT club_id FROM club_advisors INNER JOIN clubs ON club_advisors.club_id = clubs.id
+            WHERE teacher_id = $2 AND club_id = ANY($3) AND club_advisors.year = clubs.year
+            "#,
+        )
+        .bind(student_id)
+        .bind(teacher_id)
+        .bind(club_ids)
+        .fetch_all(executor)
+        .await?;
+
+        Ok(res.into_iter().map(|(club_id,)| club_id).collect())
+    }
+
+    pub async fn is_advisor(
+        pool: &sqlx::PgPool,
+        id: Uuid,
+        teacher_id: i64,
+    ) -> Result<bool, sqlx::Error> {
+        let (count,) = sqlx::query_as::<_, (i64,)>(
+            r#"
+            SELECT COUNT(club_advisors.id) FROM club_advisors INNER JOIN clubs ON club_advisors.club_id = clubs.id
+            WHERE club_id = $1 AND teacher_id = $2 AND club_advisors.year = clubs.year
+            "#,
+        )
+        .bind(id)
+        .bind(teacher_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(count > 0)
+    }
+
+    pub async fn is_member(
+        pool: &sqlx::PgPool,
+        id: Uuid,
+        student_id: i64,
+    ) -> Result<bool, sqlx::Error> {
+        let (count,) = sqlx::query_as::<_, (i64,)>(
+            r#"
+            SELECT COUNT(id) FROM club_members WHERE club_id = $1 AND student_id = $2 AND membership_status = 'approved'
+            "#,
+        )
+        .bind(id)
+        .bind(student_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(count > 0)
+    }
+
+    // creates the organization and the club together, a teacher creating a club becomes
+    // its advisor for the current academic year
+    pub async fn create(
+        pool: &sqlx::PgPool,
+        club: &CreatableClub,
+        user: &User,
+        fetch_level: Option<FetchLevel>,
+        descendant_fetch_level: Option<FetchLevel>,
+    ) -> Result<Club, sqlx::Error> {
+        let year = club.year.unwrap_or(get_current_academic_year() as i64);
+
+        let mut transaction = pool.begin().await?;
+
+        let id = Self::insert(&mut transaction, club, year).await?;
+
+        if let (UserRoles::Teacher, Some(teacher_id)) = (&user.role, user.teacher) {
+            sqlx::query(
+                r#"
+                INSERT INTO club_advisors (club_id, teacher_id, year) VALUES ($1, $2, $3)
+                "#,
+            )
+            .bind(id)
+            .bind(teacher_id as i64)
+            .bind(year)
+            .execute(&mut transaction)
+            .await?;
+        }
+
+        transaction.commit().await?;
+
+        Self::get_by_id(pool, id, fetch_level, descendant_fetch_level).await
+    }
+
+    // the organization, the club and its initial staff, the first staff becomes president
+    pub async fn insert(
+        transaction: &mut Transaction<'_, Postgres>,
+        club: &CreatableClub,
+        year: i64,
+    ) -> Result<Uuid, sqlx::Error> {
+        let description = club.description.as_ref();
+
+        // the organization id is only needed to link the club, so both rows go in one statement
+        let (id,) = sqlx::query_as::<_, (Uuid,)>(
+            r#"
+            WITH organization AS (
+                INSERT INTO organizations (name_th, name_en, description_th, description_en, main_room, logo_url)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                RETURNING id
+            )
+            INSERT INTO clubs (organization_id, background_color, accent_color, house, map_location, capacity, year, semester)
+            SELECT id, $7, $8, $9, $10, $11, $12, $13 FROM organization
+            RETURNING id
+            "#,
+        )
+        .bind(&club.name.th)
+        .bind(&club.name.en)
+        .bind(description.and_then(|description| description.th.clone()))
+        .bind(description.and_then(|description| description.en.clone()))
+        .bind(&club.main_room)
+        .bind(&club.logo_url)
+        .bind(&club.background_color)
+        .bind(&club.accent_color)
+        .bind(club.house)
+        .bind(club.map_location)
+        .bind(club.capacity.map(|capacity| capacity as i64))
+        .bind(year)
+        .bind(club.semester)
+        .fetch_one(&mut *transaction)
+        .await?;
+
+        let mut seen = HashSet::new();
+        let staffs = club
+            .staffs
+            .iter()
+            .filter(|student_id| seen.insert(**student_id));
+
+        for (i, student_id) in staffs.enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO club_staffs (club_id, student_id, year, is_president) VALUES ($1, $2, $3, $4)
+                "#,
+            )
+            .bind(id)
+            .bind(student_id)
+            .bind(year)
+            .bind(i == 0)
+            .execute(&mut *transaction)
+            .await?;
+        }
+
+        Ok(id)
+    }
+
+    pub async fn get_year(pool: &sqlx::PgPool, id: Uuid) -> Result<i64, sqlx::Error> {
+        let (year,) = sqlx::query_as::<_, (i64,)>(
+            r#"
+            SELECT year FROM clubs WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(year)
+    }
+
+    pub async fn is_archived(pool: &sqlx::PgPool, id: Uuid) -> Result<bool, sqlx::Error> {
+        let (archived,) = sqlx::query_as::<_, (bool,)>(
+            r#"
+            SELECT archived_at IS NOT NULL FROM clubs WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(archived)
+    }
+
+    pub async fn set_archived(
+        pool: &sqlx::PgPool,
+        id: Uuid,
+        archived: bool,
+    ) -> Result<(), sqlx::Error> {
+        let res = sqlx::query(
+            r#"
+            UPDATE clubs
+            SET archived_at = CASE WHEN $2 THEN COALESCE(archived_at, now()) ELSE NULL END
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(archived)
+        .execute(pool)
+        .await?;
+
+        if res.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        Ok(())
+    }
+
+    // only clubs nobody ever requested to join can be removed for good, everything else keeps
+    // its history and has to be archived instead
+    pub async fn delete_by_id(pool: &sqlx::PgPool, id: Uuid) -> Result<(), ClubError> {
+        let mut transaction = pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            SELECT id FROM clubs WHERE id = $1 FOR UPDATE
+            "#,
+        )
+        .bind(id)
+        .fetch_one(&mut transaction)
+        .await?;
+
+        let (has_members,) = sqlx::query_as::<_, (bool,)>(
+            r#"
+            SELECT EXISTS (SELECT 1 FROM club_members WHERE club_id = $1)
+            "#,
+        )
+        .bind(id)
+        .fetch_one(&mut transaction)
+        .await?;
+
+        if has_members {
+            return Err(ClubError::HasMembers);
+        }
+
+        for query in [
+            "DELETE FROM club_staffs WHERE club_id = $1",
+            "DELETE FROM club_advisors WHERE club_id = $1",
+            "DELETE FROM club_contacts WHERE club_id = $1",
+        ] {
+            sqlx::query(query)
+                .bind(id)
+                .execute(&mut transaction)
+                .await?;
+        }
+
+        sqlx::query(
+            r#"
+            WITH club AS (
+                DELETE FROM clubs WHERE id = $1 RETURNING organization_id
+            )
+            DELETE FROM organizations WHERE id IN (SELECT organization_id FROM club)
+            "#,
+        )
+        .bind(id)
+        .execute(&mut transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
     pub async fn update_by_id(
         pool: &sqlx::PgPool,
         id: Uuid,
@@ -1089,6 +1657,7 @@ impl Club {
             main_room: update.main_room.clone(),
             house: update.house.clone(),
             map_location: update.map_location,
+            capacity: update.capacity.map(|c| c as i64),
         };
 
         let res = ClubTable::update_by_id(pool, id, &update).await?;
@@ -33,6 +33,13 @@ impl MultiLangString {
     }
 }
 
+// the languages of a MultiLangString, for text the api writes itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Th,
+    En,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum FetchLevel {
@@ -87,6 +94,7 @@ impl<T> ErrorType<T> {
             403 => StatusCode::FORBIDDEN,
             404 => StatusCode::NOT_FOUND,
             405 => StatusCode::METHOD_NOT_ALLOWED,
+            409 => StatusCode::CONFLICT,
             500 => StatusCode::INTERNAL_SERVER_ERROR,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         }
@@ -131,15 +139,30 @@ impl std::fmt::Display for PaginationType {
 #[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct MetadataType {
     timestamp: DateTime<Utc>,
-    pagination: PaginationType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pagination: Option<PaginationType>,
+    // machine readable context of an error, such as the seats left in a full club
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    details: Option<serde_json::Value>,
+}
+
+impl MetadataType {
+    pub fn with_details(details: serde_json::Value) -> Self {
+        MetadataType {
+            timestamp: Utc::now(),
+            pagination: None,
+            details: Some(details),
+        }
+    }
 }
 
 impl std::fmt::Display for MetadataType {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(
             f,
-            "{{ timestamp: {}, pagination: {} }}",
-            self.timestamp, self.pagination
+            "{{ timestamp: {}, pagination: {:?}, details: {:?} }}",
+            self.timestamp, self.pagination, self.details
         )
     }
 }
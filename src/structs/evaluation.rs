@@ -0,0 +1,406 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{types::Json, FromRow, PgConnection, Postgres, Transaction};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::utils::date::AcademicCalendar;
+
+use super::{
+    classroom::{Classroom, ClassroomRoster},
+    club_session::AttendanceRate,
+    common::FetchLevel,
+    registration::RegistrationSettings,
+    student::Student,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EvaluationResult {
+    Pass,
+    Fail,
+    // the student was not an approved member of any club that year
+    NoClub,
+}
+
+#[derive(Debug)]
+pub enum EvaluationError {
+    Database(sqlx::Error),
+    NotSignedOff,
+    Locked,
+    NotLocked,
+}
+
+impl From<sqlx::Error> for EvaluationError {
+    fn from(e: sqlx::Error) -> Self {
+        EvaluationError::Database(e)
+    }
+}
+
+impl std::fmt::Display for EvaluationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            EvaluationError::Database(e) => write!(f, "{}", e),
+            EvaluationError::NotSignedOff => write!(
+                f,
+                "the report has to be signed off by a classroom advisor first"
+            ),
+            EvaluationError::Locked => write!(f, "the report is locked"),
+            EvaluationError::NotLocked => write!(f, "the report is not locked"),
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct ClubEvaluationTable {
+    student_id: i64,
+    club_id: Uuid,
+    semester: Option<i64>,
+    sessions: i64,
+    attended: i64,
+    excused: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ClubEvaluation {
+    #[schema(value_type = String)]
+    pub club_id: Uuid,
+    pub semester: Option<i64>,
+    pub sessions: i64,
+    // present or late
+    pub attended: i64,
+    pub excused: i64,
+    // see AttendanceRate::calculate
+    pub attendance_rate: Option<f64>,
+    pub passed: bool,
+}
+
+// a student as stored in a locked report, only ids so the report can be fetched at any level
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EvaluatedStudent {
+    student_id: i64,
+    class_number: Option<u32>,
+    clubs: Vec<ClubEvaluation>,
+    result: EvaluationResult,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EvaluationResults {
+    pass_attendance_rate: f64,
+    students: Vec<EvaluatedStudent>,
+}
+
+impl EvaluationResults {
+    // a student passes when they pass every club they were an approved member of
+    async fn evaluate(
+        connection: &mut PgConnection,
+        roster: &ClassroomRoster,
+    ) -> Result<Self, sqlx::Error> {
+        let settings = RegistrationSettings::get_by_year(&mut *connection, roster.year).await?;
+        let today = AcademicCalendar::get().date_of(Utc::now());
+        let student_ids = roster
+            .students
+            .iter()
+            .map(|(student_id, _)| *student_id)
+            .collect::<Vec<i64>>();
+
+        let rows = sqlx::query_as::<_, ClubEvaluationTable>(
+            r#"
+            SELECT
+                club_members.student_id,
+                club_members.club_id,
+                club_members.semester,
+                COUNT(club_sessions.id) AS sessions,
+                COUNT(club_attendance.status) FILTER (WHERE club_attendance.status IN ('present', 'late')) AS attended,
+                COUNT(club_attendance.status) FILTER (WHERE club_attendance.status = 'excused') AS excused
+            FROM club_members
+            -- sessions before the latest approval don't count, members without a recorded
+            -- approval count from the start of the year
+            LEFT JOIN LATERAL (
+                SELECT ((MAX(created_at) AT TIME ZONE 'UTC') + make_interval(secs => $4))::date AS approved_on
+                FROM club_member_history
+                WHERE club_member_id = club_members.id AND membership_status = 'approved'
+            ) approval ON true
+            LEFT JOIN club_sessions ON club_sessions.club_id = club_members.club_id AND club_sessions.date <= $3
+                AND (approval.approved_on IS NULL OR club_sessions.date >= approval.approved_on)
+                AND (club_members.semester IS NULL OR club_sessions.semester = club_members.semester)
+            LEFT JOIN club_attendance ON club_attendance.session_id = club_sessions.id AND club_attendance.student_id = club_members.student_id
+            WHERE club_members.student_id = ANY($1) AND club_members.year = $2 AND club_members.membership_status = 'approved'
+            GROUP BY club_members.student_id, club_members.club_id, club_members.semester
+            ORDER BY club_members.student_id, club_members.semester NULLS FIRST, club_members.club_id
+            "#,
+        )
+        .bind(&student_ids)
+        .bind(roster.year)
+        .bind(today)
+        .bind(AcademicCalendar::get().utc_offset.local_minus_utc() as f64)
+        .fetch_all(&mut *connection)
+        .await?;
+
+        let mut clubs: HashMap<i64, Vec<ClubEvaluation>> = HashMap::new();
+
+        for row in rows {
+            let attendance_rate =
+                AttendanceRate::calculate(row.sessions, row.attended, row.excused);
+
+            clubs
+                .entry(row.student_id)
+                .or_default()
+                .push(ClubEvaluation {
+                    club_id: row.club_id,
+                    semester: row.semester,
+                    sessions: row.sessions,
+                    attended: row.attended,
+                    excused: row.excused,
+                    attendance_rate,
+                    passed: attendance_rate
+                        .map(|rate| rate >= settings.pass_attendance_rate)
+                        .unwrap_or(true),
+                });
+        }
+
+        let students = roster
+            .students
+            .iter()
+            .map(|(student_id, class_number)| {
+                let clubs = clubs.remove(student_id).unwrap_or_default();
+                let result = match clubs.is_empty() {
+                    true => EvaluationResult::NoClub,
+                    false if clubs.iter().all(|club| club.passed) => EvaluationResult::Pass,
+                    false => EvaluationResult::Fail,
+                };
+
+                EvaluatedStudent {
+                    student_id: *student_id,
+                    class_number: *class_number,
+                    clubs,
+                    result,
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            pass_attendance_rate: settings.pass_attendance_rate,
+            students,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct EvaluationReportTable {
+    signed_off_by: Option<i64>,
+    signed_off_at: Option<DateTime<Utc>>,
+    locked_by: Option<Uuid>,
+    locked_at: Option<DateTime<Utc>>,
+    results: Option<Json<EvaluationResults>>,
+}
+
+impl EvaluationReportTable {
+    async fn get_by_classroom_id(
+        pool: &sqlx::PgPool,
+        classroom_id: u32,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT signed_off_by, signed_off_at, locked_by, locked_at, results
+            FROM evaluation_reports WHERE classroom_id = $1
+            "#,
+        )
+        .bind(classroom_id as i64)
+        .fetch_optional(pool)
+        .await
+    }
+
+    // creates the row on first use so that sign off and lock always have a row to lock
+    async fn lock_by_classroom_id(
+        transaction: &mut Transaction<'_, Postgres>,
+        classroom_id: u32,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO evaluation_reports (classroom_id) VALUES ($1)
+            ON CONFLICT (classroom_id) DO NOTHING
+            "#,
+        )
+        .bind(classroom_id as i64)
+        .execute(&mut *transaction)
+        .await?;
+
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT signed_off_by, signed_off_at, locked_by, locked_at, results
+            FROM evaluation_reports WHERE classroom_id = $1
+            FOR UPDATE
+            "#,
+        )
+        .bind(classroom_id as i64)
+        .fetch_one(&mut *transaction)
+        .await
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct StudentEvaluation {
+    pub class_number: Option<u32>,
+    pub student: Student,
+    pub clubs: Vec<ClubEvaluation>,
+    pub result: EvaluationResult,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EvaluationReport {
+    pub classroom: Classroom,
+    pub year: i64,
+    pub pass_attendance_rate: f64,
+    // teacher id of the classroom advisor
+    pub signed_off_by: Option<i64>,
+    pub signed_off_at: Option<DateTime<Utc>>,
+    pub locked_by: Option<Uuid>,
+    pub locked_at: Option<DateTime<Utc>>,
+    // in class number order
+    pub students: Vec<StudentEvaluation>,
+}
+
+impl EvaluationReport {
+    // signed off and locked reports show the results the advisor signed off, others are
+    // worked out from the attendance recorded so far
+    pub async fn get_by_roster(
+        pool: &sqlx::PgPool,
+        roster: &ClassroomRoster,
+        fetch_level: Option<FetchLevel>,
+        descendant_fetch_level: Option<FetchLevel>,
+    ) -> Result<Self, sqlx::Error> {
+        let table = EvaluationReportTable::get_by_classroom_id(pool, roster.id).await?;
+
+        let results = match table.as_ref().and_then(|table| table.results.as_ref()) {
+            Some(results) => results.0.clone(),
+            None => EvaluationResults::evaluate(&mut *pool.acquire().await?, roster).await?,
+        };
+
+        let mut students = vec![];
+
+        for student in results.students {
+            students.push(StudentEvaluation {
+                class_number: student.class_number,
+                student: Student::get_by_id(
+                    pool,
+                    student.student_id as u32,
+                    fetch_level.clone(),
+                    descendant_fetch_level.clone(),
+                )
+                .await?,
+                clubs: student.clubs,
+                result: student.result,
+            });
+        }
+
+        Ok(Self {
+            classroom: Classroom::get_by_id(
+                pool,
+                roster.id,
+                fetch_level.clone().unwrap_or(FetchLevel::Compact),
+                descendant_fetch_level.clone(),
+            )
+            .await?,
+            year: roster.year,
+            pass_attendance_rate: results.pass_attendance_rate,
+            signed_off_by: table.as_ref().and_then(|table| table.signed_off_by),
+            signed_off_at: table.as_ref().and_then(|table| table.signed_off_at),
+            locked_by: table.as_ref().and_then(|table| table.locked_by),
+            locked_at: table.as_ref().and_then(|table| table.locked_at),
+            students,
+        })
+    }
+
+    // the results are stored with the sign off, so the report locked later is the one the
+    // advisor saw, signing off again takes a fresh snapshot
+    pub async fn sign_off(
+        pool: &sqlx::PgPool,
+        roster: &ClassroomRoster,
+        teacher_id: i64,
+    ) -> Result<(), EvaluationError> {
+        let mut transaction = pool.begin().await?;
+
+        let table =
+            EvaluationReportTable::lock_by_classroom_id(&mut transaction, roster.id).await?;
+
+        if table.locked_at.is_some() {
+            return Err(EvaluationError::Locked);
+        }
+
+        let results = EvaluationResults::evaluate(&mut transaction, roster).await?;
+
+        sqlx::query(
+            r#"
+            UPDATE evaluation_reports SET signed_off_by = $2, signed_off_at = now(), results = $3
+            WHERE classroom_id = $1
+            "#,
+        )
+        .bind(roster.id as i64)
+        .bind(teacher_id)
+        .bind(Json(&results))
+        .execute(&mut transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn lock(
+        pool: &sqlx::PgPool,
+        roster: &ClassroomRoster,
+        actor: Uuid,
+    ) -> Result<(), EvaluationError> {
+        let mut transaction = pool.begin().await?;
+
+        let table =
+            EvaluationReportTable::lock_by_classroom_id(&mut transaction, roster.id).await?;
+
+        if table.locked_at.is_some() {
+            return Err(EvaluationError::Locked);
+        }
+
+        if table.signed_off_at.is_none() || table.results.is_none() {
+            return Err(EvaluationError::NotSignedOff);
+        }
+
+        // freezes the results stored at sign off
+        sqlx::query(
+            r#"
+            UPDATE evaluation_reports SET locked_by = $2, locked_at = now()
+            WHERE classroom_id = $1
+            "#,
+        )
+        .bind(roster.id as i64)
+        .bind(actor)
+        .execute(&mut transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    // the sign off goes too, so corrected results have to be signed off again
+    pub async fn unlock(pool: &sqlx::PgPool, classroom_id: u32) -> Result<(), EvaluationError> {
+        let res = sqlx::query(
+            r#"
+            UPDATE evaluation_reports
+            SET locked_by = NULL, locked_at = NULL, results = NULL, signed_off_by = NULL, signed_off_at = NULL
+            WHERE classroom_id = $1 AND locked_at IS NOT NULL
+            "#,
+        )
+        .bind(classroom_id as i64)
+        .execute(pool)
+        .await?;
+
+        if res.rows_affected() == 0 {
+            return Err(EvaluationError::NotLocked);
+        }
+
+        Ok(())
+    }
+}
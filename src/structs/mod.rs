@@ -1,8 +1,22 @@
+pub(crate) mod allocation;
 pub(crate) mod auth;
 pub(crate) mod classroom;
+pub(crate) mod club_announcement;
+pub(crate) mod club_document;
+pub(crate) mod club_event;
+pub(crate) mod club_import;
+pub(crate) mod club_member_history;
 pub(crate) mod club_request;
+pub(crate) mod club_session;
+pub(crate) mod club_staff;
 pub(crate) mod clubs;
 pub(crate) mod common;
 pub(crate) mod contacts;
+pub(crate) mod evaluation;
 pub(crate) mod health;
+pub(crate) mod registration;
+pub(crate) mod rollover;
+pub(crate) mod roster_export;
 pub(crate) mod student;
+pub(crate) mod teacher;
+pub(crate) mod unregistered_student;
@@ -0,0 +1,441 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::{FromRow, Postgres};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::common::MetadataType;
+
+// Activity Day policy is one club per student per academic year unless configured otherwise
+pub const DEFAULT_MAX_CLUBS_PER_STUDENT: i64 = 1;
+pub const DEFAULT_MAX_PREFERENCES: i64 = 3;
+pub const DEFAULT_PASS_ATTENDANCE_RATE: f64 = 0.8;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct RegistrationSettings {
+    pub year: i64,
+    pub max_clubs_per_student: i64,
+    // whether approved members can still leave their club once registration has closed
+    pub allow_leaving_after_close: bool,
+    // oversubscribed years collect ranked preferences and allocate seats by lottery
+    // instead of accepting individual join requests
+    pub lottery_enabled: bool,
+    pub max_preferences: i64,
+    // share of club sessions a student has to attend to pass the activity grade
+    pub pass_attendance_rate: f64,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryableRegistrationSettings {
+    pub year: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdatableRegistrationSettings {
+    pub max_clubs_per_student: Option<i64>,
+    pub allow_leaving_after_close: Option<bool>,
+    pub lottery_enabled: Option<bool>,
+    pub max_preferences: Option<i64>,
+    pub pass_attendance_rate: Option<f64>,
+}
+
+impl RegistrationSettings {
+    fn default_for_year(year: i64) -> Self {
+        Self {
+            year,
+            max_clubs_per_student: DEFAULT_MAX_CLUBS_PER_STUDENT,
+            allow_leaving_after_close: false,
+            lottery_enabled: false,
+            max_preferences: DEFAULT_MAX_PREFERENCES,
+            pass_attendance_rate: DEFAULT_PASS_ATTENDANCE_RATE,
+            created_at: None,
+        }
+    }
+
+    // years without a registration_settings row fall back to the defaults
+    pub async fn get_by_year<'e, E>(executor: E, year: i64) -> Result<Self, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Postgres>,
+    {
+        let res = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT year, max_clubs_per_student, allow_leaving_after_close, lottery_enabled, max_preferences, pass_attendance_rate, created_at
+            FROM registration_settings WHERE year = $1
+            "#,
+        )
+        .bind(year)
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(res.unwrap_or(Self::default_for_year(year)))
+    }
+
+    // only years with a row of their own are listed, every other year uses the defaults
+    pub async fn query(
+        pool: &sqlx::PgPool,
+        filter: Option<&QueryableRegistrationSettings>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT year, max_clubs_per_student, allow_leaving_after_close, lottery_enabled, max_preferences, pass_attendance_rate, created_at
+            FROM registration_settings
+            WHERE ($1::bigint IS NULL OR year = $1)
+            ORDER BY year
+            "#,
+        )
+        .bind(filter.and_then(|filter| filter.year))
+        .fetch_all(pool)
+        .await
+    }
+
+    // fields left out of the update keep their current value, or the default when the year
+    // has no row yet
+    pub async fn update_by_year(
+        pool: &sqlx::PgPool,
+        year: i64,
+        update: &UpdatableRegistrationSettings,
+    ) -> Result<Self, sqlx::Error> {
+        let defaults = Self::default_for_year(year);
+
+        sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO registration_settings (year, max_clubs_per_student, allow_leaving_after_close, lottery_enabled, max_preferences, pass_attendance_rate)
+            VALUES ($1, COALESCE($2, $7), COALESCE($3, $8), COALESCE($4, $9), COALESCE($5, $10), COALESCE($6, $11))
+            ON CONFLICT (year) DO UPDATE SET
+                max_clubs_per_student = COALESCE($2, registration_settings.max_clubs_per_student),
+                allow_leaving_after_close = COALESCE($3, registration_settings.allow_leaving_after_close),
+                lottery_enabled = COALESCE($4, registration_settings.lottery_enabled),
+                max_preferences = COALESCE($5, registration_settings.max_preferences),
+                pass_attendance_rate = COALESCE($6, registration_settings.pass_attendance_rate)
+            RETURNING year, max_clubs_per_student, allow_leaving_after_close, lottery_enabled, max_preferences, pass_attendance_rate, created_at
+            "#,
+        )
+        .bind(year)
+        .bind(update.max_clubs_per_student)
+        .bind(update.allow_leaving_after_close)
+        .bind(update.lottery_enabled)
+        .bind(update.max_preferences)
+        .bind(update.pass_attendance_rate)
+        .bind(defaults.max_clubs_per_student)
+        .bind(defaults.allow_leaving_after_close)
+        .bind(defaults.lottery_enabled)
+        .bind(defaults.max_preferences)
+        .bind(defaults.pass_attendance_rate)
+        .fetch_one(pool)
+        .await
+    }
+
+    // removing the row puts the year back on the defaults
+    pub async fn delete_by_year(pool: &sqlx::PgPool, year: i64) -> Result<(), sqlx::Error> {
+        let res = sqlx::query(
+            r#"
+            DELETE FROM registration_settings WHERE year = $1
+            "#,
+        )
+        .bind(year)
+        .execute(pool)
+        .await?;
+
+        if res.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryableRegistrationWindow {
+    pub year: Option<i64>,
+    pub grade: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatableRegistrationWindow {
+    pub year: Option<i64>,
+    // None means the window applies to every grade without a window of its own
+    pub grade: Option<i64>,
+    pub opens_at: DateTime<Utc>,
+    pub closes_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdatableRegistrationWindow {
+    pub grade: Option<i64>,
+    pub opens_at: Option<DateTime<Utc>>,
+    pub closes_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct RegistrationWindow {
+    #[schema(value_type = String)]
+    pub id: Uuid,
+    pub year: i64,
+    pub grade: Option<i64>,
+    pub opens_at: DateTime<Utc>,
+    pub closes_at: DateTime<Utc>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+pub enum RegistrationStatus {
+    Open,
+    Closed { next_opening: Option<DateTime<Utc>> },
+}
+
+impl RegistrationStatus {
+    pub fn to_string(&self) -> String {
+        match self {
+            RegistrationStatus::Open => "registration is open".to_string(),
+            RegistrationStatus::Closed {
+                next_opening: Some(_),
+            } => "registration is closed until the next window opens".to_string(),
+            RegistrationStatus::Closed { next_opening: None } => {
+                "registration is closed, no upcoming opening".to_string()
+            }
+        }
+    }
+
+    // error metadata for clients to show when registration opens again, next_opening is an
+    // rfc 3339 timestamp or null
+    pub fn to_metadata(&self) -> Option<MetadataType> {
+        match self {
+            RegistrationStatus::Open => None,
+            RegistrationStatus::Closed { next_opening } => {
+                Some(MetadataType::with_details(json!({
+                    "next_opening": next_opening.map(|next_opening| next_opening.to_rfc3339()),
+                })))
+            }
+        }
+    }
+}
+
+impl RegistrationWindow {
+    pub async fn get_by_id(pool: &sqlx::PgPool, id: Uuid) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT id, year, grade, opens_at, closes_at, created_at FROM registration_windows WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn query(
+        pool: &sqlx::PgPool,
+        filter: Option<&QueryableRegistrationWindow>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let year = filter.and_then(|filter| filter.year);
+        let grade = filter.and_then(|filter| filter.grade);
+
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT id, year, grade, opens_at, closes_at, created_at FROM registration_windows
+            WHERE ($1::bigint IS NULL OR year = $1) AND ($2::bigint IS NULL OR grade = $2)
+            ORDER BY year, opens_at
+            "#,
+        )
+        .bind(year)
+        .bind(grade)
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &sqlx::PgPool,
+        window: &CreatableRegistrationWindow,
+        year: i64,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO registration_windows (year, grade, opens_at, closes_at)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, year, grade, opens_at, closes_at, created_at
+            "#,
+        )
+        .bind(year)
+        .bind(window.grade)
+        .bind(window.opens_at)
+        .bind(window.closes_at)
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update_by_id(
+        pool: &sqlx::PgPool,
+        id: Uuid,
+        window: &UpdatableRegistrationWindow,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            UPDATE registration_windows
+            SET grade = COALESCE($2, grade), opens_at = COALESCE($3, opens_at), closes_at = COALESCE($4, closes_at)
+            WHERE id = $1
+            RETURNING id, year, grade, opens_at, closes_at, created_at
+            "#,
+        )
+        .bind(id)
+        .bind(window.grade)
+        .bind(window.opens_at)
+        .bind(window.closes_at)
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete_by_id(pool: &sqlx::PgPool, id: Uuid) -> Result<(), sqlx::Error> {
+        let res = sqlx::query(
+            r#"
+            DELETE FROM registration_windows WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        if res.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        Ok(())
+    }
+
+    // a grade with windows of its own ignores the windows meant for every grade
+    // years without any window at all are always open
+    pub async fn get_status(
+        pool: &sqlx::PgPool,
+        year: i64,
+        grade: Option<u32>,
+        now: DateTime<Utc>,
+    ) -> Result<RegistrationStatus, sqlx::Error> {
+        let grade = grade.map(|grade| grade as i64);
+
+        let windows = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT id, year, grade, opens_at, closes_at, created_at FROM registration_windows
+            WHERE year >= $1 AND (grade IS NULL OR grade = $2)
+            "#,
+        )
+        .bind(year)
+        .bind(grade)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(Self::status_from_windows(&windows, year, grade, now))
+    }
+
+    fn status_from_windows(
+        windows: &[Self],
+        year: i64,
+        grade: Option<i64>,
+        now: DateTime<Utc>,
+    ) -> RegistrationStatus {
+        let applicable = windows
+            .iter()
+            .filter(|window| {
+                let grade_has_own_windows = grade.is_some()
+                    && windows
+                        .iter()
+                        .any(|other| other.year == window.year && other.grade == grade);
+
+                if grade_has_own_windows {
+                    window.grade == grade
+                } else {
+                    window.grade.is_none()
+                }
+            })
+            .collect::<Vec<&Self>>();
+
+        let current = applicable
+            .iter()
+            .filter(|window| window.year == year)
+            .collect::<Vec<_>>();
+
+        if current.is_empty()
+            || current
+                .iter()
+                .any(|window| window.opens_at <= now && now < window.closes_at)
+        {
+            return RegistrationStatus::Open;
+        }
+
+        RegistrationStatus::Closed {
+            next_opening: applicable
+                .iter()
+                .filter(|window| window.opens_at > now)
+                .map(|window| window.opens_at)
+                .min(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn window(grade: Option<i64>, opens_on: u32, closes_on: u32) -> RegistrationWindow {
+        RegistrationWindow {
+            id: Uuid::new_v4(),
+            year: 2023,
+            grade,
+            opens_at: Utc.with_ymd_and_hms(2023, 5, opens_on, 0, 0, 0).unwrap(),
+            closes_at: Utc.with_ymd_and_hms(2023, 5, closes_on, 0, 0, 0).unwrap(),
+            created_at: None,
+        }
+    }
+
+    fn may(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2023, 5, day, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn open_without_windows() {
+        let status = RegistrationWindow::status_from_windows(&[], 2023, Some(4), may(1));
+
+        assert!(matches!(status, RegistrationStatus::Open));
+    }
+
+    #[test]
+    fn shared_window() {
+        let windows = [window(None, 10, 20)];
+
+        let status = RegistrationWindow::status_from_windows(&windows, 2023, Some(4), may(15));
+        assert!(matches!(status, RegistrationStatus::Open));
+
+        let status = RegistrationWindow::status_from_windows(&windows, 2023, Some(4), may(5));
+        assert!(matches!(
+            status,
+            RegistrationStatus::Closed { next_opening: Some(next_opening) }
+                if next_opening == windows[0].opens_at
+        ));
+
+        let status = RegistrationWindow::status_from_windows(&windows, 2023, Some(4), may(25));
+        assert!(matches!(
+            status,
+            RegistrationStatus::Closed { next_opening: None }
+        ));
+    }
+
+    #[test]
+    fn grade_windows_replace_shared_windows() {
+        let windows = [window(None, 10, 20), window(Some(4), 1, 5)];
+
+        // the shared window is open but grade 4 only registers in its own window
+        let status = RegistrationWindow::status_from_windows(&windows, 2023, Some(4), may(15));
+        assert!(matches!(
+            status,
+            RegistrationStatus::Closed { next_opening: None }
+        ));
+
+        let status = RegistrationWindow::status_from_windows(&windows, 2023, Some(4), may(3));
+        assert!(matches!(status, RegistrationStatus::Open));
+
+        // other grades still use the shared window
+        let status = RegistrationWindow::status_from_windows(&windows, 2023, Some(5), may(15));
+        assert!(matches!(status, RegistrationStatus::Open));
+    }
+}
@@ -0,0 +1,337 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, Transaction};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::utils::date::get_current_academic_year;
+
+use super::{club_member_history::ClubMemberHistory, registration::RegistrationSettings};
+
+// students in the final grade graduate and do not return the next academic year
+pub const FINAL_GRADE: i64 = 6;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatableRollover {
+    // the current academic year when left out
+    pub source_year: Option<i64>,
+    // the year after source_year when left out
+    pub target_year: Option<i64>,
+    // approved members who return next year become approved members of the new club
+    #[serde(default)]
+    pub carry_over_members: bool,
+    // report what would be rolled over without changing anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RolledOverClub {
+    #[schema(value_type = String)]
+    pub source_club_id: Uuid,
+    // None in a dry run
+    #[schema(value_type = Option<String>)]
+    pub target_club_id: Option<Uuid>,
+    pub contacts: i64,
+    pub advisors: Vec<i64>,
+    pub staffs: Vec<i64>,
+    pub members: Vec<i64>,
+    // continuing members who already reached the club limit of the target year
+    pub skipped_members: Vec<i64>,
+    // continuing members left out because the new club was already at capacity, oldest
+    // members are carried over first
+    pub over_capacity_members: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RolloverReport {
+    pub source_year: i64,
+    pub target_year: i64,
+    pub dry_run: bool,
+    pub clubs: Vec<RolledOverClub>,
+    // clubs of the source year that were rolled over before
+    #[schema(value_type = Vec<String>)]
+    pub already_rolled_over: Vec<Uuid>,
+}
+
+#[derive(Debug)]
+pub enum RolloverError {
+    Database(sqlx::Error),
+    InvalidYears { source_year: i64, target_year: i64 },
+}
+
+impl From<sqlx::Error> for RolloverError {
+    fn from(e: sqlx::Error) -> Self {
+        RolloverError::Database(e)
+    }
+}
+
+impl std::fmt::Display for RolloverError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RolloverError::Database(e) => write!(f, "{}", e),
+            RolloverError::InvalidYears {
+                source_year,
+                target_year,
+            } => write!(
+                f,
+                "target_year {target_year} must come after source_year {source_year}"
+            ),
+        }
+    }
+}
+
+pub struct Rollover;
+
+impl Rollover {
+    // Everything runs in one transaction which is rolled back at the end of a dry run, so the
+    // report of a dry run is exactly what a real run would do at that moment.
+    pub async fn run(
+        pool: &sqlx::PgPool,
+        rollover: &CreatableRollover,
+        actor: Uuid,
+    ) -> Result<RolloverReport, RolloverError> {
+        let source_year = rollover
+            .source_year
+            .unwrap_or(get_current_academic_year() as i64);
+        let target_year = rollover.target_year.unwrap_or(source_year + 1);
+
+        if target_year <= source_year {
+            return Err(RolloverError::InvalidYears {
+                source_year,
+                target_year,
+            });
+        }
+
+        let mut transaction = pool.begin().await?;
+        ClubMemberHistory::set_actor(&mut transaction, actor).await?;
+
+        let source_clubs = sqlx::query_as::<_, (Uuid, bool)>(
+            r#"
+            SELECT id, EXISTS (SELECT 1 FROM clubs target WHERE target.rolled_over_from = clubs.id)
+            FROM clubs
+            WHERE year = $1 AND archived_at IS NULL
+            ORDER BY id
+            FOR UPDATE
+            "#,
+        )
+        .bind(source_year)
+        .fetch_all(&mut transaction)
+        .await?;
+
+        let max_clubs_per_student =
+            RegistrationSettings::get_by_year(&mut transaction, target_year)
+                .await?
+                .max_clubs_per_student;
+
+        let mut clubs = vec![];
+        let mut already_rolled_over = vec![];
+
+        for (source_club_id, rolled_over) in source_clubs {
+            if rolled_over {
+                already_rolled_over.push(source_club_id);
+                continue;
+            }
+
+            let mut club =
+                Self::roll_over_club(&mut transaction, source_club_id, source_year, target_year)
+                    .await?;
+
+            if rollover.carry_over_members {
+                Self::carry_over_members(
+                    &mut transaction,
+                    &mut club,
+                    source_year,
+                    target_year,
+                    max_clubs_per_student,
+                )
+                .await?;
+            }
+
+            clubs.push(club);
+        }
+
+        if rollover.dry_run {
+            transaction.rollback().await?;
+
+            for club in clubs.iter_mut() {
+                club.target_club_id = None;
+            }
+        } else {
+            transaction.commit().await?;
+        }
+
+        Ok(RolloverReport {
+            source_year,
+            target_year,
+            dry_run: rollover.dry_run,
+            clubs,
+            already_rolled_over,
+        })
+    }
+
+    async fn roll_over_club(
+        transaction: &mut Transaction<'_, Postgres>,
+        source_club_id: Uuid,
+        source_year: i64,
+        target_year: i64,
+    ) -> Result<RolledOverClub, sqlx::Error> {
+        // the organization is copied too so that editing the new club leaves the old one as it was
+        let (target_club_id,) = sqlx::query_as::<_, (Uuid,)>(
+            r#"
+            WITH source AS (
+                SELECT * FROM clubs WHERE id = $1
+            ), organization AS (
+                INSERT INTO organizations (name_th, name_en, description_th, description_en, main_room, logo_url)
+                SELECT name_th, name_en, description_th, description_en, main_room, logo_url
+                FROM organizations WHERE id = (SELECT organization_id FROM source)
+                RETURNING id
+            )
+            INSERT INTO clubs (organization_id, background_color, accent_color, house, map_location, capacity, year, semester, rolled_over_from)
+            SELECT organization.id, background_color, accent_color, house, map_location, capacity, $2, semester, source.id
+            FROM source, organization
+            RETURNING id
+            "#,
+        )
+        .bind(source_club_id)
+        .bind(target_year)
+        .fetch_one(&mut *transaction)
+        .await?;
+
+        let contacts = sqlx::query(
+            r#"
+            INSERT INTO club_contacts (club_id, contact_id)
+            SELECT $2, contact_id FROM club_contacts WHERE club_id = $1
+            "#,
+        )
+        .bind(source_club_id)
+        .bind(target_club_id)
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected() as i64;
+
+        let advisors = sqlx::query_as::<_, (i64,)>(
+            r#"
+            INSERT INTO club_advisors (club_id, teacher_id, year)
+            SELECT $2, teacher_id, $4 FROM club_advisors WHERE club_id = $1 AND year = $3
+            RETURNING teacher_id
+            "#,
+        )
+        .bind(source_club_id)
+        .bind(target_club_id)
+        .bind(source_year)
+        .bind(target_year)
+        .fetch_all(&mut *transaction)
+        .await?;
+
+        // the president keeps the role when they return, otherwise the club starts without one
+        let staffs = sqlx::query_as::<_, (i64,)>(
+            r#"
+            INSERT INTO club_staffs (club_id, student_id, year, is_president)
+            SELECT $2, student_id, $4, is_president FROM club_staffs
+            WHERE club_id = $1 AND year = $3 AND EXISTS (
+                SELECT 1 FROM classroom
+                WHERE club_staffs.student_id = ANY(classroom.students) AND classroom.year = $3 AND classroom.number / 100 < $5
+            )
+            RETURNING student_id
+            "#,
+        )
+        .bind(source_club_id)
+        .bind(target_club_id)
+        .bind(source_year)
+        .bind(target_year)
+        .bind(FINAL_GRADE)
+        .fetch_all(&mut *transaction)
+        .await?;
+
+        Ok(RolledOverClub {
+            source_club_id,
+            target_club_id: Some(target_club_id),
+            contacts,
+            advisors: advisors.into_iter().map(|(id,)| id).collect(),
+            staffs: staffs.into_iter().map(|(id,)| id).collect(),
+            members: vec![],
+            skipped_members: vec![],
+            over_capacity_members: vec![],
+        })
+    }
+
+    async fn carry_over_members(
+        transaction: &mut Transaction<'_, Postgres>,
+        club: &mut RolledOverClub,
+        source_year: i64,
+        target_year: i64,
+        max_clubs_per_student: i64,
+    ) -> Result<(), sqlx::Error> {
+        let target_club_id = match club.target_club_id {
+            Some(id) => id,
+            None => return Ok(()),
+        };
+
+        let continuing = sqlx::query_as::<_, (i64,)>(
+            r#"
+            SELECT student_id FROM club_members
+            WHERE club_id = $1 AND year = $2 AND membership_status = 'approved' AND EXISTS (
+                SELECT 1 FROM classroom
+                WHERE club_members.student_id = ANY(classroom.students) AND classroom.year = $2 AND classroom.number / 100 < $3
+            )
+            ORDER BY created_at, id
+            "#,
+        )
+        .bind(club.source_club_id)
+        .bind(source_year)
+        .bind(FINAL_GRADE)
+        .fetch_all(&mut *transaction)
+        .await?;
+
+        let (capacity,) = sqlx::query_as::<_, (Option<i64>,)>(
+            r#"
+            SELECT capacity FROM clubs WHERE id = $1
+            "#,
+        )
+        .bind(target_club_id)
+        .fetch_one(&mut *transaction)
+        .await?;
+
+        for (student_id,) in continuing {
+            if let Some(capacity) = capacity {
+                if club.members.len() as i64 >= capacity {
+                    club.over_capacity_members.push(student_id);
+                    continue;
+                }
+            }
+
+            // the count includes members carried over into other clubs earlier in this run
+            let (joined_clubs,) = sqlx::query_as::<_, (i64,)>(
+                r#"
+                SELECT COUNT(id) FROM club_members
+                WHERE student_id = $1 AND year = $2 AND membership_status = 'approved'
+                "#,
+            )
+            .bind(student_id)
+            .bind(target_year)
+            .fetch_one(&mut *transaction)
+            .await?;
+
+            if joined_clubs >= max_clubs_per_student {
+                club.skipped_members.push(student_id);
+                continue;
+            }
+
+            sqlx::query(
+                r#"
+                INSERT INTO club_members (club_id, student_id, year, semester, membership_status)
+                VALUES ($1, $2, $3, (SELECT semester FROM clubs WHERE id = $1), 'approved')
+                "#,
+            )
+            .bind(target_club_id)
+            .bind(student_id)
+            .bind(target_year)
+            .execute(&mut *transaction)
+            .await?;
+
+            club.members.push(student_id);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,315 @@
+use std::collections::HashMap;
+
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+use uuid::Uuid;
+
+use super::{
+    classroom::Classroom,
+    club_request::{ClubRequest, ClubRequestSortableField, ClubRequestTable, QueryableClubRequest},
+    clubs::SubmissionStatus,
+    common::{FilterConfig, RequestType},
+    student::{PeopleTable, StudentTable},
+};
+
+// excel only reads a csv as utf-8 when it starts with a byte order mark, without it thai
+// names come out garbled
+pub(crate) const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+const HEADERS: [&str; 12] = [
+    "student_id",
+    "prefix_th",
+    "first_name_th",
+    "middle_name_th",
+    "last_name_th",
+    "prefix_en",
+    "first_name_en",
+    "middle_name_en",
+    "last_name_en",
+    "class",
+    "class_number",
+    "club",
+];
+
+#[derive(Debug)]
+pub enum RosterExportError {
+    Database(sqlx::Error),
+    Csv(csv::Error),
+    Xlsx(XlsxError),
+}
+
+impl From<sqlx::Error> for RosterExportError {
+    fn from(e: sqlx::Error) -> Self {
+        RosterExportError::Database(e)
+    }
+}
+
+impl From<csv::Error> for RosterExportError {
+    fn from(e: csv::Error) -> Self {
+        RosterExportError::Csv(e)
+    }
+}
+
+impl From<XlsxError> for RosterExportError {
+    fn from(e: XlsxError) -> Self {
+        RosterExportError::Xlsx(e)
+    }
+}
+
+impl std::fmt::Display for RosterExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RosterExportError::Database(e) => write!(f, "{}", e),
+            RosterExportError::Csv(e) => write!(f, "{}", e),
+            RosterExportError::Xlsx(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for RosterExportError {}
+
+// one csv line, quoted the same way a single writer over the whole file would
+fn csv_line<I, T>(record: I) -> Result<Vec<u8>, RosterExportError>
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<[u8]>,
+{
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer.write_record(record)?;
+
+    writer
+        .into_inner()
+        .map_err(|e| RosterExportError::Csv(e.into_error().into()))
+}
+
+pub(crate) struct RosterRow {
+    pub(crate) student_id: u32,
+    pub(crate) prefix_th: String,
+    pub(crate) first_name_th: String,
+    pub(crate) middle_name_th: Option<String>,
+    pub(crate) last_name_th: String,
+    pub(crate) prefix_en: Option<String>,
+    pub(crate) first_name_en: Option<String>,
+    pub(crate) middle_name_en: Option<String>,
+    pub(crate) last_name_en: Option<String>,
+    // classroom number such as 405, None for students without a classroom this year
+    pub(crate) class: Option<u32>,
+    pub(crate) class_number: Option<u32>,
+    pub(crate) club: String,
+}
+
+impl RosterRow {
+    fn to_record(&self) -> [String; 12] {
+        let optional = |value: &Option<String>| value.clone().unwrap_or_default();
+        let number = |value: Option<u32>| value.map(|value| value.to_string()).unwrap_or_default();
+
+        [
+            self.student_id.to_string(),
+            self.prefix_th.clone(),
+            self.first_name_th.clone(),
+            optional(&self.middle_name_th),
+            self.last_name_th.clone(),
+            optional(&self.prefix_en),
+            optional(&self.first_name_en),
+            optional(&self.middle_name_en),
+            optional(&self.last_name_en),
+            number(self.class),
+            number(self.class_number),
+            self.club.clone(),
+        ]
+    }
+}
+
+pub struct RosterExport {
+    rows: Vec<RosterRow>,
+}
+
+impl RosterExport {
+    // approved members matching the join request filters whatever membership_status was
+    // asked for, sorted the way the lists are read: by club, then class and class number
+    pub async fn query(
+        pool: &sqlx::PgPool,
+        request: &RequestType<ClubRequest, QueryableClubRequest, ClubRequestSortableField>,
+    ) -> Result<Self, RosterExportError> {
+        let filter = request
+            .filter
+            .as_ref()
+            .and_then(|filter| filter.data.clone());
+
+        let request = RequestType {
+            data: None,
+            pagination: None,
+            filter: Some(FilterConfig {
+                data: Some(QueryableClubRequest {
+                    membership_status: Some(SubmissionStatus::Approved),
+                    ..filter.unwrap_or(QueryableClubRequest {
+                        id: None,
+                        club_id: None,
+                        student_id: None,
+                        year: None,
+                        semester: None,
+                        classroom_id: None,
+                        membership_status: None,
+                    })
+                }),
+                q: None,
+            }),
+            sorting: None,
+            fetch_level: None,
+            descendant_fetch_level: None,
+        };
+
+        let members = ClubRequestTable::query_all(pool, &request).await?;
+
+        // everything is loaded in bulk, whole school exports run into thousands of members
+        let mut club_ids = members
+            .iter()
+            .map(|member| member.club_id)
+            .collect::<Vec<Uuid>>();
+        club_ids.sort();
+        club_ids.dedup();
+
+        let clubs: HashMap<Uuid, String> = sqlx::query_as::<_, (Uuid, String)>(
+            r#"
+            SELECT clubs.id, name_th FROM clubs
+            INNER JOIN organizations ON clubs.organization_id = organizations.id
+            WHERE clubs.id = ANY($1)
+            "#,
+        )
+        .bind(&club_ids)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .collect();
+
+        let mut student_ids = members
+            .iter()
+            .map(|member| member.student_id)
+            .collect::<Vec<i64>>();
+        student_ids.sort();
+        student_ids.dedup();
+
+        let students = StudentTable::get_from_ids(pool, student_ids.clone()).await?;
+        let mut people: HashMap<i64, PeopleTable> = PeopleTable::get_from_ids(
+            pool,
+            students.iter().map(|student| student.person).collect(),
+        )
+        .await?
+        .into_iter()
+        .map(|person| (person.id, person))
+        .collect();
+        let students: HashMap<i64, (String, PeopleTable)> = students
+            .into_iter()
+            .filter_map(|student| {
+                people
+                    .remove(&student.person)
+                    .map(|person| (student.id, (student.std_id, person)))
+            })
+            .collect();
+
+        let mut years = members
+            .iter()
+            .map(|member| member.year)
+            .collect::<Vec<i64>>();
+        years.sort();
+        years.dedup();
+
+        // the classroom of the year of the membership
+        let places = Classroom::get_places(pool, &student_ids, &years).await?;
+
+        let mut rows = vec![];
+
+        for member in members {
+            let (std_id, person) = match students.get(&member.student_id) {
+                Some(student) => student,
+                None => return Err(sqlx::Error::RowNotFound.into()),
+            };
+            let place = places.get(&(member.student_id, member.year));
+
+            rows.push(RosterRow {
+                student_id: std_id.parse::<u32>().unwrap_or_default(),
+                prefix_th: person.prefix_th.clone(),
+                first_name_th: person.first_name_th.clone(),
+                middle_name_th: person.middle_name_th.clone(),
+                last_name_th: person.last_name_th.clone(),
+                prefix_en: person.prefix_en.clone(),
+                first_name_en: person.first_name_en.clone(),
+                middle_name_en: person.middle_name_en.clone(),
+                last_name_en: person.last_name_en.clone(),
+                class: place.map(|(number, _)| *number),
+                class_number: place.and_then(|(_, class_number)| *class_number),
+                club: clubs.get(&member.club_id).cloned().unwrap_or_default(),
+            });
+        }
+
+        rows.sort_by(|a, b| {
+            (&a.club, a.class, a.class_number, a.student_id).cmp(&(
+                &b.club,
+                b.class,
+                b.class_number,
+                b.student_id,
+            ))
+        });
+
+        Ok(Self { rows })
+    }
+
+    pub(crate) fn rows(&self) -> &[RosterRow] {
+        &self.rows
+    }
+
+    // the csv a line at a time so it can be streamed, starting with the byte order mark and
+    // the header
+    pub fn into_csv_lines(self) -> impl Iterator<Item = Result<Vec<u8>, RosterExportError>> {
+        let header = csv_line(HEADERS).map(|header| [UTF8_BOM, &header].concat());
+
+        std::iter::once(header).chain(self.rows.into_iter().map(|row| csv_line(row.to_record())))
+    }
+
+    pub fn to_xlsx(&self) -> Result<Vec<u8>, RosterExportError> {
+        let mut workbook = Workbook::new();
+        let worksheet = workbook.add_worksheet();
+        let header_format = Format::new().set_bold();
+
+        for (column, header) in HEADERS.iter().enumerate() {
+            worksheet.write_string_with_format(0, column as u16, *header, &header_format)?;
+        }
+
+        for (index, row) in self.rows.iter().enumerate() {
+            let line = index as u32 + 1;
+
+            // numbers stay numbers so the sheet sorts and filters properly
+            worksheet.write_number(line, 0, row.student_id)?;
+
+            let names = [
+                Some(&row.prefix_th),
+                Some(&row.first_name_th),
+                row.middle_name_th.as_ref(),
+                Some(&row.last_name_th),
+                row.prefix_en.as_ref(),
+                row.first_name_en.as_ref(),
+                row.middle_name_en.as_ref(),
+                row.last_name_en.as_ref(),
+            ];
+
+            for (column, name) in names.into_iter().enumerate() {
+                if let Some(name) = name {
+                    worksheet.write_string(line, column as u16 + 1, name)?;
+                }
+            }
+
+            if let Some(class) = row.class {
+                worksheet.write_number(line, 9, class)?;
+            }
+
+            if let Some(class_number) = row.class_number {
+                worksheet.write_number(line, 10, class_number)?;
+            }
+
+            worksheet.write_string(line, 11, &row.club)?;
+        }
+
+        worksheet.autofit();
+
+        Ok(workbook.save_to_buffer()?)
+    }
+}
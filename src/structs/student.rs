@@ -76,7 +76,7 @@ enum ShirtSize {
 }
 
 #[derive(FromRow, Debug)]
-struct PeopleTable {
+pub(super) struct PeopleTable {
     pub id: i64,
     pub created_at: Option<DateTime<Utc>>,
     pub prefix_th: String,
@@ -98,7 +98,7 @@ struct PeopleTable {
 }
 
 impl PeopleTable {
-    async fn get_by_id(pool: &Pool<Postgres>, id: i64) -> Result<Self, sqlx::Error> {
+    pub(super) async fn get_by_id(pool: &Pool<Postgres>, id: i64) -> Result<Self, sqlx::Error> {
         sqlx::query_as!(
             PeopleTable,
             r#"
@@ -112,7 +112,10 @@ impl PeopleTable {
         .await
     }
 
-    async fn get_from_ids(pool: &Pool<Postgres>, ids: Vec<i64>) -> Result<Vec<Self>, sqlx::Error> {
+    pub(super) async fn get_from_ids(
+        pool: &Pool<Postgres>,
+        ids: Vec<i64>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
         sqlx::query_as!(
             PeopleTable,
             r#"
@@ -128,7 +131,7 @@ impl PeopleTable {
 }
 
 #[derive(FromRow, Debug)]
-struct StudentTable {
+pub(super) struct StudentTable {
     pub id: i64,
     pub created_at: Option<DateTime<Utc>>,
     pub std_id: String,
@@ -150,7 +153,10 @@ impl StudentTable {
         .await
     }
 
-    async fn get_from_ids(pool: &Pool<Postgres>, ids: Vec<i64>) -> Result<Vec<Self>, sqlx::Error> {
+    pub(super) async fn get_from_ids(
+        pool: &Pool<Postgres>,
+        ids: Vec<i64>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
         sqlx::query_as!(
             StudentTable,
             r#"
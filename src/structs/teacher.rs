@@ -0,0 +1,356 @@
+use actix_web::error::{ErrorNotFound, ErrorUnauthorized};
+use actix_web::{dev::Payload, Error as ActixWebError};
+use actix_web::{web, FromRequest, HttpRequest};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use futures::Future as FutureTrait;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, Pool, Postgres};
+
+use utoipa::ToSchema;
+
+use std::pin::Pin;
+
+use crate::structs::common::{ErrorResponseType, ErrorType};
+
+use crate::AppState;
+
+use crate::structs::{
+    auth::User,
+    common::{FetchLevel, MultiLangString},
+    contacts::Contact,
+    student::PeopleTable,
+};
+
+use super::auth::UserRoles;
+
+#[derive(FromRow, Debug)]
+struct TeacherTable {
+    pub id: i64,
+    pub created_at: Option<DateTime<Utc>>,
+    pub teacher_id: Option<String>,
+    pub person: i64,
+}
+
+impl TeacherTable {
+    async fn get_by_id(pool: &Pool<Postgres>, id: i64) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, TeacherTable>(
+            r#"
+            SELECT id, created_at, teacher_id, person
+            FROM teacher
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_one(pool)
+        .await
+    }
+
+    async fn get_from_ids(pool: &Pool<Postgres>, ids: Vec<i64>) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, TeacherTable>(
+            r#"
+            SELECT id, created_at, teacher_id, person
+            FROM teacher
+            WHERE id = ANY($1)
+            ORDER BY id
+            "#,
+        )
+        .bind(&ids)
+        .fetch_all(pool)
+        .await
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, ToSchema)]
+pub struct IdOnlyTeacher {
+    pub id: u32,
+}
+
+impl IdOnlyTeacher {
+    pub async fn get_by_id(pool: &Pool<Postgres>, id: u32) -> Result<Self, sqlx::Error> {
+        let teacher = TeacherTable::get_by_id(pool, id as i64).await?;
+
+        Ok(Self {
+            id: teacher.id as u32,
+        })
+    }
+
+    pub async fn get_from_ids(
+        pool: &Pool<Postgres>,
+        ids: Vec<i64>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let teachers = TeacherTable::get_from_ids(pool, ids).await?;
+
+        Ok(teachers
+            .into_iter()
+            .map(|x| Self { id: x.id as u32 })
+            .collect())
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, ToSchema)]
+pub struct CompactTeacher {
+    pub id: u32,
+    pub prefix: MultiLangString,
+    pub first_name: MultiLangString,
+    pub last_name: MultiLangString,
+    pub profile_url: Option<String>,
+    pub teacher_id: Option<String>,
+}
+
+impl CompactTeacher {
+    fn from_table(teacher: TeacherTable, person: PeopleTable) -> Self {
+        Self {
+            id: teacher.id as u32,
+            prefix: MultiLangString {
+                th: person.prefix_th,
+                en: person.prefix_en,
+            },
+            first_name: MultiLangString {
+                th: person.first_name_th,
+                en: person.first_name_en,
+            },
+            last_name: MultiLangString {
+                th: person.last_name_th,
+                en: person.last_name_en,
+            },
+            profile_url: person.profile,
+            teacher_id: teacher.teacher_id,
+        }
+    }
+
+    pub async fn get_by_id(pool: &Pool<Postgres>, id: u32) -> Result<Self, sqlx::Error> {
+        let teacher = TeacherTable::get_by_id(pool, id as i64).await?;
+        let person = PeopleTable::get_by_id(pool, teacher.person).await?;
+
+        Ok(Self::from_table(teacher, person))
+    }
+
+    pub async fn get_from_ids(
+        pool: &Pool<Postgres>,
+        ids: Vec<i64>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let teachers = TeacherTable::get_from_ids(pool, ids).await?;
+        let mut teachers_to_return = vec![];
+
+        // people are fetched one by one as get_from_ids does not keep the order of the ids
+        for teacher in teachers {
+            let person = PeopleTable::get_by_id(pool, teacher.person).await?;
+
+            teachers_to_return.push(Self::from_table(teacher, person));
+        }
+
+        Ok(teachers_to_return)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, ToSchema)]
+pub struct DefaultTeacher {
+    pub id: u32,
+    pub prefix: MultiLangString,
+    pub first_name: MultiLangString,
+    pub last_name: MultiLangString,
+    pub middle_name: Option<MultiLangString>,
+    pub profile_url: Option<String>,
+    #[schema(value_type = String, example = "1980-01-22")]
+    pub birthdate: NaiveDate,
+    pub contacts: Vec<Contact>,
+    pub teacher_id: Option<String>,
+    // teachers without an account yet have no user
+    pub user: Option<User>,
+}
+
+impl DefaultTeacher {
+    pub async fn get_by_id(
+        pool: &Pool<Postgres>,
+        id: u32,
+        descendant_fetch_level: Option<FetchLevel>,
+    ) -> Result<Self, sqlx::Error> {
+        let descendant_fetch_level = descendant_fetch_level.unwrap_or(FetchLevel::IdOnly);
+
+        let teacher = TeacherTable::get_by_id(pool, id as i64).await?;
+        let person = PeopleTable::get_by_id(pool, teacher.person).await?;
+        let user = match User::from_teacher_id(teacher.id as u32, pool).await {
+            Ok(user) => Some(user),
+            Err(sqlx::Error::RowNotFound) => None,
+            Err(e) => return Err(e),
+        };
+
+        Ok(Self {
+            id: teacher.id as u32,
+            prefix: MultiLangString {
+                th: person.prefix_th,
+                en: person.prefix_en,
+            },
+            first_name: MultiLangString {
+                th: person.first_name_th,
+                en: person.first_name_en,
+            },
+            middle_name: match (person.middle_name_th, person.middle_name_en) {
+                (Some(th), Some(en)) => Some(MultiLangString { th, en: Some(en) }),
+                _ => None,
+            },
+            last_name: MultiLangString {
+                th: person.last_name_th,
+                en: person.last_name_en,
+            },
+            contacts: Contact::get_from_ids(
+                pool,
+                person.contacts.unwrap_or(vec![]),
+                descendant_fetch_level,
+            )
+            .await?,
+            profile_url: person.profile,
+            birthdate: person.birthdate,
+            teacher_id: teacher.teacher_id,
+            user,
+        })
+    }
+
+    pub async fn get_from_ids(
+        pool: &Pool<Postgres>,
+        ids: Vec<i64>,
+        descendant_fetch_level: Option<FetchLevel>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let teachers = TeacherTable::get_from_ids(pool, ids).await?;
+        let mut teachers_to_return = vec![];
+
+        for teacher in teachers {
+            teachers_to_return.push(
+                Self::get_by_id(pool, teacher.id as u32, descendant_fetch_level.clone()).await?,
+            );
+        }
+
+        Ok(teachers_to_return)
+    }
+}
+
+#[derive(Deserialize, Debug, ToSchema)]
+pub enum Teacher {
+    Default(DefaultTeacher),
+    IdOnly(IdOnlyTeacher),
+    Compact(CompactTeacher),
+}
+
+impl Serialize for Teacher {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        match self {
+            Teacher::Default(teacher) => teacher.serialize(serializer),
+            Teacher::IdOnly(teacher) => teacher.serialize(serializer),
+            Teacher::Compact(teacher) => teacher.serialize(serializer),
+        }
+    }
+}
+
+impl Teacher {
+    pub async fn get_by_id(
+        pool: &Pool<Postgres>,
+        id: u32,
+        level: Option<FetchLevel>,
+        descendant_fetch_level: Option<FetchLevel>,
+    ) -> Result<Self, sqlx::Error> {
+        match level {
+            Some(FetchLevel::IdOnly) => Ok(Self::IdOnly(IdOnlyTeacher::get_by_id(pool, id).await?)),
+            Some(FetchLevel::Compact) => {
+                Ok(Self::Compact(CompactTeacher::get_by_id(pool, id).await?))
+            }
+            Some(FetchLevel::Default) | None => Ok(Self::Default(
+                DefaultTeacher::get_by_id(pool, id, descendant_fetch_level).await?,
+            )),
+        }
+    }
+
+    pub async fn get_from_ids(
+        pool: &Pool<Postgres>,
+        ids: Vec<i64>,
+        level: Option<FetchLevel>,
+        descendant_fetch_level: Option<FetchLevel>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        match level {
+            Some(FetchLevel::IdOnly) => Ok(IdOnlyTeacher::get_from_ids(pool, ids)
+                .await?
+                .into_iter()
+                .map(Self::IdOnly)
+                .collect()),
+            Some(FetchLevel::Compact) => Ok(CompactTeacher::get_from_ids(pool, ids)
+                .await?
+                .into_iter()
+                .map(Self::Compact)
+                .collect()),
+            Some(FetchLevel::Default) | None => {
+                Ok(
+                    DefaultTeacher::get_from_ids(pool, ids, descendant_fetch_level)
+                        .await?
+                        .into_iter()
+                        .map(Self::Default)
+                        .collect(),
+                )
+            }
+        }
+    }
+}
+
+impl FromRequest for Teacher {
+    type Error = ActixWebError;
+    type Future = Pin<Box<dyn FutureTrait<Output = Result<Self, Self::Error>>>>;
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let pool = req.app_data::<web::Data<AppState>>().unwrap().db.clone();
+
+        // run normal user auth
+        let fut = User::from_request(req, _payload);
+
+        // then check if user is teacher
+        Box::pin(async move {
+            let user = fut.await?;
+
+            match user.role {
+                UserRoles::Teacher => {
+                    let teacher_id = match user.teacher {
+                        Some(id) => id,
+                        None => {
+                            return Err(ErrorNotFound(ErrorResponseType::new(
+                                ErrorType {
+                                    id: "404".to_string(),
+                                    detail: "Teacher ID not Found".to_string(),
+                                    code: 404,
+                                    error_type: "entity_not_found".to_string(),
+                                    source: "".to_string(),
+                                },
+                                None,
+                            )))
+                        }
+                    };
+
+                    match Teacher::get_by_id(&pool, teacher_id, Some(FetchLevel::IdOnly), None)
+                        .await
+                    {
+                        Ok(teacher) => Ok(teacher),
+                        Err(_) => Err(ErrorNotFound(ErrorResponseType::new(
+                            ErrorType {
+                                id: "404".to_string(),
+                                detail: "Teacher not found".to_string(),
+                                code: 404,
+                                error_type: "entity_not_found".to_string(),
+                                source: "".to_string(),
+                            },
+                            None,
+                        ))),
+                    }
+                }
+                _ => Err(ErrorUnauthorized(ErrorResponseType::new(
+                    ErrorType {
+                        id: "401".to_string(),
+                        detail: "User not a teacher".to_string(),
+                        code: 401,
+                        error_type: "invalid_permission".to_string(),
+                        source: "".to_string(),
+                    },
+                    None,
+                ))),
+            }
+        })
+    }
+}
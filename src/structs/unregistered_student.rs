@@ -0,0 +1,245 @@
+use std::collections::{HashMap, HashSet};
+
+use rust_xlsxwriter::{Format, Workbook};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::utils::date::get_current_academic_year;
+
+use super::{
+    classroom::Classroom,
+    roster_export::{RosterExportError, UTF8_BOM},
+    student::CompactStudent,
+};
+
+const HEADERS: [&str; 9] = [
+    "student_id",
+    "prefix_th",
+    "first_name_th",
+    "last_name_th",
+    "prefix_en",
+    "first_name_en",
+    "last_name_en",
+    "class",
+    "class_number",
+];
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct QueryableUnregisteredStudent {
+    // one classroom, takes precedence over grade
+    pub classroom_id: Option<i64>,
+    // every classroom of a grade, e.g. 4 for classrooms 401 to 415
+    pub grade: Option<i64>,
+    // the current academic year when left out, only used with grade
+    pub year: Option<i64>,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct UnregisteredStudent {
+    pub class_number: Option<u32>,
+    pub student: CompactStudent,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct UnregisteredClassroom {
+    pub classroom_id: u32,
+    pub number: u32,
+    pub total_students: u32,
+    pub unregistered_count: u32,
+    pub students: Vec<UnregisteredStudent>,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct UnregisteredStudentReport {
+    pub year: i64,
+    pub total_students: u32,
+    pub unregistered_count: u32,
+    pub classrooms: Vec<UnregisteredClassroom>,
+}
+
+impl UnregisteredStudentReport {
+    // students count as registered once they have an approved or pending membership in the
+    // classroom's academic year, declined, waitlisted and withdrawn requests still need chasing
+    pub async fn query(
+        pool: &sqlx::PgPool,
+        filter: &QueryableUnregisteredStudent,
+    ) -> Result<Self, sqlx::Error> {
+        let year = filter.year.unwrap_or(get_current_academic_year() as i64);
+
+        let (classroom_ids, year) = match (filter.classroom_id, filter.grade) {
+            (Some(classroom_id), _) => {
+                let roster = Classroom::get_roster(pool, classroom_id as u32).await?;
+                (vec![classroom_id], roster.year)
+            }
+            (None, Some(grade)) => {
+                let classrooms = sqlx::query_as::<_, (i64,)>(
+                    r#"
+                    SELECT id FROM classroom WHERE year = $1 AND number / 100 = $2 ORDER BY number
+                    "#,
+                )
+                .bind(year)
+                .bind(grade)
+                .fetch_all(pool)
+                .await?;
+
+                (classrooms.into_iter().map(|(id,)| id).collect(), year)
+            }
+            (None, None) => (vec![], year),
+        };
+
+        let mut report = Self {
+            year,
+            total_students: 0,
+            unregistered_count: 0,
+            classrooms: vec![],
+        };
+
+        for classroom_id in classroom_ids {
+            let roster = Classroom::get_roster(pool, classroom_id as u32).await?;
+            let student_ids = roster
+                .students
+                .iter()
+                .map(|(student_id, _)| *student_id)
+                .collect::<Vec<i64>>();
+
+            let registered: HashSet<i64> = sqlx::query_as::<_, (i64,)>(
+                r#"
+                SELECT DISTINCT student_id FROM club_members
+                WHERE student_id = ANY($1) AND year = $2 AND membership_status IN ('approved', 'pending')
+                "#,
+            )
+            .bind(&student_ids)
+            .bind(year)
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .map(|(student_id,)| student_id)
+            .collect();
+
+            let unregistered = roster
+                .students
+                .iter()
+                .filter(|(student_id, _)| !registered.contains(student_id))
+                .collect::<Vec<_>>();
+
+            let mut students: HashMap<u32, CompactStudent> = CompactStudent::get_from_ids(
+                pool,
+                unregistered
+                    .iter()
+                    .map(|(student_id, _)| *student_id)
+                    .collect(),
+            )
+            .await?
+            .into_iter()
+            .map(|student| (student.id, student))
+            .collect();
+
+            // the roster is already in class number order
+            let students = unregistered
+                .iter()
+                .filter_map(|(student_id, class_number)| {
+                    students
+                        .remove(&(*student_id as u32))
+                        .map(|student| UnregisteredStudent {
+                            class_number: *class_number,
+                            student,
+                        })
+                })
+                .collect::<Vec<UnregisteredStudent>>();
+
+            report.total_students += roster.students.len() as u32;
+            report.unregistered_count += students.len() as u32;
+            report.classrooms.push(UnregisteredClassroom {
+                classroom_id: roster.id,
+                number: roster.number,
+                total_students: roster.students.len() as u32,
+                unregistered_count: students.len() as u32,
+                students,
+            });
+        }
+
+        Ok(report)
+    }
+
+    fn records(&self) -> Vec<(u32, &UnregisteredStudent)> {
+        self.classrooms
+            .iter()
+            .flat_map(|classroom| {
+                classroom
+                    .students
+                    .iter()
+                    .map(move |student| (classroom.number, student))
+            })
+            .collect()
+    }
+
+    pub fn to_csv(&self) -> Result<Vec<u8>, RosterExportError> {
+        let mut writer = csv::Writer::from_writer(UTF8_BOM.to_vec());
+
+        writer.write_record(HEADERS)?;
+
+        for (class, row) in self.records() {
+            let student = &row.student;
+
+            writer.write_record([
+                student.student_id.to_string(),
+                student.prefix.th.clone(),
+                student.first_name.th.clone(),
+                student.last_name.th.clone(),
+                student.prefix.en.clone().unwrap_or_default(),
+                student.first_name.en.clone().unwrap_or_default(),
+                student.last_name.en.clone().unwrap_or_default(),
+                class.to_string(),
+                row.class_number
+                    .map(|class_number| class_number.to_string())
+                    .unwrap_or_default(),
+            ])?;
+        }
+
+        writer
+            .into_inner()
+            .map_err(|e| RosterExportError::Csv(e.into_error().into()))
+    }
+
+    pub fn to_xlsx(&self) -> Result<Vec<u8>, RosterExportError> {
+        let mut workbook = Workbook::new();
+        let worksheet = workbook.add_worksheet();
+        let header_format = Format::new().set_bold();
+
+        for (column, header) in HEADERS.iter().enumerate() {
+            worksheet.write_string_with_format(0, column as u16, *header, &header_format)?;
+        }
+
+        for (index, (class, row)) in self.records().into_iter().enumerate() {
+            let line = index as u32 + 1;
+            let student = &row.student;
+
+            worksheet.write_number(line, 0, student.student_id)?;
+
+            let names = [
+                Some(&student.prefix.th),
+                Some(&student.first_name.th),
+                Some(&student.last_name.th),
+                student.prefix.en.as_ref(),
+                student.first_name.en.as_ref(),
+                student.last_name.en.as_ref(),
+            ];
+
+            for (column, name) in names.into_iter().enumerate() {
+                if let Some(name) = name {
+                    worksheet.write_string(line, column as u16 + 1, name)?;
+                }
+            }
+
+            worksheet.write_number(line, 7, class)?;
+
+            if let Some(class_number) = row.class_number {
+                worksheet.write_number(line, 8, class_number)?;
+            }
+        }
+
+        worksheet.autofit();
+
+        Ok(workbook.save_to_buffer()?)
+    }
+}
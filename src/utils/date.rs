@@ -1,13 +1,204 @@
-use chrono::{Datelike, Utc};
+use std::env;
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, Utc};
+
+static ACADEMIC_CALENDAR: OnceLock<AcademicCalendar> = OnceLock::new();
+
+// the school calendar, an academic year is named after the calendar year it starts in and
+// is split into two semesters
+#[derive(Debug, Clone, Copy)]
+pub struct AcademicCalendar {
+    // (month, day) the academic year and its first semester start on
+    pub year_start: (u32, u32),
+    // (month, day) the second semester starts on
+    pub second_semester_start: (u32, u32),
+    // dates flip at midnight in this timezone rather than in UTC
+    pub utc_offset: FixedOffset,
+}
+
+impl Default for AcademicCalendar {
+    // thai schools start the year in may and the second semester in november
+    fn default() -> Self {
+        Self {
+            year_start: (5, 1),
+            second_semester_start: (11, 1),
+            utc_offset: FixedOffset::east_opt(7 * 3600).unwrap(),
+        }
+    }
+}
+
+impl AcademicCalendar {
+    // ACADEMIC_YEAR_START and SECOND_SEMESTER_START are read as MM-DD and ACADEMIC_UTC_OFFSET
+    // as +HH:MM, anything left out keeps its default
+    pub fn from_env() -> Result<Self, String> {
+        let default = Self::default();
+
+        let year_start = match Self::var("ACADEMIC_YEAR_START") {
+            Some(value) => Self::parse_month_day("ACADEMIC_YEAR_START", &value)?,
+            None => default.year_start,
+        };
+        let second_semester_start = match Self::var("SECOND_SEMESTER_START") {
+            Some(value) => Self::parse_month_day("SECOND_SEMESTER_START", &value)?,
+            None => default.second_semester_start,
+        };
+        let utc_offset = match Self::var("ACADEMIC_UTC_OFFSET") {
+            Some(value) => value
+                .parse::<FixedOffset>()
+                .map_err(|_| format!("ACADEMIC_UTC_OFFSET must look like +07:00, got {value}"))?,
+            None => default.utc_offset,
+        };
+
+        if second_semester_start == year_start {
+            return Err("SECOND_SEMESTER_START must differ from ACADEMIC_YEAR_START".to_string());
+        }
+
+        Ok(Self {
+            year_start,
+            second_semester_start,
+            utc_offset,
+        })
+    }
+
+    // empty values count as left out so the example env file can be copied as is
+    fn var(name: &str) -> Option<String> {
+        env::var(name).ok().filter(|value| !value.is_empty())
+    }
+
+    fn parse_month_day(name: &str, value: &str) -> Result<(u32, u32), String> {
+        // checked against a non leap year so that every academic year has the date
+        let date = NaiveDate::parse_from_str(&format!("2001-{value}"), "%Y-%m-%d")
+            .map_err(|_| format!("{name} must look like 05-16, got {value}"))?;
+
+        Ok((date.month(), date.day()))
+    }
+
+    // sets the calendar used by the functions below, only the first call has an effect
+    pub fn init(calendar: AcademicCalendar) {
+        let _ = ACADEMIC_CALENDAR.set(calendar);
+    }
+
+    pub fn get() -> &'static AcademicCalendar {
+        ACADEMIC_CALENDAR.get_or_init(AcademicCalendar::default)
+    }
+
+    // the local date at a point in time
+    pub fn date_of(&self, at: DateTime<Utc>) -> NaiveDate {
+        at.with_timezone(&self.utc_offset).date_naive()
+    }
+
+    pub fn academic_year_of(&self, at: DateTime<Utc>) -> u32 {
+        self.academic_year_of_date(self.date_of(at))
+    }
+
+    pub fn academic_year_of_date(&self, date: NaiveDate) -> u32 {
+        if (date.month(), date.day()) < self.year_start {
+            date.year() as u32 - 1
+        } else {
+            date.year() as u32
+        }
+    }
+
+    pub fn semester_of(&self, at: DateTime<Utc>) -> u32 {
+        self.semester_of_date(self.date_of(at))
+    }
+
+    pub fn semester_of_date(&self, date: NaiveDate) -> u32 {
+        let year = self.academic_year_of_date(date);
+
+        if date >= self.date_in_year(year, self.second_semester_start) {
+            2
+        } else {
+            1
+        }
+    }
+
+    // the calendar date of a (month, day) within an academic year, months before the year
+    // start fall into the next calendar year
+    fn date_in_year(&self, year: u32, (month, day): (u32, u32)) -> NaiveDate {
+        let calendar_year = if (month, day) < self.year_start {
+            year + 1
+        } else {
+            year
+        };
+
+        NaiveDate::from_ymd_opt(calendar_year as i32, month, day).unwrap()
+    }
+}
 
 pub fn get_current_academic_year() -> u32 {
-    let now = Utc::now();
-    let month = now.month();
-    let year = now.year();
-
-    if month <= 4 {
-        year as u32 - 1
-    } else {
-        year as u32
+    AcademicCalendar::get().academic_year_of(Utc::now())
+}
+
+pub fn get_current_semester() -> u32 {
+    AcademicCalendar::get().semester_of(Utc::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn june_calendar() -> AcademicCalendar {
+        AcademicCalendar {
+            year_start: (6, 1),
+            second_semester_start: (1, 15),
+            ..AcademicCalendar::default()
+        }
+    }
+
+    #[test]
+    fn year_flips_on_the_year_start() {
+        let calendar = AcademicCalendar::default();
+
+        assert_eq!(calendar.academic_year_of_date(date(2024, 4, 30)), 2023);
+        assert_eq!(calendar.academic_year_of_date(date(2024, 5, 1)), 2024);
+    }
+
+    #[test]
+    fn semester_flips_on_both_boundaries() {
+        let calendar = AcademicCalendar::default();
+
+        assert_eq!(calendar.semester_of_date(date(2024, 4, 30)), 2);
+        assert_eq!(calendar.semester_of_date(date(2024, 5, 1)), 1);
+        assert_eq!(calendar.semester_of_date(date(2024, 10, 31)), 1);
+        assert_eq!(calendar.semester_of_date(date(2024, 11, 1)), 2);
+    }
+
+    #[test]
+    fn later_year_start() {
+        let calendar = june_calendar();
+
+        assert_eq!(calendar.academic_year_of_date(date(2024, 5, 31)), 2023);
+        assert_eq!(calendar.academic_year_of_date(date(2024, 6, 1)), 2024);
+        assert_eq!(calendar.semester_of_date(date(2024, 5, 31)), 2);
+        assert_eq!(calendar.semester_of_date(date(2024, 6, 1)), 1);
+        assert_eq!(calendar.semester_of_date(date(2025, 1, 14)), 1);
+        assert_eq!(calendar.semester_of_date(date(2025, 1, 15)), 2);
+    }
+
+    #[test]
+    fn dates_before_the_year_start_fall_into_the_next_calendar_year() {
+        let calendar = june_calendar();
+
+        assert_eq!(calendar.date_in_year(2024, (1, 15)), date(2025, 1, 15));
+        assert_eq!(calendar.date_in_year(2024, (5, 31)), date(2025, 5, 31));
+        assert_eq!(calendar.date_in_year(2024, (6, 1)), date(2024, 6, 1));
+        assert_eq!(calendar.date_in_year(2024, (12, 31)), date(2024, 12, 31));
+    }
+
+    #[test]
+    fn year_flips_at_local_midnight() {
+        let calendar = AcademicCalendar::default();
+
+        // 17:00 UTC on april 30th is midnight on may 1st in UTC+7
+        let before = date(2024, 4, 30).and_hms_opt(16, 59, 59).unwrap().and_utc();
+        let after = date(2024, 4, 30).and_hms_opt(17, 0, 0).unwrap().and_utc();
+
+        assert_eq!(calendar.academic_year_of(before), 2023);
+        assert_eq!(calendar.academic_year_of(after), 2024);
     }
 }
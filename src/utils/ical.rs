@@ -0,0 +1,131 @@
+use chrono::{DateTime, Utc};
+
+// a minimal RFC 5545 writer, only what is needed to publish events to calendar apps
+pub struct Calendar {
+    lines: Vec<String>,
+}
+
+pub struct CalendarEvent<'a> {
+    pub uid: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub summary: &'a str,
+    pub description: Option<&'a str>,
+    pub location: Option<&'a str>,
+    pub last_modified: DateTime<Utc>,
+}
+
+impl Calendar {
+    pub fn new(name: &str) -> Self {
+        let mut calendar = Calendar { lines: vec![] };
+
+        calendar.push("BEGIN", "VCALENDAR");
+        calendar.push("VERSION", "2.0");
+        calendar.push("PRODID", "-//MySK//Club Registrar//EN");
+        calendar.push("CALSCALE", "GREGORIAN");
+        calendar.push("METHOD", "PUBLISH");
+        calendar.push("X-WR-CALNAME", &escape(name));
+
+        calendar
+    }
+
+    pub fn add_event(&mut self, event: &CalendarEvent) {
+        self.push("BEGIN", "VEVENT");
+        self.push("UID", &escape(&event.uid));
+        self.push("DTSTAMP", &format_date_time(event.last_modified));
+        self.push("LAST-MODIFIED", &format_date_time(event.last_modified));
+        self.push("DTSTART", &format_date_time(event.starts_at));
+        self.push("DTEND", &format_date_time(event.ends_at));
+        self.push("SUMMARY", &escape(event.summary));
+
+        if let Some(description) = event.description {
+            self.push("DESCRIPTION", &escape(description));
+        }
+
+        if let Some(location) = event.location {
+            self.push("LOCATION", &escape(location));
+        }
+
+        self.push("END", "VEVENT");
+    }
+
+    pub fn finish(mut self) -> String {
+        self.push("END", "VCALENDAR");
+
+        let mut res = String::new();
+
+        for line in self.lines {
+            res.push_str(&fold(&line));
+            res.push_str("\r\n");
+        }
+
+        res
+    }
+
+    fn push(&mut self, name: &str, value: &str) {
+        self.lines.push(format!("{name}:{value}"));
+    }
+}
+
+fn format_date_time(at: DateTime<Utc>) -> String {
+    at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+// lines are limited to 75 octets, longer ones continue on the next line after a space,
+// never splitting a multi byte character (thai text is 3 bytes a character)
+fn fold(line: &str) -> String {
+    let mut res = String::new();
+    // the leading space of a continuation line counts towards its length
+    let mut length = 0;
+
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            res.push_str("\r\n ");
+            length = 1;
+        }
+
+        res.push(c);
+        length += c.len_utf8();
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_special_characters() {
+        assert_eq!(escape("a\\b;c,d\r\ne\nf"), r"a\\b\;c\,d\ne\nf");
+    }
+
+    #[test]
+    fn fold_short_line_untouched() {
+        assert_eq!(fold("SUMMARY:ชมรม"), "SUMMARY:ชมรม");
+    }
+
+    #[test]
+    fn fold_thai_line() {
+        let line = format!("SUMMARY:{}", "ชมรมดนตรีไทย".repeat(10));
+        let folded = fold(&line);
+
+        // folding is undone by removing every line break followed by a space
+        assert_eq!(folded.replace("\r\n ", ""), line);
+
+        for part in folded.split("\r\n") {
+            assert!(part.len() <= 75, "{} octets: {part}", part.len());
+        }
+
+        assert!(folded.split("\r\n").count() > 1);
+    }
+}
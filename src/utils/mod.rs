@@ -1,2 +1,5 @@
 // pub(crate) mod memory;
 pub(crate) mod date;
+pub(crate) mod ical;
+pub(crate) mod pdf;
+pub(crate) mod random;
@@ -0,0 +1,319 @@
+use std::env;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::time::Duration;
+
+use printpdf::{
+    image_crate::{self, DynamicImage, GenericImageView},
+    path::PaintMode,
+    Color, Image, ImageTransform, IndirectFontRef, Line, Mm, PdfDocument, PdfDocumentReference,
+    PdfLayerReference, Point, Pt, Rect, Rgb,
+};
+
+// pdfs are drawn with a font loaded from PDF_FONT_PATH, none of the builtin pdf fonts has
+// thai glyphs, so something like Sarabun or Noto Sans Thai has to be provided
+pub struct PdfFont {
+    bytes: Vec<u8>,
+}
+
+impl PdfFont {
+    // None when PDF_FONT_PATH is not set, the pdf endpoints are then unavailable
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let path = match env::var("PDF_FONT_PATH") {
+            Ok(path) if !path.trim().is_empty() => path,
+            _ => return Ok(None),
+        };
+
+        let bytes = std::fs::read(&path)
+            .map_err(|e| format!("PDF_FONT_PATH ({path}) can't be read: {e}"))?;
+
+        if let Err(e) = ttf_parser::Face::parse(&bytes, 0) {
+            return Err(format!(
+                "PDF_FONT_PATH ({path}) is not a truetype font: {e}"
+            ));
+        }
+
+        Ok(Some(Self { bytes }))
+    }
+}
+
+// A4 pages drawn top down, y is measured from the top edge unlike in printpdf
+pub struct PdfCanvas<'a> {
+    document: PdfDocumentReference,
+    layer: PdfLayerReference,
+    font: IndirectFontRef,
+    face: ttf_parser::Face<'a>,
+    width: f32,
+    height: f32,
+}
+
+impl<'a> PdfCanvas<'a> {
+    pub fn new(title: &str, font: &'a PdfFont, landscape: bool) -> Result<Self, printpdf::Error> {
+        let (width, height) = match landscape {
+            true => (297.0, 210.0),
+            false => (210.0, 297.0),
+        };
+
+        let (document, page, layer) = PdfDocument::new(title, Mm(width), Mm(height), "content");
+        let layer = document.get_page(page).get_layer(layer);
+        let pdf_font = document.add_external_font(font.bytes.as_slice())?;
+        // checked when the font was loaded
+        let face = ttf_parser::Face::parse(&font.bytes, 0).unwrap();
+
+        Ok(Self {
+            document,
+            layer,
+            font: pdf_font,
+            face,
+            width,
+            height,
+        })
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    pub fn add_page(&mut self) {
+        let (page, layer) = self
+            .document
+            .add_page(Mm(self.width), Mm(self.height), "content");
+        self.layer = self.document.get_page(page).get_layer(layer);
+    }
+
+    // in mm, glyphs missing from the font count as nothing
+    pub fn text_width(&self, text: &str, size: f32) -> f32 {
+        let units_per_em = self.face.units_per_em() as f32;
+
+        let advance = text
+            .chars()
+            .filter_map(|c| self.face.glyph_index(c))
+            .filter_map(|glyph| self.face.glyph_hor_advance(glyph))
+            .map(|advance| advance as f32)
+            .sum::<f32>();
+
+        Mm::from(Pt(advance / units_per_em * size)).0
+    }
+
+    // y is the baseline
+    pub fn text(&self, text: &str, size: f32, x: f32, y: f32, color: &Rgb) {
+        self.layer.set_fill_color(Color::Rgb(color.clone()));
+        self.layer
+            .use_text(text, size, Mm(x), Mm(self.height - y), &self.font);
+    }
+
+    pub fn text_centered(&self, text: &str, size: f32, y: f32, color: &Rgb) {
+        let x = (self.width - self.text_width(text, size)) / 2.0;
+        self.text(text, size, x, y, color);
+    }
+
+    // cuts the text short with an ellipsis so table cells don't run into each other
+    pub fn text_clipped(&self, text: &str, size: f32, x: f32, y: f32, max_width: f32, color: &Rgb) {
+        if self.text_width(text, size) <= max_width {
+            return self.text(text, size, x, y, color);
+        }
+
+        let mut clipped = String::new();
+
+        for c in text.chars() {
+            clipped.push(c);
+
+            if self.text_width(&format!("{clipped}…"), size) > max_width {
+                clipped.pop();
+                break;
+            }
+        }
+
+        self.text(&format!("{clipped}…"), size, x, y, color);
+    }
+
+    pub fn fill_rect(&self, x: f32, y: f32, width: f32, height: f32, color: &Rgb) {
+        self.layer.set_fill_color(Color::Rgb(color.clone()));
+        self.layer.add_rect(
+            Rect::new(
+                Mm(x),
+                Mm(self.height - y - height),
+                Mm(x + width),
+                Mm(self.height - y),
+            )
+            .with_mode(PaintMode::Fill),
+        );
+    }
+
+    pub fn stroke_rect(
+        &self,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        thickness: f32,
+        color: &Rgb,
+    ) {
+        self.layer.set_outline_color(Color::Rgb(color.clone()));
+        self.layer.set_outline_thickness(thickness);
+        self.layer.add_rect(
+            Rect::new(
+                Mm(x),
+                Mm(self.height - y - height),
+                Mm(x + width),
+                Mm(self.height - y),
+            )
+            .with_mode(PaintMode::Stroke),
+        );
+    }
+
+    pub fn line(&self, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: &Rgb) {
+        self.layer.set_outline_color(Color::Rgb(color.clone()));
+        self.layer.set_outline_thickness(thickness);
+        self.layer.add_line(Line {
+            points: vec![
+                (Point::new(Mm(x1), Mm(self.height - y1)), false),
+                (Point::new(Mm(x2), Mm(self.height - y2)), false),
+            ],
+            is_closed: false,
+        });
+    }
+
+    // scaled down to fit in the box, centered on x, with its top at y
+    pub fn image(
+        &self,
+        image: &DynamicImage,
+        center_x: f32,
+        y: f32,
+        max_width: f32,
+        max_height: f32,
+    ) {
+        let dpi = 300.0;
+        let (pixel_width, pixel_height) = image.dimensions();
+        let width = pixel_width as f32 / dpi * 25.4;
+        let height = pixel_height as f32 / dpi * 25.4;
+        let scale = (max_width / width).min(max_height / height);
+
+        Image::from_dynamic_image(image).add_to_layer(
+            self.layer.clone(),
+            ImageTransform {
+                translate_x: Some(Mm(center_x - width * scale / 2.0)),
+                translate_y: Some(Mm(self.height - y - height * scale)),
+                scale_x: Some(scale),
+                scale_y: Some(scale),
+                dpi: Some(dpi),
+                ..Default::default()
+            },
+        );
+    }
+
+    pub fn finish(self) -> Result<Vec<u8>, printpdf::Error> {
+        self.document.save_to_bytes()
+    }
+}
+
+// colors are stored as #rrggbb like the frontend uses them
+pub fn parse_hex_color(color: &str) -> Option<Rgb> {
+    let hex = color.trim().trim_start_matches('#');
+
+    if hex.len() != 6 {
+        return None;
+    }
+
+    let channel = |index: usize| {
+        u8::from_str_radix(&hex[index..index + 2], 16)
+            .ok()
+            .map(|value| value as f32 / 255.0)
+    };
+
+    Some(Rgb::new(channel(0)?, channel(2)?, channel(4)?, None))
+}
+
+// logos are far smaller than this, anything bigger is not worth waiting for
+const MAX_IMAGE_BYTES: usize = 2 * 1024 * 1024;
+
+// logo urls are set by club staff, so only https urls on public addresses are fetched, the
+// address is resolved once and pinned so the request can't be pointed elsewhere afterwards
+async fn public_address(url: &reqwest::Url) -> Option<SocketAddr> {
+    if url.scheme() != "https" {
+        return None;
+    }
+
+    let host = url.host_str()?.to_string();
+    let port = url.port_or_known_default()?;
+
+    let addresses = actix_rt::task::spawn_blocking(move || {
+        (host.as_str(), port)
+            .to_socket_addrs()
+            .map(|addresses| addresses.collect::<Vec<SocketAddr>>())
+    })
+    .await
+    .ok()?
+    .ok()?;
+
+    // every address has to be public, not just the first one
+    if addresses.is_empty() || !addresses.iter().all(|address| is_public(&address.ip())) {
+        return None;
+    }
+
+    addresses.into_iter().next()
+}
+
+fn is_public(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_unspecified()
+                // carrier grade nat, 100.64.0.0/10
+                || (ip.octets()[0] == 100 && (ip.octets()[1] & 0xc0) == 64))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(&IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    // unique local, fc00::/7
+                    || (first & 0xfe00) == 0xfc00
+                    // link local, fe80::/10
+                    || (first & 0xffc0) == 0xfe80)
+            }
+        },
+    }
+}
+
+// a logo that can't be fetched or decoded is left out rather than failing the whole document
+pub async fn fetch_image(url: &str) -> Option<DynamicImage> {
+    let url = reqwest::Url::parse(url).ok()?;
+    let address = public_address(&url).await?;
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        // a redirect could lead to an address that was never checked
+        .redirect(reqwest::redirect::Policy::none())
+        .resolve(url.host_str()?, address)
+        .build()
+        .ok()?;
+
+    let mut response = client.get(url).send().await.ok()?.error_for_status().ok()?;
+
+    if response.content_length().unwrap_or(0) > MAX_IMAGE_BYTES as u64 {
+        return None;
+    }
+
+    // the content length can be missing or wrong, so the body is read in chunks and cut off
+    let mut bytes = vec![];
+
+    while let Some(chunk) = response.chunk().await.ok()? {
+        if bytes.len() + chunk.len() > MAX_IMAGE_BYTES {
+            return None;
+        }
+
+        bytes.extend_from_slice(&chunk);
+    }
+
+    image_crate::load_from_memory(&bytes).ok()
+}